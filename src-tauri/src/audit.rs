@@ -0,0 +1,219 @@
+use crate::config::EditResult;
+use crate::dev_certs::DevCertificateResult;
+use crate::logs::{append_log_entry, capture_log_context, LogContext, LogKind, StoredLogEntry};
+use crate::nginx::{OperationResult, ProcessOperationResult};
+use crate::settings::read_app_settings;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+/// 后端写入操作日志后推送给前端的事件名
+pub const OPERATION_LOG_EVENT: &str = "operation-log-appended";
+
+/// 命令执行成功时记录的退出状态
+const EXIT_STATUS_SUCCESS: i32 = 0;
+/// 命令执行失败（业务失败或返回错误）时记录的退出状态
+const EXIT_STATUS_FAILURE: i32 = 1;
+
+static ENTRY_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// 可被审计层识别成功与否的命令返回值
+pub trait AuditOutcome {
+    fn is_success(&self) -> bool;
+    fn message(&self) -> &str;
+}
+
+impl AuditOutcome for OperationResult {
+    fn is_success(&self) -> bool {
+        self.success
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl AuditOutcome for ProcessOperationResult {
+    fn is_success(&self) -> bool {
+        self.success
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl AuditOutcome for DevCertificateResult {
+    fn is_success(&self) -> bool {
+        self.success
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl AuditOutcome for EditResult {
+    fn is_success(&self) -> bool {
+        self.success
+    }
+
+    fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn next_entry_id(timestamp_ms: i64, command: &str) -> String {
+    let sequence = ENTRY_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+    format!("{}-{}-{}", timestamp_ms, command, sequence)
+}
+
+/// 根据命令返回值生成日志级别、摘要与退出状态
+fn summarize_outcome<T: AuditOutcome>(label: &str, result: &Result<T, String>) -> (&'static str, String, i32) {
+    match result {
+        Ok(outcome) if outcome.is_success() => {
+            let message = outcome.message().trim();
+            let summary = if message.is_empty() {
+                format!("{}成功", label)
+            } else {
+                message.to_string()
+            };
+            ("success", summary, EXIT_STATUS_SUCCESS)
+        }
+        Ok(outcome) => (
+            "error",
+            format!("{}失败: {}", label, outcome.message().trim()),
+            EXIT_STATUS_FAILURE,
+        ),
+        Err(error) => (
+            "error",
+            format!("{}失败: {}", label, error.trim()),
+            EXIT_STATUS_FAILURE,
+        ),
+    }
+}
+
+fn new_operation_entry(source: &str, level: &str, summary: String, context: LogContext) -> StoredLogEntry {
+    let timestamp_ms = now_timestamp_ms();
+
+    StoredLogEntry {
+        id: next_entry_id(timestamp_ms, source),
+        level: level.to_string(),
+        summary,
+        timestamp_ms,
+        kind: LogKind::Operation,
+        detail: None,
+        context: Some(context),
+    }
+}
+
+fn build_audit_entry<T: AuditOutcome>(
+    command: &str,
+    label: &str,
+    result: &Result<T, String>,
+    duration: Duration,
+) -> StoredLogEntry {
+    let (level, summary, exit_status) = summarize_outcome(label, result);
+    let context = capture_log_context(command, Some(duration), Some(exit_status));
+    new_operation_entry(command, level, summary, context)
+}
+
+/// 生成一条由后端自行产生（非用户命令触发）的操作日志
+pub fn build_system_log_entry(source: &str, level: &str, summary: String) -> StoredLogEntry {
+    new_operation_entry(source, level, summary, capture_log_context(source, None, None))
+}
+
+/// 按当前保留策略持久化一条日志
+fn persist_log_entry(entry: StoredLogEntry) -> Result<(), String> {
+    let retention_days = read_app_settings()
+        .map(|settings| settings.log_retention_days)
+        .unwrap_or(30);
+
+    append_log_entry(entry, retention_days).map_err(|error| format!("写入操作日志失败: {}", error))
+}
+
+/// 持久化一条后端生成的日志，并通过事件推送给前端；落盘失败时额外推送一条错误日志
+pub fn publish_log_entry(app: &AppHandle, entry: StoredLogEntry) {
+    let persisted = persist_log_entry(entry.clone());
+    let _ = app.emit(OPERATION_LOG_EVENT, entry);
+
+    if let Err(error) = persisted {
+        let _ = app.emit(OPERATION_LOG_EVENT, build_system_log_entry("audit", "error", error));
+    }
+}
+
+/// 执行命令并自动记录操作日志，无论前端是否仍在等待结果
+pub async fn audited<T, F>(
+    app: &AppHandle,
+    command: &str,
+    label: &str,
+    operation: F,
+) -> Result<T, String>
+where
+    T: AuditOutcome,
+    F: Future<Output = Result<T, String>>,
+{
+    let started_at = Instant::now();
+    let result = operation.await;
+    let entry = build_audit_entry(command, label, &result, started_at.elapsed());
+
+    publish_log_entry(app, entry);
+    result
+}
+
+/// 无窗口（命令行）模式下执行命令，只落盘操作日志，不推送事件；
+/// 第二个返回值为日志落盘失败的原因，由调用方输出
+pub async fn audited_headless<T, F>(command: &str, label: &str, operation: F) -> (Result<T, String>, Option<String>)
+where
+    T: AuditOutcome,
+    F: Future<Output = Result<T, String>>,
+{
+    let started_at = Instant::now();
+    let result = operation.await;
+    let log_error = persist_log_entry(build_audit_entry(command, label, &result, started_at.elapsed())).err();
+    (result, log_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_outcome_should_map_success_and_failures() {
+        let success: Result<OperationResult, String> = Ok(OperationResult {
+            success: true,
+            message: "✓ Nginx 启动成功".to_string(),
+        });
+        let failure: Result<OperationResult, String> = Ok(OperationResult {
+            success: false,
+            message: "Nginx 已经在运行中".to_string(),
+        });
+        let error: Result<OperationResult, String> = Err("读取配置文件失败".to_string());
+
+        assert_eq!(
+            summarize_outcome("Nginx 启动", &success),
+            ("success", "✓ Nginx 启动成功".to_string(), EXIT_STATUS_SUCCESS)
+        );
+        assert_eq!(
+            summarize_outcome("Nginx 启动", &failure),
+            ("error", "Nginx 启动失败: Nginx 已经在运行中".to_string(), EXIT_STATUS_FAILURE)
+        );
+        assert_eq!(summarize_outcome("Nginx 启动", &error).2, EXIT_STATUS_FAILURE);
+    }
+
+    #[test]
+    fn next_entry_id_should_be_unique_for_same_timestamp() {
+        let first = next_entry_id(1, "reload_nginx");
+        let second = next_entry_id(1, "reload_nginx");
+
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,433 @@
+use crate::config::{delete_temp_config, parse_nginx_config, write_temp_config_for_validation, Directive, LocationBlock, ServerBlock};
+use crate::config_watcher::{
+    flatten_config, is_config_file_of, keyed_servers, normalize_watch_path, server_ref, ConfigStructureSummary,
+    ServerBlockRef,
+};
+use crate::nginx::test_nginx_config_file;
+use crate::port_preflight::{parse_listen_address, ListenProtocol};
+use crate::settings::read_app_settings;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+const DIFF_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedFileChange {
+    pub file_path: String,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedLocationChange {
+    pub server: ServerBlockRef,
+    pub kind: PlanChangeKind,
+    pub modifier: Option<String>,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanValidation {
+    /// 内容无变化时不执行校验；未设置 Nginx 路径或无法写入临时文件时同样未执行，但 success 为 false
+    pub performed: bool,
+    pub success: bool,
+    pub message: String,
+}
+
+/// 重载后监听与 server 状态的变化
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReloadImpact {
+    pub requires_reload: bool,
+    pub listeners_added: Vec<String>,
+    pub listeners_removed: Vec<String>,
+    pub servers_enabled: Vec<ServerBlockRef>,
+    pub servers_disabled: Vec<ServerBlockRef>,
+}
+
+/// 编辑操作的结构化变更计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePlan {
+    pub config_path: String,
+    pub files: Vec<PlannedFileChange>,
+    pub servers: ConfigStructureSummary,
+    pub locations: Vec<PlannedLocationChange>,
+    /// 统一格式 diff
+    pub diff: String,
+    pub validation: PlanValidation,
+    pub reload_impact: ReloadImpact,
+    pub new_content: String,
+}
+
+const IMPLICIT_LISTEN: &str = "80";
+
+fn listener_label(listen: &str) -> String {
+    match parse_listen_address(listen) {
+        Some(address) => format!(
+            "{}:{}{}",
+            address.host.as_deref().unwrap_or("*"),
+            address.port,
+            if address.protocol == ListenProtocol::Udp { "/udp" } else { "" }
+        ),
+        None => listen.split_whitespace().next().unwrap_or(listen).to_string(),
+    }
+}
+
+/// 已启用 server 的监听地址集合；未写 listen 的 server 按 nginx 默认的 *:80 计入
+/// （非 root 运行时实际为 *:8000）
+fn active_listeners(servers: &[(String, ServerBlock)]) -> BTreeSet<String> {
+    servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .flat_map(|(_, server)| {
+            if server.listen.is_empty() {
+                vec![listener_label(IMPLICIT_LISTEN)]
+            } else {
+                server.listen.iter().map(|listen| listener_label(listen)).collect()
+            }
+        })
+        .collect()
+}
+
+fn directive_pairs(directives: &[Directive]) -> Vec<(&str, &str)> {
+    directives
+        .iter()
+        .map(|directive| (directive.name.as_str(), directive.value.as_str()))
+        .collect()
+}
+
+fn same_location(a: &LocationBlock, b: &LocationBlock) -> bool {
+    a.path == b.path && a.modifier == b.modifier
+}
+
+fn location_changes(server: &ServerBlockRef, previous: &ServerBlock, current: &ServerBlock) -> Vec<PlannedLocationChange> {
+    let change = |kind, location: &LocationBlock| PlannedLocationChange {
+        server: server.clone(),
+        kind,
+        modifier: location.modifier.clone(),
+        path: location.path.clone(),
+    };
+    let mut changes = Vec::new();
+
+    for location in &current.locations {
+        match previous.locations.iter().find(|old| same_location(old, location)) {
+            None => changes.push(change(PlanChangeKind::Added, location)),
+            Some(old) if directive_pairs(&old.directives) != directive_pairs(&location.directives) => {
+                changes.push(change(PlanChangeKind::Modified, location))
+            }
+            Some(_) => {}
+        }
+    }
+    for location in &previous.locations {
+        if !current.locations.iter().any(|new| same_location(new, location)) {
+            changes.push(change(PlanChangeKind::Removed, location));
+        }
+    }
+    changes
+}
+
+/// 对比修改前后的内容，得出除校验外的变更计划
+pub(crate) fn summarize_changes(config_path: &str, before: &str, after: String) -> Result<ChangePlan, String> {
+    let previous = parse_nginx_config(before, config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let current = parse_nginx_config(&after, config_path).map_err(|e| format!("生成的配置无法解析: {}", e))?;
+    let previous: Vec<(String, ServerBlock)> = previous.servers.into_iter().map(|s| (config_path.to_string(), s)).collect();
+    let current: Vec<(String, ServerBlock)> = current.servers.into_iter().map(|s| (config_path.to_string(), s)).collect();
+
+    let previous_keyed = keyed_servers(&previous);
+    let current_keyed = keyed_servers(&current);
+    let mut matched = BTreeSet::new();
+    let mut pairs = Vec::new();
+    let mut unmatched = Vec::new();
+    for (key, (file_path, server)) in &current_keyed {
+        match previous_keyed.get(key) {
+            Some((_, old)) => {
+                matched.insert(key);
+                pairs.push((file_path, *old, *server));
+            }
+            None => unmatched.push((file_path, *server)),
+        }
+    }
+
+    let mut servers = ConfigStructureSummary::default();
+    for (file_path, server) in unmatched {
+        // 同一位置的 server 视为原地修改，例如修改了 listen 或 server_name
+        let same_place = previous_keyed
+            .iter()
+            .find(|(key, (_, old))| !matched.contains(key) && old.start_line == server.start_line);
+        match same_place {
+            Some((key, (_, old))) => {
+                matched.insert(key);
+                pairs.push((file_path, *old, server));
+            }
+            None => servers.added.push(server_ref(file_path, server)),
+        }
+    }
+    for (key, (file_path, old)) in &previous_keyed {
+        if !matched.contains(key) {
+            servers.removed.push(server_ref(file_path, old));
+        }
+    }
+
+    let mut locations = Vec::new();
+    let mut impact = ReloadImpact::default();
+    for (file_path, old, server) in pairs {
+        if old.raw_content.trim() == server.raw_content.trim() && old.enabled == server.enabled {
+            continue;
+        }
+        let reference = server_ref(file_path, server);
+        match (old.enabled, server.enabled) {
+            (false, true) => impact.servers_enabled.push(reference.clone()),
+            (true, false) => impact.servers_disabled.push(reference.clone()),
+            _ => {}
+        }
+        locations.extend(location_changes(&reference, old, server));
+        servers.changed.push(reference);
+    }
+
+    let listeners_before = active_listeners(&previous);
+    let listeners_after = active_listeners(&current);
+    impact.listeners_added = listeners_after.difference(&listeners_before).cloned().collect();
+    impact.listeners_removed = listeners_before.difference(&listeners_after).cloned().collect();
+    impact.requires_reload = before != after;
+
+    let text_diff = TextDiff::from_lines(before, after.as_str());
+    let mut files = Vec::new();
+    if before != after {
+        let (mut added_lines, mut removed_lines) = (0, 0);
+        for change in text_diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => added_lines += 1,
+                ChangeTag::Delete => removed_lines += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+        files.push(PlannedFileChange {
+            file_path: config_path.to_string(),
+            added_lines,
+            removed_lines,
+        });
+    }
+    let diff = text_diff
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(config_path, config_path)
+        .to_string();
+
+    Ok(ChangePlan {
+        config_path: config_path.to_string(),
+        files,
+        servers,
+        locations,
+        diff,
+        validation: PlanValidation {
+            performed: false,
+            success: true,
+            message: "配置无变化，无需校验".to_string(),
+        },
+        reload_impact: impact,
+        new_content: after,
+    })
+}
+
+/// 将内容写入原文件所在目录的临时文件并执行 nginx -t
+async fn test_content(nginx_path: &str, original_path: &str, content: String) -> PlanValidation {
+    let temp_path = match write_temp_config_for_validation(original_path.to_string(), content).await {
+        Ok(temp_path) => temp_path,
+        Err(message) => {
+            return PlanValidation {
+                performed: false,
+                success: false,
+                message,
+            }
+        }
+    };
+    let result = test_nginx_config_file(nginx_path.to_string(), temp_path.clone()).await;
+    let _ = delete_temp_config(temp_path).await;
+
+    match result {
+        Ok(result) => PlanValidation {
+            performed: true,
+            success: result.success,
+            message: result.message,
+        },
+        Err(message) => PlanValidation {
+            performed: true,
+            success: false,
+            message,
+        },
+    }
+}
+
+/// 执行 nginx -t 所用的 Nginx 路径与主配置文件
+pub(crate) struct ValidationTarget {
+    pub nginx_path: String,
+    pub main_config: String,
+}
+
+impl ValidationTarget {
+    pub(crate) fn from_settings() -> Self {
+        let settings = read_app_settings().unwrap_or_default();
+        Self {
+            nginx_path: settings.nginx_path,
+            main_config: settings.config_path,
+        }
+    }
+}
+
+/// 使用设置中的 Nginx 对一组文件修改（文件路径、新内容）执行 nginx -t
+pub(crate) async fn validate_changes(changes: &[(&str, &str)]) -> PlanValidation {
+    validate_changes_with(&ValidationTarget::from_settings(), changes).await
+}
+
+/// 使用指定的 Nginx 对一组文件修改执行 nginx -t；
+/// 涉及主配置 include 的文件时，展开主配置后整体校验
+pub(crate) async fn validate_changes_with(target: &ValidationTarget, changes: &[(&str, &str)]) -> PlanValidation {
+    if target.nginx_path.is_empty() {
+        return PlanValidation {
+            performed: false,
+            success: false,
+            message: "请先设置 Nginx 路径".to_string(),
+        };
+    }
+
+    let main_config = &target.main_config;
+    let main_path = normalize_watch_path(Path::new(&main_config));
+    let touches_includes = !main_config.trim().is_empty()
+        && changes.iter().any(|(path, _)| normalize_watch_path(Path::new(path)) != main_path)
+        && changes.iter().all(|(path, _)| is_config_file_of(main_config, path));
+    if touches_includes {
+        return match flatten_config(main_config, changes) {
+            Ok(content) => test_content(&target.nginx_path, main_config, content).await,
+            Err(message) => PlanValidation {
+                performed: false,
+                success: false,
+                message,
+            },
+        };
+    }
+
+    let mut validation = PlanValidation {
+        performed: false,
+        success: true,
+        message: "配置无变化，无需校验".to_string(),
+    };
+    for (path, content) in changes {
+        validation = test_content(&target.nginx_path, path, content.to_string()).await;
+        if !validation.success {
+            break;
+        }
+    }
+    validation
+}
+
+/// 计划校验未通过时返回错误，写入配置前调用；只有内容无变化而未执行校验时放行
+pub(crate) fn ensure_plan_valid(validation: &PlanValidation) -> Result<(), String> {
+    if !validation.success {
+        return Err(format!("配置校验未通过，未写入文件:\n{}", validation.message));
+    }
+    Ok(())
+}
+
+/// 生成变更计划并在内容变化时使用设置中的 Nginx 执行校验
+pub(crate) async fn build_change_plan(config_path: &str, before: &str, after: String) -> Result<ChangePlan, String> {
+    build_change_plan_with(&ValidationTarget::from_settings(), config_path, before, after).await
+}
+
+/// 生成变更计划并在内容变化时使用指定的 Nginx 执行校验
+pub(crate) async fn build_change_plan_with(
+    target: &ValidationTarget,
+    config_path: &str,
+    before: &str,
+    after: String,
+) -> Result<ChangePlan, String> {
+    let mut plan = summarize_changes(config_path, before, after)?;
+    if plan.reload_impact.requires_reload {
+        plan.validation = validate_changes_with(target, &[(config_path, &plan.new_content)]).await;
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{build_toggle_server_state_content, build_update_server_content};
+
+    const CONFIG: &str = "events {}\nhttp {\n    server {\n        listen 80;\n        server_name a.test;\n        location / {\n            root html;\n        }\n        location /old/ {\n            return 410;\n        }\n    }\n    server {\n        listen 8080;\n        server_name b.test;\n    }\n}\n";
+
+    #[test]
+    fn update_plan_should_report_locations_listeners_and_diff() {
+        let config = parse_nginx_config(CONFIG, "nginx.conf").unwrap();
+        let server_text = "    server {\n        listen 80;\n        listen 443 ssl;\n        server_name a.test;\n        location / {\n            root /srv/www;\n        }\n        location /api/ {\n            proxy_pass http://127.0.0.1:3000;\n        }\n    }";
+        let after = build_update_server_content(CONFIG, "nginx.conf", &config.servers[0].id, server_text).unwrap();
+        let plan = summarize_changes("nginx.conf", CONFIG, after).unwrap();
+
+        assert_eq!(plan.servers.changed.len(), 1);
+        assert!(plan.servers.added.is_empty() && plan.servers.removed.is_empty());
+        let locations: Vec<(PlanChangeKind, &str)> =
+            plan.locations.iter().map(|change| (change.kind, change.path.as_str())).collect();
+        assert_eq!(
+            locations,
+            vec![
+                (PlanChangeKind::Modified, "/"),
+                (PlanChangeKind::Added, "/api/"),
+                (PlanChangeKind::Removed, "/old/"),
+            ]
+        );
+        assert_eq!(plan.reload_impact.listeners_added, vec!["*:443"]);
+        assert!(plan.reload_impact.listeners_removed.is_empty());
+        assert!(plan.reload_impact.requires_reload);
+        assert_eq!(plan.files.len(), 1);
+        assert!(plan.diff.starts_with("--- nginx.conf\n+++ nginx.conf\n@@"));
+        assert!(plan.diff.contains("+        listen 443 ssl;\n"));
+        assert!(plan.diff.contains("-            return 410;\n"));
+    }
+
+    #[test]
+    fn toggle_plan_should_report_disabled_server_and_removed_listener() {
+        let config = parse_nginx_config(CONFIG, "nginx.conf").unwrap();
+        let after = build_toggle_server_state_content(CONFIG, "nginx.conf", &config.servers[1].id, false).unwrap();
+        let plan = summarize_changes("nginx.conf", CONFIG, after).unwrap();
+
+        assert_eq!(plan.servers.changed.len(), 1);
+        assert_eq!(plan.reload_impact.servers_disabled.len(), 1);
+        assert_eq!(plan.reload_impact.servers_disabled[0].server_name, vec!["b.test"]);
+        assert_eq!(plan.reload_impact.listeners_removed, vec!["*:8080"]);
+        assert!(plan.locations.is_empty());
+
+        let unchanged = summarize_changes("nginx.conf", CONFIG, CONFIG.to_string()).unwrap();
+        assert!(!unchanged.reload_impact.requires_reload);
+        assert!(unchanged.files.is_empty());
+        assert!(unchanged.diff.is_empty());
+        assert!(unchanged.validation.success);
+    }
+
+    #[test]
+    fn listeners_should_include_implicit_default_listen() {
+        let before = "http {\n    server {\n        server_name a.test;\n    }\n}\n";
+        let config = parse_nginx_config(before, "nginx.conf").unwrap();
+        let after = build_toggle_server_state_content(before, "nginx.conf", &config.servers[0].id, false).unwrap();
+        let plan = summarize_changes("nginx.conf", before, after).unwrap();
+
+        assert_eq!(plan.reload_impact.listeners_removed, vec!["*:80"]);
+        assert!(ensure_plan_valid(&PlanValidation {
+            performed: false,
+            success: false,
+            message: "请先设置 Nginx 路径".to_string(),
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,681 @@
+use crate::audit::audited_headless;
+use crate::change_plan::{build_change_plan_with, ValidationTarget};
+use crate::config::{build_toggle_server_state_content, write_config_file, EditResult};
+use crate::config_watcher::{parse_config_servers, resolve_config_files, server_ref};
+use crate::logs::{
+    append_log_entry, capture_log_context, load_operation_logs, FileChangeLogDetail, FileChangeScopeDiff, LogKind,
+    StoredLogEntry,
+};
+use crate::nginx::{check_nginx_status, query_nginx_version, reload_nginx_headless, test_nginx_config, test_nginx_config_file};
+use crate::nginx_lint::{lint_nginx_config, LintSeverity};
+use crate::settings::read_app_settings;
+use chrono::{Local, TimeZone};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 命令执行成功
+const EXIT_OK: i32 = 0;
+/// 命令执行了但结果为失败（校验不通过、存在 error 级别问题等）
+const EXIT_FAILURE: i32 = 1;
+/// 参数错误
+const EXIT_USAGE: i32 = 2;
+/// Nginx 未运行，与 `systemctl status` 的约定保持一致
+const EXIT_NOT_RUNNING: i32 = 3;
+
+const USAGE: &str = "用法: nginx-config-manager [--json] [--config <配置文件>] [--nginx <Nginx 路径>] <命令>
+
+命令:
+  status                          查看 Nginx 运行状态
+  test                            校验 Nginx 配置
+  reload                          重新加载配置
+  servers list                    列出配置及 include 文件中的 server
+  server enable <id> [--file <文件>]   启用 server
+  server disable <id> [--file <文件>]  停用 server
+  lint                            对已启用的 server 执行 lint 检查
+  backup list                     列出可回滚的配置文件修改记录
+  backup restore [<记录 id>]      将配置文件回滚到修改前的内容（默认最近一次）
+
+未指定 --config / --nginx 时使用应用设置中的路径；--json 输出 JSON 便于脚本处理。";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CliOptions {
+    json: bool,
+    config_path: Option<String>,
+    nginx_path: Option<String>,
+    file_path: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum CliCommand {
+    Help,
+    Status,
+    Test,
+    Reload,
+    ServersList,
+    ServerToggle { server_id: String, enabled: bool },
+    Lint,
+    BackupList,
+    BackupRestore { entry_id: Option<String> },
+}
+
+struct CommandOutput {
+    exit_code: i32,
+    data: Value,
+    text: String,
+    /// 不影响结果的警告（如日志落盘失败），输出到 stderr
+    warnings: Vec<String>,
+}
+
+impl CommandOutput {
+    fn new(exit_code: i32, data: Value, text: String) -> Self {
+        Self {
+            exit_code,
+            data,
+            text,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn with_warnings(mut self, warnings: impl IntoIterator<Item = Option<String>>) -> Self {
+        self.warnings.extend(warnings.into_iter().flatten());
+        self
+    }
+}
+
+/// 命令实际使用的路径：命令行参数优先，其次为应用设置
+struct CliContext {
+    nginx_path: String,
+    config_path: String,
+    config_overridden: bool,
+    file_path: Option<String>,
+}
+
+impl CliContext {
+    fn resolve(options: &CliOptions) -> Result<Self, String> {
+        let settings = read_app_settings()?;
+        Ok(Self {
+            nginx_path: options.nginx_path.clone().unwrap_or(settings.nginx_path),
+            config_path: options.config_path.clone().unwrap_or(settings.config_path),
+            config_overridden: options.config_path.is_some(),
+            file_path: options.file_path.clone(),
+        })
+    }
+
+    fn require_config_path(&self) -> Result<&str, String> {
+        if self.config_path.trim().is_empty() {
+            return Err("未设置配置文件路径，请使用 --config 指定".to_string());
+        }
+        Ok(&self.config_path)
+    }
+
+    /// 写入配置前的 nginx -t 校验使用命令行指定的 Nginx 与主配置文件
+    fn validation_target(&self) -> Result<ValidationTarget, String> {
+        if self.nginx_path.trim().is_empty() {
+            return Err("未设置 Nginx 路径，无法校验配置，请使用 --nginx 指定".to_string());
+        }
+        Ok(ValidationTarget {
+            nginx_path: self.nginx_path.clone(),
+            main_config: self.config_path.clone(),
+        })
+    }
+}
+
+fn take_value(args: &[String], index: usize, flag: &str) -> Result<String, String> {
+    args.get(index + 1)
+        .filter(|value| !value.starts_with("--"))
+        .cloned()
+        .ok_or_else(|| format!("{} 需要一个参数", flag))
+}
+
+fn parse_args(args: &[String]) -> Result<(CliOptions, CliCommand), String> {
+    let mut options = CliOptions::default();
+    let mut positional = Vec::new();
+    let mut index = 0;
+
+    while index < args.len() {
+        let arg = args[index].as_str();
+        match arg {
+            "--json" => options.json = true,
+            "-h" | "--help" => return Ok((options, CliCommand::Help)),
+            "--config" | "--nginx" | "--file" => {
+                let value = take_value(args, index, arg)?;
+                match arg {
+                    "--config" => options.config_path = Some(value),
+                    "--nginx" => options.nginx_path = Some(value),
+                    _ => options.file_path = Some(value),
+                }
+                index += 1;
+            }
+            _ if arg.starts_with("--") => return Err(format!("未知参数: {}", arg)),
+            _ => positional.push(arg),
+        }
+        index += 1;
+    }
+
+    let command = match positional.as_slice() {
+        [] | ["help"] => CliCommand::Help,
+        ["status"] => CliCommand::Status,
+        ["test"] => CliCommand::Test,
+        ["reload"] => CliCommand::Reload,
+        ["servers", "list"] => CliCommand::ServersList,
+        ["server", action @ ("enable" | "disable"), server_id] => CliCommand::ServerToggle {
+            server_id: server_id.to_string(),
+            enabled: *action == "enable",
+        },
+        ["lint"] => CliCommand::Lint,
+        ["backup", "list"] => CliCommand::BackupList,
+        ["backup", "restore"] => CliCommand::BackupRestore { entry_id: None },
+        ["backup", "restore", entry_id] => CliCommand::BackupRestore {
+            entry_id: Some(entry_id.to_string()),
+        },
+        _ => return Err(format!("无法识别的命令: {}", positional.join(" "))),
+    };
+
+    Ok((options, command))
+}
+
+/// 存在位置参数或帮助参数时视为命令行调用，仅有系统附加的 `-psn_*` 之类参数时仍启动窗口
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| !arg.starts_with('-') || arg == "-h" || arg == "--help" || arg == "--json")
+}
+
+/// 字符显示宽度，中日韩全角字符按 2 列计算
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|ch| if ch >= '\u{1100}' && !ch.is_ascii() { 2 } else { 1 })
+        .sum()
+}
+
+fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|header| display_width(header)).collect();
+    for row in rows {
+        for (column, cell) in row.iter().enumerate() {
+            widths[column] = widths[column].max(display_width(cell));
+        }
+    }
+
+    let render_row = |cells: Vec<&str>| {
+        let line: Vec<String> = cells
+            .iter()
+            .enumerate()
+            .map(|(column, cell)| format!("{}{}", cell, " ".repeat(widths[column] - display_width(cell))))
+            .collect();
+        line.join("  ").trim_end().to_string()
+    };
+
+    let separators: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    let mut lines = vec![render_row(headers.to_vec())];
+    lines.push(render_row(separators.iter().map(String::as_str).collect()));
+    lines.extend(rows.iter().map(|row| render_row(row.iter().map(String::as_str).collect())));
+    lines.join("\n")
+}
+
+fn format_timestamp(timestamp_ms: i64) -> String {
+    Local
+        .timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| timestamp_ms.to_string())
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// 与界面保存时一致地记录文件修改，便于通过 `backup restore` 撤销；返回落盘失败的原因
+fn record_file_change(
+    command: &str,
+    operation_label: &str,
+    config_path: &str,
+    before: String,
+    after: String,
+) -> Option<String> {
+    if before == after {
+        return None;
+    }
+
+    let file_name = Path::new(config_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| config_path.to_string());
+    let timestamp_ms = now_timestamp_ms();
+    let entry = StoredLogEntry {
+        id: format!("{}-{}", timestamp_ms, command),
+        level: "success".to_string(),
+        summary: format!("{} · {}", operation_label, file_name),
+        timestamp_ms,
+        kind: LogKind::FileChange,
+        detail: Some(FileChangeLogDetail {
+            operation_label: operation_label.to_string(),
+            config_path: config_path.to_string(),
+            saved_at: timestamp_ms,
+            file_diff: FileChangeScopeDiff {
+                label: "文件".to_string(),
+                before,
+                after,
+            },
+            server_diff: None,
+            location_diffs: Vec::new(),
+        }),
+        context: Some(capture_log_context(command, None, None)),
+    };
+    let retention_days = read_app_settings()
+        .map(|settings| settings.log_retention_days)
+        .unwrap_or(30);
+
+    append_log_entry(entry, retention_days)
+        .err()
+        .map(|error| format!("写入文件修改记录失败: {}", error))
+}
+
+async fn run_status(context: &CliContext) -> Result<CommandOutput, String> {
+    let status = check_nginx_status().await?;
+    let version = query_nginx_version(&context.nginx_path);
+    let exit_code = if status.is_running { EXIT_OK } else { EXIT_NOT_RUNNING };
+    let text = render_table(
+        &["项目", "值"],
+        &[
+            vec!["运行状态".to_string(), if status.is_running { "运行中" } else { "未运行" }.to_string()],
+            vec!["进程数".to_string(), status.process_count.to_string()],
+            vec!["版本".to_string(), version.clone().unwrap_or_else(|| "-".to_string())],
+            vec!["Nginx 路径".to_string(), context.nginx_path.clone()],
+            vec!["配置文件".to_string(), context.config_path.clone()],
+            vec!["说明".to_string(), status.message.clone()],
+        ],
+    );
+    let data = json!({
+        "status": status,
+        "version": version,
+        "nginxPath": context.nginx_path,
+        "configPath": context.config_path,
+    });
+
+    Ok(CommandOutput::new(exit_code, data, text))
+}
+
+async fn run_test(context: &CliContext) -> Result<CommandOutput, String> {
+    let result = if context.config_overridden {
+        test_nginx_config_file(context.nginx_path.clone(), context.config_path.clone()).await?
+    } else {
+        test_nginx_config(context.nginx_path.clone()).await?
+    };
+    let exit_code = if result.success { EXIT_OK } else { EXIT_FAILURE };
+    let text = result.message.clone();
+
+    Ok(CommandOutput::new(exit_code, json!(result), text))
+}
+
+async fn run_reload(context: &CliContext) -> Result<CommandOutput, String> {
+    let (result, log_error, snapshot_error) =
+        reload_nginx_headless(context.nginx_path.clone(), context.config_path.clone()).await;
+    let result = result?;
+    let exit_code = if result.success { EXIT_OK } else { EXIT_FAILURE };
+    let text = result.message.clone();
+
+    Ok(CommandOutput::new(exit_code, json!(result), text).with_warnings([log_error, snapshot_error]))
+}
+
+fn run_servers_list(context: &CliContext) -> Result<CommandOutput, String> {
+    let config_path = context.require_config_path()?;
+    let (servers, parse_errors) = parse_config_servers(&resolve_config_files(config_path));
+    let refs: Vec<_> = servers
+        .iter()
+        .map(|(file_path, server)| server_ref(file_path, server))
+        .collect();
+
+    let rows: Vec<Vec<String>> = refs
+        .iter()
+        .map(|server| {
+            vec![
+                server.id.clone(),
+                if server.enabled { "启用" } else { "停用" }.to_string(),
+                server.listen.join(", "),
+                server.server_name.join(" "),
+                format!("{}:{}", server.file_path, server.start_line),
+            ]
+        })
+        .collect();
+    let mut text = render_table(&["ID", "状态", "监听", "域名", "位置"], &rows);
+    for error in &parse_errors {
+        text.push_str(&format!("\n解析失败: {}", error));
+    }
+    let exit_code = if parse_errors.is_empty() { EXIT_OK } else { EXIT_FAILURE };
+    let data = json!({ "configPath": config_path, "servers": refs, "parseErrors": parse_errors });
+
+    Ok(CommandOutput::new(exit_code, data, text))
+}
+
+/// 在主配置及 include 文件中定位 server 所在文件，id 按文件内行号生成，跨文件重复时需 `--file` 指定
+fn locate_server_file(context: &CliContext, server_id: &str) -> Result<String, String> {
+    let config_path = context.require_config_path()?;
+    let (servers, _) = parse_config_servers(&resolve_config_files(config_path));
+    let candidates: Vec<&str> = servers
+        .iter()
+        .filter(|(file_path, server)| {
+            server.id == server_id && context.file_path.as_deref().is_none_or(|wanted| wanted == file_path)
+        })
+        .map(|(file_path, _)| file_path.as_str())
+        .collect();
+
+    match candidates.as_slice() {
+        [file_path] => Ok(file_path.to_string()),
+        [] => Err(format!("未找到 id 为 {} 的 server", server_id)),
+        _ => Err(format!(
+            "存在多个 id 为 {} 的 server，请使用 --file 指定文件:\n{}",
+            server_id,
+            candidates.join("\n")
+        )),
+    }
+}
+
+async fn run_server_toggle(context: &CliContext, server_id: &str, enabled: bool) -> Result<CommandOutput, String> {
+    let file_path = locate_server_file(context, server_id)?;
+    let before = fs::read_to_string(&file_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let after = build_toggle_server_state_content(&before, &file_path, server_id, enabled)?;
+    let plan = build_change_plan_with(&context.validation_target()?, &file_path, &before, after).await?;
+
+    if !plan.validation.success {
+        let data = json!({ "success": false, "message": plan.validation.message, "plan": plan });
+        return Ok(CommandOutput::new(
+            EXIT_FAILURE,
+            data,
+            format!("配置校验未通过，未写入文件:\n{}", plan.validation.message),
+        ));
+    }
+
+    let command = "set_server_enabled_state";
+    let label = format!("Server 块 {} {}", server_id, if enabled { "启用" } else { "停用" });
+    let write_path = file_path.clone();
+    let content = plan.new_content.clone();
+    let (result, log_error) = audited_headless(command, &label, async move {
+        write_config_file(&write_path, content)?;
+        Ok(EditResult {
+            success: true,
+            message: if enabled { "Server 块已恢复启用" } else { "Server 块已临时停用" }.to_string(),
+        })
+    })
+    .await;
+    let result = result?;
+    let change_log_error = record_file_change(
+        command,
+        if enabled { "启用 Server" } else { "停用 Server" },
+        &file_path,
+        before,
+        plan.new_content.clone(),
+    );
+
+    let text = format!("{}\n{}", result.message, plan.diff.trim_end());
+    let data = json!({ "success": true, "message": result.message, "plan": plan });
+    Ok(CommandOutput::new(EXIT_OK, data, text).with_warnings([log_error, change_log_error]))
+}
+
+async fn run_lint(context: &CliContext) -> Result<CommandOutput, String> {
+    let report = lint_nginx_config(context.require_config_path()?.to_string()).await?;
+    let rows: Vec<Vec<String>> = report
+        .findings
+        .iter()
+        .map(|finding| {
+            vec![
+                format!("{:?}", finding.severity).to_lowercase(),
+                finding.rule_id.clone(),
+                format!("{}:{}", finding.file_path, finding.line),
+                finding.server_name.join(" "),
+                finding.message.clone(),
+            ]
+        })
+        .collect();
+    let mut text = if rows.is_empty() {
+        "未发现问题".to_string()
+    } else {
+        render_table(&["级别", "规则", "位置", "Server", "说明"], &rows)
+    };
+    for error in &report.parse_errors {
+        text.push_str(&format!("\n解析失败: {}", error));
+    }
+    let has_error = report.findings.iter().any(|finding| finding.severity == LintSeverity::Error);
+    let exit_code = if has_error || !report.parse_errors.is_empty() { EXIT_FAILURE } else { EXIT_OK };
+
+    Ok(CommandOutput::new(exit_code, json!(report), text))
+}
+
+async fn load_file_change_logs() -> Result<Vec<StoredLogEntry>, String> {
+    let retention_days = read_app_settings()
+        .map(|settings| settings.log_retention_days)
+        .unwrap_or(30);
+    Ok(load_operation_logs(retention_days)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.kind == LogKind::FileChange && entry.detail.is_some())
+        .collect())
+}
+
+async fn run_backup_list() -> Result<CommandOutput, String> {
+    let entries = load_file_change_logs().await?;
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .filter_map(|entry| {
+            let detail = entry.detail.as_ref()?;
+            Some(vec![
+                entry.id.clone(),
+                format_timestamp(detail.saved_at),
+                detail.operation_label.clone(),
+                detail.config_path.clone(),
+            ])
+        })
+        .collect();
+    let text = if rows.is_empty() {
+        "暂无可回滚的修改记录".to_string()
+    } else {
+        render_table(&["ID", "时间", "操作", "配置文件"], &rows)
+    };
+
+    Ok(CommandOutput::new(EXIT_OK, json!(entries), text))
+}
+
+async fn run_backup_restore(context: &CliContext, entry_id: Option<&str>) -> Result<CommandOutput, String> {
+    let entries = load_file_change_logs().await?;
+    let entry = match entry_id {
+        Some(entry_id) => entries.iter().find(|entry| entry.id == entry_id),
+        // 未指定记录时取当前配置文件（含 include 文件）最近一次修改
+        None => {
+            let files: Vec<String> = resolve_config_files(context.require_config_path()?)
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect();
+            entries.iter().find(|entry| {
+                entry.detail.as_ref().is_some_and(|detail| {
+                    detail.config_path == context.config_path || files.contains(&detail.config_path)
+                })
+            })
+        }
+    }
+    .ok_or_else(|| "未找到可回滚的修改记录".to_string())?;
+    let detail = entry.detail.as_ref().ok_or_else(|| "修改记录缺少文件内容".to_string())?;
+    let target_path = detail.config_path.clone();
+    let restored = detail.file_diff.before.clone();
+
+    let current = fs::read_to_string(&target_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let plan = build_change_plan_with(&context.validation_target()?, &target_path, &current, restored.clone()).await?;
+    if !plan.validation.success {
+        let data = json!({ "success": false, "message": plan.validation.message, "plan": plan });
+        return Ok(CommandOutput::new(
+            EXIT_FAILURE,
+            data,
+            format!("回滚后的配置校验未通过，未写入文件:\n{}", plan.validation.message),
+        ));
+    }
+
+    let label = format!("配置文件回滚到 {} 之前", detail.operation_label);
+    let write_path = target_path.clone();
+    let content = restored.clone();
+    let (result, log_error) = audited_headless("restore_config_backup", &label, async move {
+        write_config_file(&write_path, content)?;
+        Ok(EditResult {
+            success: true,
+            message: "配置文件已回滚".to_string(),
+        })
+    })
+    .await;
+    let result = result?;
+    let change_log_error =
+        record_file_change("restore_config_backup", "回滚配置文件", &target_path, current, restored);
+
+    let text = format!("{}: {}\n{}", result.message, target_path, plan.diff.trim_end());
+    let data = json!({ "success": result.success, "message": result.message, "entryId": entry.id, "plan": plan });
+    Ok(CommandOutput::new(EXIT_OK, data, text).with_warnings([log_error, change_log_error]))
+}
+
+async fn execute(command: &CliCommand, context: &CliContext) -> Result<CommandOutput, String> {
+    match command {
+        CliCommand::Help => Ok(CommandOutput::new(EXIT_OK, json!({ "usage": USAGE }), USAGE.to_string())),
+        CliCommand::Status => run_status(context).await,
+        CliCommand::Test => run_test(context).await,
+        CliCommand::Reload => run_reload(context).await,
+        CliCommand::ServersList => run_servers_list(context),
+        CliCommand::ServerToggle { server_id, enabled } => run_server_toggle(context, server_id, *enabled).await,
+        CliCommand::Lint => run_lint(context).await,
+        CliCommand::BackupList => run_backup_list().await,
+        CliCommand::BackupRestore { entry_id } => run_backup_restore(context, entry_id.as_deref()).await,
+    }
+}
+
+fn print_error(json_output: bool, error: &str) {
+    if json_output {
+        println!("{}", json!({ "success": false, "error": error }));
+    } else {
+        eprintln!("错误: {}", error);
+    }
+}
+
+/// 发布版使用 windows 子系统不会继承终端，命令行模式下附加到父进程的控制台以输出结果
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+
+    // 父进程没有控制台（例如从资源管理器启动）时附加失败，此时无处输出，忽略即可
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
+
+/// 执行命令行子命令并返回进程退出码，不创建窗口
+pub fn run(args: Vec<String>) -> i32 {
+    attach_parent_console();
+    let (options, command) = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            print_error(args.iter().any(|arg| arg == "--json"), &error);
+            eprintln!("\n{}", USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let runtime = match tokio::runtime::Builder::new_current_thread().enable_time().build() {
+        Ok(runtime) => runtime,
+        Err(error) => {
+            print_error(options.json, &format!("创建运行时失败: {}", error));
+            return EXIT_FAILURE;
+        }
+    };
+    let result = CliContext::resolve(&options).and_then(|context| runtime.block_on(execute(&command, &context)));
+
+    match result {
+        Ok(output) => {
+            for warning in &output.warnings {
+                eprintln!("警告: {}", warning);
+            }
+            if options.json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&output.data).unwrap_or_else(|_| output.data.to_string())
+                );
+            } else {
+                println!("{}", output.text);
+            }
+            output.exit_code
+        }
+        Err(error) => {
+            print_error(options.json, &error);
+            EXIT_FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_args_should_accept_global_flags_anywhere() {
+        let (options, command) =
+            parse_args(&args(&["server", "--json", "disable", "server_12", "--file", "/etc/nginx/conf.d/a.conf"])).unwrap();
+
+        assert!(options.json);
+        assert_eq!(options.file_path.as_deref(), Some("/etc/nginx/conf.d/a.conf"));
+        assert_eq!(
+            command,
+            CliCommand::ServerToggle {
+                server_id: "server_12".to_string(),
+                enabled: false,
+            }
+        );
+        assert_eq!(
+            parse_args(&args(&["--config", "/etc/nginx/nginx.conf", "backup", "restore"])).unwrap().1,
+            CliCommand::BackupRestore { entry_id: None }
+        );
+        assert!(parse_args(&args(&["server", "remove", "server_1"])).is_err());
+        assert!(parse_args(&args(&["status", "--config"])).is_err());
+        assert!(is_cli_invocation(&args(&["status"])));
+        assert!(!is_cli_invocation(&args(&["-psn_0_12345"])));
+    }
+
+    #[test]
+    fn render_table_should_align_wide_characters() {
+        let table = render_table(
+            &["ID", "状态"],
+            &[
+                vec!["server_1".to_string(), "启用".to_string()],
+                vec!["s2".to_string(), "停用".to_string()],
+            ],
+        );
+
+        assert_eq!(table, "ID        状态\n--------  ----\nserver_1  启用\ns2        停用");
+    }
+
+    #[test]
+    fn validation_target_should_use_cli_paths_and_require_nginx() {
+        let context = CliContext {
+            nginx_path: "/opt/nginx/sbin/nginx".to_string(),
+            config_path: "/opt/nginx/conf/nginx.conf".to_string(),
+            config_overridden: true,
+            file_path: None,
+        };
+        let target = context.validation_target().unwrap();
+        assert_eq!(target.nginx_path, "/opt/nginx/sbin/nginx");
+        assert_eq!(target.main_config, "/opt/nginx/conf/nginx.conf");
+
+        let headless = CliContext {
+            nginx_path: String::new(),
+            ..context
+        };
+        assert!(headless.validation_target().is_err());
+    }
+}
@@ -1,6 +1,10 @@
+use crate::audit::audited;
+use crate::change_plan::{build_change_plan, ChangePlan};
+use crate::config_watcher::record_own_write;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use tauri::AppHandle;
 
 /// 配置指令
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,7 +131,7 @@ fn unwrap_disabled_server_block(disabled_text: &str) -> Result<String, String> {
         .join("\n"))
 }
 
-fn render_server_block_by_state(server_text: &str, enabled: bool) -> String {
+pub(crate) fn render_server_block_by_state(server_text: &str, enabled: bool) -> String {
     if enabled {
         server_text.to_string()
     } else {
@@ -214,8 +218,25 @@ pub async fn read_config_file(config_path: String) -> Result<ParseResult, String
     }
 }
 
+/// 读取并解析配置文件，供后端其他模块复用
+pub(crate) fn load_nginx_config(config_path: &str) -> Result<NginxConfig, String> {
+    let content = fs::read_to_string(config_path)
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+
+    parse_nginx_config(&content, config_path)
+        .map_err(|e| format!("解析配置文件失败: {}", e))
+}
+
+/// 写入配置文件并登记为应用自身的修改，配置监听不会再把它当作外部变化
+pub(crate) fn write_config_file(path: impl AsRef<Path>, content: impl AsRef<[u8]>) -> Result<(), String> {
+    let path = path.as_ref();
+    fs::write(path, content).map_err(|e| format!("写入配置文件失败: {}", e))?;
+    record_own_write(path);
+    Ok(())
+}
+
 /// 解析 Nginx 配置文件
-fn parse_nginx_config(content: &str, file_path: &str) -> Result<NginxConfig, String> {
+pub(crate) fn parse_nginx_config(content: &str, file_path: &str) -> Result<NginxConfig, String> {
     let lines: Vec<&str> = content.lines().collect();
     let mut servers = Vec::new();
     let mut global_directives = Vec::new();
@@ -394,7 +415,10 @@ fn parse_location_block(lines: &[&str], start: usize) -> Result<(LocationBlock,
     
     // 解析 location 路径和修饰符
     // 格式: location [modifier] path {
-    let parts: Vec<&str> = line.split_whitespace().collect();
+    let parts: Vec<&str> = line
+        .split_whitespace()
+        .filter(|part| *part != "{")
+        .collect();
     let (modifier, path) = if parts.len() >= 3 {
         // 有修饰符: location ~ /api {
         let mod_str = parts[1];
@@ -445,7 +469,7 @@ fn parse_location_block(lines: &[&str], start: usize) -> Result<(LocationBlock,
 }
 
 /// 解析单个指令
-fn parse_directive(line: &str, line_number: usize) -> Option<Directive> {
+pub(crate) fn parse_directive(line: &str, line_number: usize) -> Option<Directive> {
     // 先去除注释（# 后面的内容）
     let line_without_comment = if let Some(pos) = line.find('#') {
         &line[..pos]
@@ -480,7 +504,7 @@ fn parse_directive(line: &str, line_number: usize) -> Option<Directive> {
 }
 
 /// 查找块的结束位置（匹配的右花括号）
-fn find_block_end(lines: &[&str], start: usize) -> Result<usize, String> {
+pub(crate) fn find_block_end(lines: &[&str], start: usize) -> Result<usize, String> {
     let mut depth = 0;
     let mut found_start = false;
     
@@ -540,6 +564,20 @@ pub struct EditResult {
 /// 新增 Server 块
 #[tauri::command]
 pub async fn add_server_block(
+    app: AppHandle,
+    config_path: String,
+    server_input: ServerBlockInput,
+) -> Result<EditResult, String> {
+    audited(
+        &app,
+        "add_server_block",
+        "Server 块新增",
+        add_server_block_internal(config_path, server_input),
+    )
+    .await
+}
+
+async fn add_server_block_internal(
     config_path: String,
     server_input: ServerBlockInput,
 ) -> Result<EditResult, String> {
@@ -575,8 +613,7 @@ pub async fn add_server_block(
     let new_content = content + &server_content;
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -587,6 +624,22 @@ pub async fn add_server_block(
 /// 更新 Server 块
 #[tauri::command]
 pub async fn update_server_block(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+    server_input: ServerBlockInput,
+) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 更新", server_id);
+    audited(
+        &app,
+        "update_server_block",
+        &label,
+        update_server_block_internal(config_path, server_id, server_input),
+    )
+    .await
+}
+
+async fn update_server_block_internal(
     config_path: String,
     server_id: String,
     server_input: ServerBlockInput,
@@ -640,8 +693,7 @@ pub async fn update_server_block(
     let new_content = replace_server_range(&content, server.start_line, server.end_line, &replacement);
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -652,6 +704,15 @@ pub async fn update_server_block(
 /// 删除 Server 块
 #[tauri::command]
 pub async fn delete_server_block(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 删除", server_id);
+    audited(&app, "delete_server_block", &label, delete_server_block_internal(config_path, server_id)).await
+}
+
+async fn delete_server_block_internal(
     config_path: String,
     server_id: String,
 ) -> Result<EditResult, String> {
@@ -688,8 +749,7 @@ pub async fn delete_server_block(
     let new_content = new_lines.join("\n");
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -700,6 +760,22 @@ pub async fn delete_server_block(
 /// 在 Server 块中添加 Location 块
 #[tauri::command]
 pub async fn add_location_to_server(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+    location_input: LocationBlockInput,
+) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 的 Location 添加", server_id);
+    audited(
+        &app,
+        "add_location_to_server",
+        &label,
+        add_location_to_server_internal(config_path, server_id, location_input),
+    )
+    .await
+}
+
+async fn add_location_to_server_internal(
     config_path: String,
     server_id: String,
     location_input: LocationBlockInput,
@@ -760,8 +836,7 @@ pub async fn add_location_to_server(
     let new_content = new_lines.join("\n");
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -770,7 +845,7 @@ pub async fn add_location_to_server(
 }
 
 /// 查找 http 块的位置
-fn find_http_block(lines: &[&str]) -> Result<(usize, usize), String> {
+pub(crate) fn find_http_block(lines: &[&str]) -> Result<(usize, usize), String> {
     let mut http_start = None;
     let mut depth = 0;
 
@@ -808,16 +883,20 @@ fn find_http_block(lines: &[&str]) -> Result<(usize, usize), String> {
     Err("http 块未正确闭合，缺少匹配的右花括号".to_string())
 }
 
-/// 生成添加 Server 块后的新配置内容（不保存到文件）
+/// 生成添加 Server 块的变更计划（不保存到文件）
 #[tauri::command]
 pub async fn generate_add_server_content(
     config_path: String,
     server_text: String,
-) -> Result<String, String> {
-    // 读取配置文件
+) -> Result<ChangePlan, String> {
     let content = fs::read_to_string(&config_path)
         .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_add_server_content(&content, &server_text)?;
+    build_change_plan(&config_path, &content, new_content).await
+}
 
+/// 生成添加 Server 块后的新配置内容
+pub(crate) fn build_add_server_content(content: &str, server_text: &str) -> Result<String, String> {
     let lines: Vec<&str> = content.lines().collect();
 
     // 查找 http 块的位置
@@ -856,15 +935,30 @@ pub async fn generate_add_server_content(
 /// 添加 Server 块（文本格式）- 先校验再保存
 #[tauri::command]
 pub async fn add_server_block_text(
+    app: AppHandle,
+    config_path: String,
+    server_text: String,
+) -> Result<EditResult, String> {
+    audited(
+        &app,
+        "add_server_block_text",
+        "Server 块新增",
+        add_server_block_text_internal(config_path, server_text),
+    )
+    .await
+}
+
+pub(crate) async fn add_server_block_text_internal(
     config_path: String,
     server_text: String,
 ) -> Result<EditResult, String> {
     // 生成新配置内容
-    let new_content = generate_add_server_content(config_path.clone(), server_text).await?;
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_add_server_content(&content, &server_text)?;
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -872,18 +966,27 @@ pub async fn add_server_block_text(
     })
 }
 
-/// 生成更新 Server 块后的新配置内容（不保存到文件）
+/// 生成更新 Server 块的变更计划（不保存到文件）
 #[tauri::command]
 pub async fn generate_update_server_content(
     config_path: String,
     server_id: String,
     server_text: String,
-) -> Result<String, String> {
-    // 读取并解析配置文件
+) -> Result<ChangePlan, String> {
     let content = fs::read_to_string(&config_path)
         .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_update_server_content(&content, &config_path, &server_id, &server_text)?;
+    build_change_plan(&config_path, &content, new_content).await
+}
 
-    let config = parse_nginx_config(&content, &config_path)
+/// 生成更新 Server 块后的新配置内容
+pub(crate) fn build_update_server_content(
+    content: &str,
+    config_path: &str,
+    server_id: &str,
+    server_text: &str,
+) -> Result<String, String> {
+    let config = parse_nginx_config(content, config_path)
         .map_err(|e| format!("解析配置文件失败: {}", e))?;
 
     // 找到要更新的 server 块
@@ -891,21 +994,31 @@ pub async fn generate_update_server_content(
         .find(|s| s.id == server_id)
         .ok_or_else(|| format!("未找到 ID 为 {} 的 Server 块", server_id))?;
 
-    let replacement = render_server_block_by_state(&server_text, server.enabled);
-    let new_content = replace_server_range(&content, server.start_line, server.end_line, &replacement);
-    Ok(new_content)
+    let replacement = render_server_block_by_state(server_text, server.enabled);
+    Ok(replace_server_range(content, server.start_line, server.end_line, &replacement))
 }
 
+/// 生成启用/停用 Server 块的变更计划（不保存到文件）
 #[tauri::command]
 pub async fn generate_toggle_server_state_content(
     config_path: String,
     server_id: String,
     enabled: bool,
-) -> Result<String, String> {
+) -> Result<ChangePlan, String> {
     let content = fs::read_to_string(&config_path)
         .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_toggle_server_state_content(&content, &config_path, &server_id, enabled)?;
+    build_change_plan(&config_path, &content, new_content).await
+}
 
-    let config = parse_nginx_config(&content, &config_path)
+/// 生成启用/停用 Server 块后的新配置内容
+pub(crate) fn build_toggle_server_state_content(
+    content: &str,
+    config_path: &str,
+    server_id: &str,
+    enabled: bool,
+) -> Result<String, String> {
+    let config = parse_nginx_config(content, config_path)
         .map_err(|e| format!("解析配置文件失败: {}", e))?;
 
     let server = config.servers.iter()
@@ -913,23 +1026,44 @@ pub async fn generate_toggle_server_state_content(
         .ok_or_else(|| format!("未找到 ID 为 {} 的 Server 块", server_id))?;
 
     if server.enabled == enabled {
-        return Ok(content);
+        return Ok(content.to_string());
     }
 
     let replacement = render_server_block_by_state(&server.raw_content, enabled);
-    Ok(replace_server_range(&content, server.start_line, server.end_line, &replacement))
+    Ok(replace_server_range(content, server.start_line, server.end_line, &replacement))
 }
 
 #[tauri::command]
 pub async fn set_server_enabled_state(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+    enabled: bool,
+) -> Result<EditResult, String> {
+    let label = if enabled {
+        format!("Server 块 {} 启用", server_id)
+    } else {
+        format!("Server 块 {} 停用", server_id)
+    };
+    audited(
+        &app,
+        "set_server_enabled_state",
+        &label,
+        set_server_enabled_state_internal(config_path, server_id, enabled),
+    )
+    .await
+}
+
+async fn set_server_enabled_state_internal(
     config_path: String,
     server_id: String,
     enabled: bool,
 ) -> Result<EditResult, String> {
-    let new_content = generate_toggle_server_state_content(config_path.clone(), server_id, enabled).await?;
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_toggle_server_state_content(&content, &config_path, &server_id, enabled)?;
 
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -944,16 +1078,33 @@ pub async fn set_server_enabled_state(
 /// 更新 Server 块（文本格式）- 先校验再保存
 #[tauri::command]
 pub async fn update_server_block_text(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+    server_text: String,
+) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 更新", server_id);
+    audited(
+        &app,
+        "update_server_block_text",
+        &label,
+        update_server_block_text_internal(config_path, server_id, server_text),
+    )
+    .await
+}
+
+async fn update_server_block_text_internal(
     config_path: String,
     server_id: String,
     server_text: String,
 ) -> Result<EditResult, String> {
     // 生成新配置内容
-    let new_content = generate_update_server_content(config_path.clone(), server_id, server_text).await?;
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let new_content = build_update_server_content(&content, &config_path, &server_id, &server_text)?;
 
     // 写入配置文件
-    fs::write(&config_path, new_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, new_content)?;
 
     Ok(EditResult {
         success: true,
@@ -1012,12 +1163,25 @@ pub async fn read_config_file_content(config_path: String) -> Result<String, Str
 /// 写入格式化后的配置文件
 #[tauri::command]
 pub async fn write_formatted_config(
+    app: AppHandle,
+    config_path: String,
+    formatted_content: String,
+) -> Result<EditResult, String> {
+    audited(
+        &app,
+        "write_formatted_config",
+        "配置文件格式化",
+        write_formatted_config_internal(config_path, formatted_content),
+    )
+    .await
+}
+
+async fn write_formatted_config_internal(
     config_path: String,
     formatted_content: String,
 ) -> Result<EditResult, String> {
     // 写入配置文件
-    fs::write(&config_path, formatted_content)
-        .map_err(|e| format!("写入配置文件失败: {}", e))?;
+    write_config_file(&config_path, formatted_content)?;
 
     Ok(EditResult {
         success: true,
@@ -1052,6 +1216,22 @@ mod tests {
         assert_eq!(config.servers[0].server_name, vec!["demo.local"]);
     }
 
+    #[test]
+    fn parse_location_block_should_not_take_open_brace_as_path() {
+        let content = "server {\n    listen 80;\n    location /api/ {\n        return 200;\n    }\n    location ~ \\.php$ {\n        return 404;\n    }\n    location /static/{\n        root html;\n    }\n}\n";
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let locations: Vec<(Option<&str>, &str)> = config.servers[0]
+            .locations
+            .iter()
+            .map(|location| (location.modifier.as_deref(), location.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            locations,
+            vec![(None, "/api/"), (Some("~"), "\\.php$"), (None, "/static/")]
+        );
+    }
+
     #[test]
     fn parse_server_block_should_extract_category_and_relative_location_lines() {
         let content = r#"http {
@@ -1074,4 +1254,22 @@ mod tests {
         assert_eq!(location.relative_start_line, 6);
         assert_eq!(location.relative_end_line, 8);
     }
+
+    #[test]
+    fn parse_location_block_should_ignore_detached_opening_brace() {
+        let content = "http {\n    server {\n        listen 80;\n        location /api/ {\n            return 200;\n        }\n        location ~ \\.php$ {\n            return 404;\n        }\n        location = /exact{\n            return 204;\n        }\n    }\n}\n";
+
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let locations: Vec<(Option<&str>, &str)> = config.servers[0]
+            .locations
+            .iter()
+            .map(|location| (location.modifier.as_deref(), location.path.as_str()))
+            .collect();
+
+        assert_eq!(
+            locations,
+            vec![(None, "/api/"), (Some("~"), "\\.php$"), (Some("="), "/exact")]
+        );
+        assert_eq!(config.servers[0].locations[0].id, "location_3__api_");
+    }
 }
@@ -0,0 +1,351 @@
+use crate::config_watcher::resolve_config_files;
+use crate::logs::FileChangeScopeDiff;
+use crate::nginx::query_nginx_processes;
+use crate::settings::{get_app_config_dir, read_app_settings};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const APPLIED_CONFIG_FILE: &str = "applied_config.json";
+
+/// 最近一次成功启动/重载时的配置快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AppliedConfigSnapshot {
+    pub config_path: String,
+    pub recorded_at_ms: i64,
+    /// 记录时的 master PID，PID 变化说明 Nginx 已在应用外重新启动
+    pub master_pid: Option<u32>,
+    pub digest: String,
+    pub files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigDriftStatus {
+    InSync,
+    Drifted,
+    /// 没有可比对的快照，或 Nginx 未运行
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigFileDriftKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFileDrift {
+    pub path: String,
+    pub kind: ConfigFileDriftKind,
+    pub diff: FileChangeScopeDiff,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigDriftReport {
+    pub status: ConfigDriftStatus,
+    pub message: String,
+    pub config_path: String,
+    pub applied_at_ms: Option<i64>,
+    pub applied_digest: Option<String>,
+    pub current_digest: String,
+    pub files: Vec<ConfigFileDrift>,
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn applied_snapshot_cache() -> &'static Mutex<Option<AppliedConfigSnapshot>> {
+    static APPLIED_SNAPSHOT: OnceLock<Mutex<Option<AppliedConfigSnapshot>>> = OnceLock::new();
+    APPLIED_SNAPSHOT.get_or_init(|| Mutex::new(read_applied_snapshot_file()))
+}
+
+fn applied_snapshot_path() -> Result<PathBuf, String> {
+    Ok(get_app_config_dir()?.join(APPLIED_CONFIG_FILE))
+}
+
+fn read_applied_snapshot_file() -> Option<AppliedConfigSnapshot> {
+    let json = fs::read_to_string(applied_snapshot_path().ok()?).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn write_applied_snapshot_file(snapshot: &AppliedConfigSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string(snapshot).map_err(|e| format!("序列化配置快照失败: {}", e))?;
+    fs::write(applied_snapshot_path()?, json).map_err(|e| format!("写入配置快照失败: {}", e))
+}
+
+/// FNV-1a 64 位哈希，结果跨版本稳定，可持久化比对
+fn fnv1a64(bytes: &[u8], mut hash: u64) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// 计算配置文件集合的指纹，文件路径与内容都参与计算
+pub(crate) fn fingerprint_config_files(files: &BTreeMap<String, String>) -> String {
+    let hash = files.iter().fold(0xcbf2_9ce4_8422_2325, |hash, (path, content)| {
+        let hash = fnv1a64(path.as_bytes(), hash);
+        let hash = fnv1a64(&[0], hash);
+        let hash = fnv1a64(content.as_bytes(), hash);
+        fnv1a64(&[0], hash)
+    });
+    format!("{:016x}", hash)
+}
+
+/// 读取主配置文件及全部 include 文件的当前内容，不存在的文件不计入
+pub(crate) fn read_config_file_set(config_path: &str) -> BTreeMap<String, String> {
+    resolve_config_files(config_path)
+        .into_iter()
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            Some((path.to_string_lossy().to_string(), content))
+        })
+        .collect()
+}
+
+/// 启动、重启、重载成功后调用，记录此刻磁盘上的配置作为运行中配置；
+/// `config_path` 为本次操作实际生效的配置路径
+pub fn record_applied_config(config_path: &str) -> Result<(), String> {
+    if config_path.trim().is_empty() {
+        return Ok(());
+    }
+
+    let files = read_config_file_set(config_path);
+    let snapshot = AppliedConfigSnapshot {
+        digest: fingerprint_config_files(&files),
+        config_path: config_path.to_string(),
+        recorded_at_ms: now_timestamp_ms(),
+        master_pid: query_nginx_processes().ok().and_then(|snapshot| snapshot.master_pid),
+        files,
+    };
+
+    let persisted = write_applied_snapshot_file(&snapshot);
+    if let Ok(mut cache) = applied_snapshot_cache().lock() {
+        *cache = Some(snapshot);
+    }
+    persisted
+}
+
+fn unknown_report(config_path: &str, current_digest: String, message: &str) -> ConfigDriftReport {
+    ConfigDriftReport {
+        status: ConfigDriftStatus::Unknown,
+        message: message.to_string(),
+        config_path: config_path.to_string(),
+        applied_at_ms: None,
+        applied_digest: None,
+        current_digest,
+        files: Vec::new(),
+    }
+}
+
+/// 按文件对比快照与当前内容
+fn diff_config_file_sets(
+    applied: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<ConfigFileDrift> {
+    let mut drifts = Vec::new();
+
+    for (path, content) in current {
+        let kind = match applied.get(path) {
+            None => ConfigFileDriftKind::Added,
+            Some(before) if before != content => ConfigFileDriftKind::Modified,
+            Some(_) => continue,
+        };
+        drifts.push(ConfigFileDrift {
+            path: path.clone(),
+            kind,
+            diff: FileChangeScopeDiff {
+                label: path.clone(),
+                before: applied.get(path).cloned().unwrap_or_default(),
+                after: content.clone(),
+            },
+        });
+    }
+
+    for (path, before) in applied {
+        if !current.contains_key(path) {
+            drifts.push(ConfigFileDrift {
+                path: path.clone(),
+                kind: ConfigFileDriftKind::Removed,
+                diff: FileChangeScopeDiff {
+                    label: path.clone(),
+                    before: before.clone(),
+                    after: String::new(),
+                },
+            });
+        }
+    }
+
+    drifts
+}
+
+fn build_drift_report(
+    config_path: &str,
+    applied: Option<&AppliedConfigSnapshot>,
+    current: &BTreeMap<String, String>,
+    master_pid: Option<u32>,
+) -> ConfigDriftReport {
+    let current_digest = fingerprint_config_files(current);
+
+    if master_pid.is_none() {
+        return unknown_report(config_path, current_digest, "Nginx 未运行，无法比对运行中的配置");
+    }
+    let Some(applied) = applied.filter(|applied| applied.config_path == config_path) else {
+        return unknown_report(
+            config_path,
+            current_digest,
+            "尚未记录该配置文件的生效快照，请通过本应用启动或重载一次",
+        );
+    };
+    if applied.master_pid.is_some() && applied.master_pid != master_pid {
+        return unknown_report(
+            config_path,
+            current_digest,
+            "Nginx 已在本应用之外重新启动，生效快照已过期",
+        );
+    }
+
+    let files = diff_config_file_sets(&applied.files, current);
+    let (status, message) = if applied.digest == current_digest {
+        (ConfigDriftStatus::InSync, "磁盘配置与运行中的配置一致".to_string())
+    } else {
+        (
+            ConfigDriftStatus::Drifted,
+            format!("有 {} 个配置文件的修改尚未生效，需要重载 Nginx", files.len()),
+        )
+    };
+
+    ConfigDriftReport {
+        status,
+        message,
+        config_path: config_path.to_string(),
+        applied_at_ms: Some(applied.recorded_at_ms),
+        applied_digest: Some(applied.digest.clone()),
+        current_digest,
+        files,
+    }
+}
+
+/// 对比当前磁盘配置与最近一次生效的配置
+pub(crate) fn check_config_drift(config_path: &str) -> ConfigDriftReport {
+    let current = read_config_file_set(config_path);
+    let master_pid = query_nginx_processes().ok().and_then(|snapshot| snapshot.master_pid);
+    let applied = applied_snapshot_cache().lock().ok().and_then(|cache| cache.clone());
+
+    build_drift_report(config_path, applied.as_ref(), &current, master_pid)
+}
+
+/// 运行中 Nginx 对应的配置文件内容；没有有效快照时返回 None
+pub(crate) fn applied_config_file_set(config_path: &str) -> Option<BTreeMap<String, String>> {
+    let master_pid = query_nginx_processes().ok()?.master_pid?;
+    let applied = applied_snapshot_cache().lock().ok()?.clone()?;
+    if applied.config_path != config_path || applied.master_pid.is_some_and(|pid| pid != master_pid) {
+        return None;
+    }
+    Some(applied.files)
+}
+
+/// 查询磁盘配置与运行中配置的差异
+#[tauri::command]
+pub async fn get_config_drift(config_path: Option<String>) -> Result<ConfigDriftReport, String> {
+    let config_path = match config_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => path,
+        None => read_app_settings()?.config_path,
+    };
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+
+    Ok(check_config_drift(&config_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_set(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    fn snapshot(files: BTreeMap<String, String>, master_pid: Option<u32>) -> AppliedConfigSnapshot {
+        AppliedConfigSnapshot {
+            config_path: "/etc/nginx/nginx.conf".to_string(),
+            recorded_at_ms: 1,
+            master_pid,
+            digest: fingerprint_config_files(&files),
+            files,
+        }
+    }
+
+    #[test]
+    fn build_drift_report_should_list_pending_file_changes() {
+        let applied = snapshot(
+            file_set(&[
+                ("/etc/nginx/nginx.conf", "include conf.d/*.conf;\n"),
+                ("/etc/nginx/conf.d/a.conf", "server { listen 80; }\n"),
+                ("/etc/nginx/conf.d/old.conf", "server { listen 81; }\n"),
+            ]),
+            Some(100),
+        );
+        let current = file_set(&[
+            ("/etc/nginx/nginx.conf", "include conf.d/*.conf;\n"),
+            ("/etc/nginx/conf.d/a.conf", "server { listen 8080; }\n"),
+            ("/etc/nginx/conf.d/new.conf", "server { listen 82; }\n"),
+        ]);
+
+        let report = build_drift_report("/etc/nginx/nginx.conf", Some(&applied), &current, Some(100));
+        assert_eq!(report.status, ConfigDriftStatus::Drifted);
+        let kinds: Vec<(&str, ConfigFileDriftKind)> = report
+            .files
+            .iter()
+            .map(|file| (file.path.as_str(), file.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("/etc/nginx/conf.d/a.conf", ConfigFileDriftKind::Modified),
+                ("/etc/nginx/conf.d/new.conf", ConfigFileDriftKind::Added),
+                ("/etc/nginx/conf.d/old.conf", ConfigFileDriftKind::Removed),
+            ]
+        );
+        assert_eq!(report.files[0].diff.before, "server { listen 80; }\n");
+
+        let in_sync = build_drift_report("/etc/nginx/nginx.conf", Some(&applied), &applied.files, Some(100));
+        assert_eq!(in_sync.status, ConfigDriftStatus::InSync);
+        assert!(in_sync.files.is_empty());
+    }
+
+    #[test]
+    fn build_drift_report_should_be_unknown_without_valid_snapshot() {
+        let files = file_set(&[("/etc/nginx/nginx.conf", "events {}\n")]);
+        let applied = snapshot(files.clone(), Some(100));
+
+        let stopped = build_drift_report("/etc/nginx/nginx.conf", Some(&applied), &files, None);
+        assert_eq!(stopped.status, ConfigDriftStatus::Unknown);
+        let missing = build_drift_report("/etc/nginx/nginx.conf", None, &files, Some(100));
+        assert_eq!(missing.status, ConfigDriftStatus::Unknown);
+        let restarted = build_drift_report("/etc/nginx/nginx.conf", Some(&applied), &files, Some(200));
+        assert_eq!(restarted.status, ConfigDriftStatus::Unknown);
+        assert_ne!(
+            fingerprint_config_files(&files),
+            fingerprint_config_files(&file_set(&[("/etc/nginx/nginx.conf", "events { }\n")]))
+        );
+    }
+}
@@ -0,0 +1,1016 @@
+use crate::config::{DirectiveInput, LocationBlockInput, ServerBlockInput};
+use crate::config_text::{render_directive_line, render_location_block, INDENT_UNIT};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const TLS_PROTOCOLS: [&str; 4] = ["TLSv1", "TLSv1.1", "TLSv1.2", "TLSv1.3"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSourceFormat {
+    Apache,
+    Caddy,
+}
+
+/// 无法转换的源配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportIssue {
+    pub line: usize,
+    pub directive: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedServer {
+    /// 源配置中的站点描述，例如 VirtualHost *:80 或 Caddy 站点地址
+    pub source: String,
+    pub line: usize,
+    pub server: ServerBlockInput,
+    pub locations: Vec<LocationBlockInput>,
+    /// 渲染后的 server 块文本，可直接交给新增 server 的校验流程
+    pub server_text: String,
+    pub issues: Vec<ImportIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub format: ImportSourceFormat,
+    pub source_path: String,
+    pub servers: Vec<ImportedServer>,
+    /// 站点之外无法转换的内容
+    pub issues: Vec<ImportIssue>,
+}
+
+fn issue(line: usize, directive: &str, reason: impl Into<String>) -> ImportIssue {
+    ImportIssue {
+        line,
+        directive: directive.to_string(),
+        reason: reason.into(),
+    }
+}
+
+fn directive(name: &str, value: impl Into<String>) -> DirectiveInput {
+    DirectiveInput {
+        name: name.to_string(),
+        value: value.into(),
+    }
+}
+
+/// 按空白拆分参数，双引号内的空白保留
+fn split_tokens(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn quote_if_needed(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '{' | '}' | '"' | '\'')) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn proxy_location_directives(proxy_pass: &str, preserve_host: bool) -> Vec<DirectiveInput> {
+    let mut directives = vec![directive("proxy_pass", proxy_pass)];
+    if preserve_host {
+        directives.push(directive("proxy_set_header", "Host $host"));
+    }
+    directives.push(directive("proxy_set_header", "X-Real-IP $remote_addr"));
+    directives.push(directive("proxy_set_header", "X-Forwarded-For $proxy_add_x_forwarded_for"));
+    directives.push(directive("proxy_set_header", "X-Forwarded-Proto $scheme"));
+    directives
+}
+
+/// 转换过程中的 server 草稿
+#[derive(Default)]
+struct ServerDraft {
+    listen: Vec<String>,
+    server_name: Vec<String>,
+    directives: Vec<DirectiveInput>,
+    locations: Vec<LocationBlockInput>,
+    issues: Vec<ImportIssue>,
+}
+
+impl ServerDraft {
+    fn add_listen(&mut self, listen: String) {
+        if !self.listen.contains(&listen) {
+            self.listen.push(listen);
+        }
+    }
+
+    fn add_name(&mut self, name: &str) {
+        if !name.is_empty() && !self.server_name.iter().any(|existing| existing == name) {
+            self.server_name.push(name.to_string());
+        }
+    }
+
+    fn push(&mut self, name: &str, value: impl Into<String>) {
+        self.directives.push(directive(name, value));
+    }
+
+    fn issue(&mut self, line: usize, directive: &str, reason: impl Into<String>) {
+        self.issues.push(issue(line, directive, reason));
+    }
+
+    fn location_mut(&mut self, path: &str, modifier: Option<&str>) -> &mut LocationBlockInput {
+        let index = match self
+            .locations
+            .iter()
+            .position(|location| location.path == path && location.modifier.as_deref() == modifier)
+        {
+            Some(index) => index,
+            None => {
+                self.locations.push(LocationBlockInput {
+                    path: path.to_string(),
+                    modifier: modifier.map(str::to_string),
+                    directives: Vec::new(),
+                });
+                self.locations.len() - 1
+            }
+        };
+        &mut self.locations[index]
+    }
+
+    fn finish(self, source: String, line: usize) -> ImportedServer {
+        let server = ServerBlockInput {
+            listen: self.listen,
+            server_name: self.server_name,
+            directives: self.directives,
+        };
+        let server_text = render_server_text(&server, &self.locations);
+        ImportedServer {
+            source,
+            line,
+            server,
+            locations: self.locations,
+            server_text,
+            issues: self.issues,
+        }
+    }
+}
+
+/// 渲染为不带外层缩进的 server 块文本
+pub(crate) fn render_server_text(server: &ServerBlockInput, locations: &[LocationBlockInput]) -> String {
+    let mut lines = vec!["server {".to_string()];
+    for listen in &server.listen {
+        lines.push(format!("{}listen {};", INDENT_UNIT, listen));
+    }
+    if !server.server_name.is_empty() {
+        lines.push(format!("{}server_name {};", INDENT_UNIT, server.server_name.join(" ")));
+    }
+    for directive in &server.directives {
+        lines.push(render_directive_line(INDENT_UNIT, directive));
+    }
+
+    for location in locations {
+        lines.push(String::new());
+        lines.push(render_location_block(INDENT_UNIT, location));
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+// ==================== Apache ====================
+
+/// 合并以 \ 结尾的续行，返回（起始行号，内容）
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut result = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+
+    for (index, raw) in content.lines().enumerate() {
+        let (start, mut text) = pending.take().unwrap_or((index + 1, String::new()));
+        let trimmed = raw.trim();
+        if let Some(continued) = trimmed.strip_suffix('\\') {
+            text.push_str(continued);
+            text.push(' ');
+            pending = Some((start, text));
+        } else {
+            text.push_str(trimmed);
+            result.push((start, text));
+        }
+    }
+    if let Some(last) = pending {
+        result.push(last);
+    }
+    result
+}
+
+fn apache_listen(address: &str) -> String {
+    let address = address.trim();
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.ends_with(':') && port.chars().all(|c| c.is_ascii_digit()) => {
+            if matches!(host, "*" | "_default_" | "0.0.0.0") {
+                port.to_string()
+            } else {
+                address.to_string()
+            }
+        }
+        _ if matches!(address, "*" | "_default_") => "80".to_string(),
+        _ => address.to_string(),
+    }
+}
+
+fn apache_host_name(value: &str) -> &str {
+    let value = value.split_once("://").map(|(_, rest)| rest).unwrap_or(value);
+    match value.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => value,
+    }
+}
+
+fn translate_ssl_protocol(args: &[String]) -> Option<String> {
+    let mut enabled: Vec<&str> = Vec::new();
+    for arg in args {
+        let (remove, name) = match arg.chars().next() {
+            Some('-') => (true, &arg[1..]),
+            Some('+') => (false, &arg[1..]),
+            _ => (false, arg.as_str()),
+        };
+        let names: Vec<&str> = if name.eq_ignore_ascii_case("all") {
+            TLS_PROTOCOLS.to_vec()
+        } else if name.eq_ignore_ascii_case("SSLv3") || name.eq_ignore_ascii_case("SSLv2") {
+            continue;
+        } else {
+            vec![*TLS_PROTOCOLS.iter().find(|protocol| protocol.eq_ignore_ascii_case(name))?]
+        };
+        for name in names {
+            if remove {
+                enabled.retain(|existing| *existing != name);
+            } else if !enabled.contains(&name) {
+                enabled.push(name);
+            }
+        }
+    }
+    enabled.sort_by_key(|name| TLS_PROTOCOLS.iter().position(|protocol| protocol == name));
+    (!enabled.is_empty()).then(|| enabled.join(" "))
+}
+
+/// Apache 重定向状态对应的 rewrite 标志
+fn redirect_flag(status: Option<&str>) -> Option<&'static str> {
+    match status.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("temp") | Some("302") => Some("redirect"),
+        Some("permanent") | Some("301") => Some("permanent"),
+        _ => None,
+    }
+}
+
+struct ApacheVirtualHost {
+    draft: ServerDraft,
+    source: String,
+    line: usize,
+    ssl: bool,
+    preserve_host: bool,
+    /// 上一条 RewriteRule 之后出现的 RewriteCond
+    pending_condition: bool,
+    proxies: Vec<(usize, String, String)>,
+    reverse_proxies: Vec<(String, String)>,
+}
+
+impl ApacheVirtualHost {
+    fn new(args: &str, line: usize) -> Self {
+        let mut draft = ServerDraft::default();
+        for address in args.split_whitespace() {
+            draft.add_listen(apache_listen(address));
+        }
+        if draft.listen.is_empty() {
+            draft.add_listen("80".to_string());
+        }
+        Self {
+            draft,
+            source: format!("VirtualHost {}", args.trim()),
+            line,
+            ssl: false,
+            preserve_host: false,
+            pending_condition: false,
+            proxies: Vec::new(),
+            reverse_proxies: Vec::new(),
+        }
+    }
+
+    fn directive(&mut self, line: usize, name: &str, args: &[String]) {
+        let lower = name.to_ascii_lowercase();
+        let draft = &mut self.draft;
+        match (lower.as_str(), args) {
+            ("servername", [value, ..]) => draft.add_name(apache_host_name(value)),
+            ("serveralias", values) => values.iter().for_each(|value| draft.add_name(value)),
+            ("documentroot", [path]) => draft.push("root", quote_if_needed(path)),
+            ("directoryindex", values) if !values.is_empty() => draft.push("index", values.join(" ")),
+            ("errorlog", [path]) | ("customlog", [path, ..]) if path.contains("${") || path.starts_with('|') => {
+                draft.issue(line, name, "日志路径包含 Apache 变量或管道，请手动设置")
+            }
+            ("errorlog", [path]) => draft.push("error_log", quote_if_needed(path)),
+            ("customlog", [path, rest @ ..]) => {
+                draft.push("access_log", quote_if_needed(path));
+                if let Some(format) = rest.first().filter(|format| !matches!(format.as_str(), "combined" | "common")) {
+                    draft.issue(line, name, format!("日志格式 {} 未转换，使用 nginx 默认格式", format));
+                }
+            }
+            ("rewriteengine", _) | ("proxyrequests", _) => {}
+            ("sslengine", [value]) => self.ssl = value.eq_ignore_ascii_case("on"),
+            ("sslcertificatefile", [path]) => draft.push("ssl_certificate", quote_if_needed(path)),
+            ("sslcertificatekeyfile", [path]) => draft.push("ssl_certificate_key", quote_if_needed(path)),
+            ("sslcertificatechainfile", _) => {
+                draft.issue(line, name, "nginx 需要将中间证书合并到 ssl_certificate 文件中")
+            }
+            ("sslprotocol", values) => match translate_ssl_protocol(values) {
+                Some(protocols) => draft.push("ssl_protocols", protocols),
+                None => draft.issue(line, name, "无法识别的协议列表"),
+            },
+            ("sslciphersuite", [ciphers]) => draft.push("ssl_ciphers", quote_if_needed(ciphers)),
+            ("sslhonorcipherorder", [value]) => draft.push("ssl_prefer_server_ciphers", value.to_ascii_lowercase()),
+            ("proxypreservehost", [value]) => self.preserve_host = value.eq_ignore_ascii_case("on"),
+            ("proxypass", [path, target, rest @ ..]) if target != "!" => {
+                if !rest.is_empty() {
+                    draft.issue(line, name, format!("连接参数 {} 未转换", rest.join(" ")));
+                }
+                self.proxies.push((line, path.clone(), target.clone()));
+            }
+            ("proxypassreverse", [path, target]) => self.reverse_proxies.push((path.clone(), target.clone())),
+            ("header", values) => {
+                let values: Vec<&String> = values.iter().filter(|value| !value.eq_ignore_ascii_case("always")).collect();
+                match values.as_slice() {
+                    [action, header, value] if action.eq_ignore_ascii_case("set") || action.eq_ignore_ascii_case("append") => {
+                        draft.push("add_header", format!("{} {} always", header, quote_if_needed(value)))
+                    }
+                    _ => draft.issue(line, name, "仅支持 Header set / append"),
+                }
+            }
+            ("redirect", values) => {
+                let (status, path, target) = match values {
+                    [path, target] if path.starts_with('/') => (None, path, target),
+                    [status, path, target] => (Some(status.as_str()), path, target),
+                    _ => return draft.issue(line, name, "不支持的 Redirect 形式"),
+                };
+                let Some(flag) = redirect_flag(status) else {
+                    return draft.issue(line, name, "仅支持 301 / 302 重定向");
+                };
+                let suffix = if path.ends_with('/') { "(.*)" } else { "(/.*)?" };
+                let pattern = format!("^{}{}$", regex::escape(path), suffix);
+                draft.push("rewrite", format!("{} {}$1 {}", quote_if_needed(&pattern), target, flag));
+            }
+            ("redirectmatch", values) => {
+                let (status, pattern, target) = match values {
+                    [pattern, target] => (None, pattern, target),
+                    [status, pattern, target] => (Some(status.as_str()), pattern, target),
+                    _ => return draft.issue(line, name, "不支持的 RedirectMatch 形式"),
+                };
+                match redirect_flag(status) {
+                    Some(flag) => draft.push("rewrite", format!("{} {} {}", quote_if_needed(pattern), target, flag)),
+                    None => draft.issue(line, name, "仅支持 301 / 302 重定向"),
+                }
+            }
+            ("rewritecond", _) => self.pending_condition = true,
+            ("rewriterule", [pattern, substitution, rest @ ..]) => {
+                let has_condition = std::mem::take(&mut self.pending_condition);
+                if has_condition {
+                    return draft.issue(line, name, "规则依赖 RewriteCond，需改写为 nginx 的 if 或 map");
+                }
+                match translate_rewrite_rule(pattern, substitution, rest.first().map(String::as_str)) {
+                    Ok(value) => draft.push("rewrite", value),
+                    Err(reason) => draft.issue(line, name, reason),
+                }
+            }
+            _ => draft.issue(line, name, "不支持的 Apache 指令"),
+        }
+    }
+
+    fn finish(mut self) -> ImportedServer {
+        if self.ssl {
+            self.draft.listen = self.draft.listen.iter().map(|listen| format!("{} ssl", listen)).collect();
+        }
+
+        for (line, path, target) in std::mem::take(&mut self.proxies) {
+            let mut directives = proxy_location_directives(&target, self.preserve_host);
+            if let Some((_, reverse)) = self
+                .reverse_proxies
+                .iter()
+                .find(|(reverse_path, reverse)| reverse_path == &path && reverse != &target)
+            {
+                directives.push(directive("proxy_redirect", format!("{} {}", reverse, path)));
+            }
+            let location = self.draft.location_mut(&path, None);
+            if location.directives.is_empty() {
+                location.directives = directives;
+            } else {
+                self.draft.issue(line, "ProxyPass", format!("路径 {} 重复代理，仅保留第一条", path));
+            }
+        }
+        self.draft.finish(self.source, self.line)
+    }
+}
+
+/// RewriteRule 转换为 rewrite 指令的参数
+fn translate_rewrite_rule(pattern: &str, substitution: &str, flags: Option<&str>) -> Result<String, String> {
+    if substitution == "-" {
+        return Err("不改写 URL 的规则（-）无法转换".to_string());
+    }
+    if pattern.starts_with('!') || pattern.contains("%{") || substitution.contains("%{") {
+        return Err("规则包含取反或服务器变量，请手动转换".to_string());
+    }
+
+    let mut pattern = pattern.to_string();
+    let mut flag = None;
+    let flags = flags.map(|flags| flags.trim_start_matches('[').trim_end_matches(']')).unwrap_or_default();
+    for item in flags.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+        let upper = item.to_ascii_uppercase();
+        match upper.as_str() {
+            "L" | "LAST" => {
+                flag.get_or_insert("last");
+            }
+            "END" => {
+                flag.get_or_insert("break");
+            }
+            "NC" | "NOCASE" => pattern = format!("(?i){}", pattern),
+            "R" | "R=302" => flag = Some("redirect"),
+            "R=301" => flag = Some("permanent"),
+            "QSA" if !substitution.contains('?') => {}
+            _ => return Err(format!("标志 {} 无法转换", item)),
+        }
+    }
+
+    let mut value = format!("{} {}", quote_if_needed(&pattern), quote_if_needed(substitution));
+    if let Some(flag) = flag {
+        value.push(' ');
+        value.push_str(flag);
+    }
+    Ok(value)
+}
+
+pub(crate) fn import_apache(content: &str) -> (Vec<ImportedServer>, Vec<ImportIssue>) {
+    let mut servers = Vec::new();
+    let mut issues = Vec::new();
+    let mut current: Option<ApacheVirtualHost> = None;
+    // 跳过的 <Directory> 等区块嵌套深度
+    let mut skip_depth = 0usize;
+    // VirtualHost 之外被忽略的全局指令及其行号
+    let mut skipped_globals: Vec<(usize, String)> = Vec::new();
+
+    for (line, text) in logical_lines(content) {
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+
+        if let Some(closing) = text.strip_prefix("</") {
+            if skip_depth > 0 {
+                skip_depth -= 1;
+            } else if closing.to_ascii_lowercase().starts_with("virtualhost") {
+                if let Some(vhost) = current.take() {
+                    servers.push(vhost.finish());
+                }
+            }
+            continue;
+        }
+
+        if let Some(opening) = text.strip_prefix('<') {
+            let opening = opening.trim_end_matches('>');
+            let (section, args) = opening.split_once(char::is_whitespace).unwrap_or((opening, ""));
+            if skip_depth > 0 {
+                skip_depth += 1;
+            } else if section.eq_ignore_ascii_case("VirtualHost") {
+                current = Some(ApacheVirtualHost::new(args, line));
+            } else if !section.eq_ignore_ascii_case("IfModule") {
+                skip_depth = 1;
+                let reason = format!("<{}> 区块未转换，请手动改写为 location", section);
+                match current.as_mut() {
+                    Some(vhost) => vhost.draft.issue(line, section, reason),
+                    None => issues.push(issue(line, section, reason)),
+                }
+            }
+            continue;
+        }
+
+        if skip_depth > 0 {
+            continue;
+        }
+        let tokens = split_tokens(&text);
+        let Some((name, args)) = tokens.split_first() else {
+            continue;
+        };
+        match current.as_mut() {
+            Some(vhost) => vhost.directive(line, name, args),
+            None if name.eq_ignore_ascii_case("Include") || name.eq_ignore_ascii_case("IncludeOptional") => {
+                issues.push(issue(line, name, "包含的文件需单独导入"))
+            }
+            None => skipped_globals.push((line, name.clone())),
+        }
+    }
+
+    if let Some((first_line, _)) = skipped_globals.first() {
+        let listed: Vec<String> = skipped_globals
+            .iter()
+            .map(|(line, name)| format!("{}（第 {} 行）", name, line))
+            .collect();
+        issues.push(issue(
+            *first_line,
+            "全局指令",
+            format!("VirtualHost 之外的指令未导入：{}，请手动确认是否需要迁移", listed.join("、")),
+        ));
+    }
+
+    if let Some(vhost) = current {
+        issues.push(issue(vhost.line, "VirtualHost", "VirtualHost 缺少结束标签"));
+        servers.push(vhost.finish());
+    }
+    (servers, issues)
+}
+
+// ==================== Caddyfile ====================
+
+/// Caddy 占位符转换为 nginx 变量
+fn translate_placeholders(value: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or_else(|| format!("占位符未闭合：{}", value))? + start;
+        let variable = match &rest[start + 1..end] {
+            "uri" | "http.request.uri" => "$request_uri",
+            "path" | "http.request.uri.path" => "$uri",
+            "query" | "http.request.uri.query" => "$args",
+            "host" | "http.request.host" => "$host",
+            "scheme" | "http.request.scheme" => "$scheme",
+            "remote_host" | "http.request.remote.host" => "$remote_addr",
+            other => return Err(format!("占位符 {{{}}} 无法转换", other)),
+        };
+        result.push_str(variable);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Caddy 路径匹配器对应的 location（修饰符，路径）
+fn caddy_matcher_location(matcher: &str) -> Option<(Option<&'static str>, String)> {
+    if matcher == "*" {
+        return Some((None, "/".to_string()));
+    }
+    if !matcher.starts_with('/') {
+        return None;
+    }
+    match matcher.strip_suffix('*') {
+        Some(prefix) if !prefix.contains('*') => Some((None, prefix.to_string())),
+        Some(_) => None,
+        None if matcher.contains('*') => None,
+        None => Some((Some("="), matcher.to_string())),
+    }
+}
+
+fn is_caddy_matcher(token: &str) -> bool {
+    token == "*" || token.starts_with('/') || token.starts_with('@')
+}
+
+fn caddy_upstream(upstream: &str) -> Result<String, String> {
+    let (scheme, address) = match upstream.split_once("://") {
+        Some((scheme @ ("http" | "https"), address)) => (scheme, address),
+        Some((scheme, _)) => return Err(format!("不支持的上游协议 {}", scheme)),
+        None => ("http", upstream),
+    };
+    let address = if address.starts_with(':') {
+        format!("127.0.0.1{}", address)
+    } else {
+        address.to_string()
+    };
+    Ok(format!("{}://{}", scheme, address))
+}
+
+struct CaddySite {
+    draft: ServerDraft,
+    source: String,
+    line: usize,
+    /// 站点地址需要 HTTPS（Caddy 自动证书或显式 https://）
+    wants_tls: bool,
+    plain_ports: Vec<String>,
+    has_certificate: bool,
+}
+
+impl CaddySite {
+    fn new(addresses: &[String], line: usize) -> Self {
+        let mut draft = ServerDraft::default();
+        let mut wants_tls = false;
+        let mut plain_ports = Vec::new();
+
+        for address in addresses.iter().flat_map(|address| address.split(',')).filter(|address| !address.is_empty()) {
+            let (scheme, rest) = match address.split_once("://") {
+                Some((scheme, rest)) => (Some(scheme), rest),
+                None => (None, address),
+            };
+            let rest = rest.split('/').next().unwrap_or_default();
+            let (host, port) = match rest.rsplit_once(':') {
+                Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => (host, Some(port)),
+                _ => (rest, None),
+            };
+            draft.add_name(host);
+            match (scheme, port) {
+                (_, Some(port)) => plain_ports.push(port.to_string()),
+                (Some("http"), None) => plain_ports.push("80".to_string()),
+                _ => wants_tls = true,
+            }
+        }
+
+        Self {
+            draft,
+            source: addresses.join(" "),
+            line,
+            wants_tls,
+            plain_ports,
+            has_certificate: false,
+        }
+    }
+
+    fn directive(&mut self, line: usize, name: &str, args: &[String]) {
+        let draft = &mut self.draft;
+        if args.first().is_some_and(|arg| arg.starts_with('@')) || name.starts_with('@') {
+            return draft.issue(line, name, "命名匹配器无法转换");
+        }
+
+        match (name, args) {
+            ("root", [path]) | ("root", [_, path]) if args.len() == 1 || args[0] == "*" => {
+                draft.push("root", quote_if_needed(path))
+            }
+            ("file_server", []) => {}
+            ("file_server", [option]) if option == "browse" => draft.push("autoindex", "on"),
+            ("encode", encodings) => {
+                if encodings.iter().any(|encoding| encoding == "gzip") {
+                    draft.push("gzip", "on");
+                }
+                if encodings.iter().any(|encoding| encoding != "gzip") {
+                    draft.issue(line, name, "仅转换 gzip 压缩");
+                }
+            }
+            ("tls", [certificate, key]) => {
+                draft.push("ssl_certificate", quote_if_needed(certificate));
+                draft.push("ssl_certificate_key", quote_if_needed(key));
+                self.has_certificate = true;
+            }
+            ("header", [header, value]) if !header.starts_with(['-', '+', '>', '/']) => {
+                draft.push("add_header", format!("{} {} always", header, quote_if_needed(value)))
+            }
+            ("try_files", files) if !files.is_empty() => {
+                match files.iter().map(|file| translate_placeholders(file)).collect::<Result<Vec<_>, _>>() {
+                    Ok(files) => draft.push("try_files", files.join(" ")),
+                    Err(reason) => draft.issue(line, name, reason),
+                }
+            }
+            ("reverse_proxy", args) if !args.is_empty() => {
+                let (matcher, upstreams) = match args.split_first() {
+                    Some((first, rest)) if is_caddy_matcher(first) && !rest.is_empty() => (first.as_str(), rest),
+                    _ => ("*", args),
+                };
+                let Some((modifier, path)) = caddy_matcher_location(matcher) else {
+                    return draft.issue(line, name, format!("匹配器 {} 无法转换", matcher));
+                };
+                let upstream = match caddy_upstream(&upstreams[0]) {
+                    Ok(upstream) => upstream,
+                    Err(reason) => return draft.issue(line, name, reason),
+                };
+                if upstreams.len() > 1 {
+                    draft.issue(line, name, "多个上游需手动配置 upstream 负载均衡，仅使用第一个");
+                }
+                // Caddy 默认透传 Host 请求头
+                draft.location_mut(&path, modifier).directives.extend(proxy_location_directives(&upstream, true));
+            }
+            ("redir", args) => {
+                let is_code = |token: &str| matches!(token, "permanent" | "temporary" | "html") || token.parse::<u16>().is_ok();
+                let (matcher, target, code) = match args {
+                    [target] => (None, target, None),
+                    [target, code] if is_code(code) => (None, target, Some(code.as_str())),
+                    [matcher, target] => (Some(matcher.as_str()), target, None),
+                    [matcher, target, code] => (Some(matcher.as_str()), target, Some(code.as_str())),
+                    _ => return draft.issue(line, name, "不支持的 redir 形式"),
+                };
+                let code = match code {
+                    None | Some("temporary") => "302".to_string(),
+                    Some("permanent") => "301".to_string(),
+                    Some(code) if code.parse::<u16>().is_ok_and(|code| (300..400).contains(&code)) => code.to_string(),
+                    Some(code) => return draft.issue(line, name, format!("重定向方式 {} 无法转换", code)),
+                };
+                let target = match translate_placeholders(target) {
+                    Ok(target) => target,
+                    Err(reason) => return draft.issue(line, name, reason),
+                };
+                let value = format!("{} {}", code, quote_if_needed(&target));
+                match matcher.map(caddy_matcher_location) {
+                    None => draft.push("return", value),
+                    Some(Some((modifier, path))) => draft.location_mut(&path, modifier).directives.push(directive("return", value)),
+                    Some(None) => draft.issue(line, name, "匹配器无法转换"),
+                }
+            }
+            ("respond", args) => {
+                let value = match args {
+                    [status] if status.parse::<u16>().is_ok() => status.clone(),
+                    [body] => format!("200 {}", quote_if_needed(body)),
+                    [body, status] if status.parse::<u16>().is_ok() => format!("{} {}", status, quote_if_needed(body)),
+                    _ => return draft.issue(line, name, "不支持的 respond 形式"),
+                };
+                draft.push("return", value);
+            }
+            ("php_fastcgi", [address]) => {
+                let address = match address.strip_prefix("unix/") {
+                    Some(path) => format!("unix:{}", path),
+                    None => address.clone(),
+                };
+                draft.push("index", "index.php index.html");
+                draft
+                    .location_mut("/", None)
+                    .directives
+                    .push(directive("try_files", "$uri $uri/ /index.php?$query_string"));
+                draft.location_mut("\\.php$", Some("~")).directives.extend([
+                    directive("try_files", "$uri =404"),
+                    directive("fastcgi_pass", address),
+                    directive("fastcgi_index", "index.php"),
+                    directive("include", "fastcgi_params"),
+                    directive("fastcgi_param", "SCRIPT_FILENAME $document_root$fastcgi_script_name"),
+                ]);
+            }
+            _ => draft.issue(line, name, "不支持的 Caddy 指令或参数"),
+        }
+    }
+
+    fn finish(mut self) -> ImportedServer {
+        for port in std::mem::take(&mut self.plain_ports) {
+            self.draft.add_listen(port);
+        }
+        if self.wants_tls {
+            if self.has_certificate {
+                self.draft.add_listen("443 ssl".to_string());
+            } else {
+                self.draft.add_listen("80".to_string());
+                self.draft.issue(
+                    self.line,
+                    "tls",
+                    "Caddy 自动 HTTPS 未转换，已改为监听 80 端口，请配置证书后启用 HTTPS",
+                );
+            }
+        }
+        self.draft.finish(self.source, self.line)
+    }
+}
+
+pub(crate) fn import_caddy(content: &str) -> (Vec<ImportedServer>, Vec<ImportIssue>) {
+    let mut servers = Vec::new();
+    let mut issues = Vec::new();
+    let mut current: Option<CaddySite> = None;
+    // 当前站点是否使用花括号，未使用时整个文件只有一个站点
+    let mut braced = false;
+    // 跳过的块深度（全局选项、代码片段、子指令块）
+    let mut skip_depth = 0usize;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = index + 1;
+        let text = raw.split(" #").next().unwrap_or_default().trim();
+        if text.is_empty() || text.starts_with('#') {
+            continue;
+        }
+        let mut tokens = split_tokens(text);
+        let opens = tokens.last().is_some_and(|token| token.ends_with('{'));
+        if opens {
+            let last = tokens.pop().unwrap_or_default();
+            let rest = last.trim_end_matches('{');
+            if !rest.is_empty() {
+                tokens.push(rest.to_string());
+            }
+        }
+        let closes = tokens.len() == 1 && tokens[0] == "}";
+
+        if skip_depth > 0 {
+            if closes {
+                skip_depth -= 1;
+            } else if opens {
+                skip_depth += 1;
+            }
+            continue;
+        }
+
+        if closes {
+            if braced {
+                if let Some(site) = current.take() {
+                    servers.push(site.finish());
+                }
+                braced = false;
+            }
+            continue;
+        }
+
+        if current.is_none() {
+            // 顶层：全局选项、代码片段或站点地址
+            match tokens.first() {
+                None if opens => {
+                    skip_depth = 1;
+                    issues.push(issue(line, "{", "全局选项未转换"));
+                }
+                Some(first) if first.starts_with('(') => {
+                    skip_depth = usize::from(opens);
+                    issues.push(issue(line, first, "代码片段未转换，请在站点中展开后再导入"));
+                }
+                Some(first) if first == "import" => issues.push(issue(line, first, "import 的内容未转换")),
+                Some(_) => {
+                    current = Some(CaddySite::new(&tokens, line));
+                    braced = opens;
+                }
+                None => {}
+            }
+            continue;
+        }
+
+        let Some(site) = current.as_mut() else {
+            continue;
+        };
+        let Some((name, args)) = tokens.split_first() else {
+            continue;
+        };
+        if opens {
+            skip_depth = 1;
+            site.draft.issue(line, name, "带子指令块的指令未转换");
+            continue;
+        }
+        site.directive(line, name, args);
+    }
+
+    if let Some(site) = current {
+        if braced {
+            issues.push(issue(site.line, &site.source, "站点块缺少结束花括号"));
+        }
+        servers.push(site.finish());
+    }
+    (servers, issues)
+}
+
+/// 导入 Apache 或 Caddy 配置，转换为待审核的 server 块
+#[tauri::command]
+pub async fn import_foreign_config(source_path: String, format: ImportSourceFormat) -> Result<ImportReport, String> {
+    let content = fs::read_to_string(&source_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let (servers, issues) = match format {
+        ImportSourceFormat::Apache => import_apache(&content),
+        ImportSourceFormat::Caddy => import_caddy(&content),
+    };
+    if servers.is_empty() {
+        return Err("未在文件中找到可导入的站点".to_string());
+    }
+
+    Ok(ImportReport {
+        format,
+        source_path,
+        servers,
+        issues,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    fn values<'a>(directives: &'a [DirectiveInput], name: &str) -> Vec<&'a str> {
+        directives
+            .iter()
+            .filter(|directive| directive.name == name)
+            .map(|directive| directive.value.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn apache_virtual_hosts_should_convert_with_issues() {
+        let content = r#"Listen 80
+ServerName global.example.com
+<VirtualHost *:80>
+    ServerName example.com:80
+    ServerAlias www.example.com
+    DocumentRoot "/var/www/example"
+    Redirect permanent /old https://example.com/new
+    RewriteEngine On
+    RewriteCond %{HTTPS} off
+    RewriteRule ^/(.*)$ https://example.com/$1 [R=301,L]
+    RewriteRule ^/blog/(.*)$ /news/$1 [L,NC]
+    <Directory /var/www/example>
+        AllowOverride All
+    </Directory>
+</VirtualHost>
+<IfModule mod_ssl.c>
+<VirtualHost *:443>
+    ServerName example.com
+    SSLEngine on
+    SSLCertificateFile /etc/ssl/example.crt
+    SSLCertificateKeyFile /etc/ssl/example.key
+    SSLProtocol all -SSLv3 -TLSv1 -TLSv1.1
+    ProxyPreserveHost On
+    ProxyPass /api/ http://127.0.0.1:8080/ \
+        retry=0
+    ProxyPassReverse /api/ http://127.0.0.1:8080/
+    Header always set X-Frame-Options DENY
+    SetEnvIf Request_URI "^/health" dontlog
+</VirtualHost>
+</IfModule>
+"#;
+        let (servers, issues) = import_apache(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, 1);
+        assert!(issues[0].reason.contains("Listen（第 1 行）、ServerName（第 2 行）"));
+        assert_eq!(servers.len(), 2);
+
+        let http = &servers[0];
+        assert_eq!(http.server.listen, vec!["80"]);
+        assert_eq!(http.server.server_name, vec!["example.com", "www.example.com"]);
+        assert_eq!(values(&http.server.directives, "root"), vec!["/var/www/example"]);
+        assert_eq!(
+            values(&http.server.directives, "rewrite"),
+            vec![
+                "^/old(/.*)?$ https://example.com/new$1 permanent",
+                "(?i)^/blog/(.*)$ /news/$1 last",
+            ]
+        );
+        let reasons: Vec<&str> = http.issues.iter().map(|issue| issue.directive.as_str()).collect();
+        assert_eq!(reasons, vec!["RewriteRule", "Directory"]);
+
+        let https = &servers[1];
+        assert_eq!(https.server.listen, vec!["443 ssl"]);
+        assert_eq!(values(&https.server.directives, "ssl_protocols"), vec!["TLSv1.2 TLSv1.3"]);
+        assert_eq!(values(&https.server.directives, "add_header"), vec!["X-Frame-Options DENY always"]);
+        assert_eq!(https.locations[0].path, "/api/");
+        assert_eq!(values(&https.locations[0].directives, "proxy_pass"), vec!["http://127.0.0.1:8080/"]);
+        assert!(values(&https.locations[0].directives, "proxy_set_header").contains(&"Host $host"));
+        assert!(values(&https.locations[0].directives, "proxy_redirect").is_empty());
+        let reasons: Vec<&str> = https.issues.iter().map(|issue| issue.directive.as_str()).collect();
+        assert_eq!(reasons, vec!["ProxyPass", "SetEnvIf"]);
+
+        let parsed = parse_nginx_config(&https.server_text, "").unwrap();
+        assert_eq!(parsed.servers.len(), 1);
+        assert_eq!(parsed.servers[0].locations.len(), 1);
+    }
+
+    #[test]
+    fn caddyfile_sites_should_convert_with_issues() {
+        let content = r#"{
+    email admin@example.com
+}
+
+example.com, www.example.com {
+    root * /srv/site
+    encode gzip zstd
+    file_server
+    reverse_proxy /api/* localhost:3000
+    redir /old /new permanent
+    header X-Frame-Options DENY
+    @static path *.css
+    basicauth {
+        bob hash
+    }
+    try_files {path} /index.html
+}
+
+:8080 {
+    respond "service ok" 200
+}
+"#;
+        let (servers, issues) = import_caddy(content);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(servers.len(), 2);
+
+        let site = &servers[0];
+        assert_eq!(site.server.listen, vec!["80"]);
+        assert_eq!(site.server.server_name, vec!["example.com", "www.example.com"]);
+        assert_eq!(values(&site.server.directives, "root"), vec!["/srv/site"]);
+        assert_eq!(values(&site.server.directives, "gzip"), vec!["on"]);
+        assert_eq!(values(&site.server.directives, "try_files"), vec!["$uri /index.html"]);
+        assert_eq!(site.locations[0].path, "/api/");
+        assert_eq!(values(&site.locations[0].directives, "proxy_pass"), vec!["http://localhost:3000"]);
+        assert_eq!(site.locations[1].modifier.as_deref(), Some("="));
+        assert_eq!(values(&site.locations[1].directives, "return"), vec!["301 /new"]);
+        let reasons: Vec<&str> = site.issues.iter().map(|issue| issue.directive.as_str()).collect();
+        assert_eq!(reasons, vec!["encode", "@static", "basicauth", "tls"]);
+
+        let status = &servers[1];
+        assert_eq!(status.server.listen, vec!["8080"]);
+        assert!(status.server.server_name.is_empty());
+        assert_eq!(values(&status.server.directives, "return"), vec!["200 \"service ok\""]);
+
+        for server in &servers {
+            assert_eq!(parse_nginx_config(&server.server_text, "").unwrap().servers.len(), 1);
+        }
+    }
+}
@@ -0,0 +1,900 @@
+use crate::audit::audited;
+use crate::change_plan::{ensure_plan_valid, validate_changes, PlanValidation};
+use crate::config::{
+    find_block_end, find_http_block, parse_directive, parse_nginx_config, render_server_block_by_state, write_config_file,
+    Directive, DirectiveInput, EditResult, LocationBlock, LocationBlockInput, NginxConfig, ServerBlock, ServerBlockInput,
+};
+use crate::config_drift::fingerprint_config_files;
+use crate::config_import::render_server_text;
+use crate::config_text::{apply_edits, indent_of, indent_lines, render_location_block, TextEdit, INDENT_UNIT};
+use crate::config_watcher::resolve_config_files;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// 模型格式版本，结构不兼容变更时递增
+pub const CONFIG_MODEL_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDirective {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationModel {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modifier: Option<String>,
+    pub path: String,
+    #[serde(default)]
+    pub directives: Vec<ModelDirective>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerModel {
+    /// 用于与现有配置匹配的标识，缺省为「首个域名@首个监听」
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub listen: Vec<String>,
+    #[serde(default)]
+    pub server_name: Vec<String>,
+    /// 除 listen / server_name 外的 server 级指令
+    #[serde(default)]
+    pub directives: Vec<ModelDirective>,
+    #[serde(default)]
+    pub locations: Vec<LocationModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpstreamModel {
+    pub name: String,
+    #[serde(default)]
+    pub directives: Vec<ModelDirective>,
+}
+
+/// 声明式配置模型，导出与应用共用同一结构
+///
+/// 模型只覆盖主配置文件，include 文件中的 server / upstream 不参与导出与应用，
+/// 导出结果与变更计划通过 uncovered_files 列出这些文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigModel {
+    pub version: u32,
+    /// 为 true 时删除模型中未列出的 server / upstream，否则保留为非托管块
+    #[serde(default)]
+    pub prune: bool,
+    /// 仅导出，应用时忽略
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub global_directives: Vec<ModelDirective>,
+    #[serde(default)]
+    pub upstreams: Vec<UpstreamModel>,
+    #[serde(default)]
+    pub servers: Vec<ServerModel>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelChangeKind {
+    Add,
+    Update,
+    Remove,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelTarget {
+    Server,
+    Upstream,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelChange {
+    pub kind: ModelChangeKind,
+    pub target: ModelTarget,
+    pub key: String,
+    /// 变更明细，例如 "+ proxy_read_timeout 60s"
+    pub details: Vec<String>,
+}
+
+/// 应用模型前的变更计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigModelPlan {
+    pub config_path: String,
+    pub changes: Vec<ModelChange>,
+    /// 模型未涉及、保持不变的 server / upstream
+    pub unmanaged: Vec<String>,
+    pub has_changes: bool,
+    pub new_content: String,
+    pub validation: PlanValidation,
+    /// 配置原文与变更后内容的摘要，应用时据此确认执行的是已审阅的计划
+    pub digest: String,
+    /// 含有 server / upstream、但不在模型范围内的 include 文件
+    pub uncovered_files: Vec<String>,
+}
+
+/// 导出配置模型的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigModelExport {
+    pub output_path: String,
+    pub uncovered_files: Vec<String>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn model_directive(directive: &Directive) -> ModelDirective {
+    ModelDirective {
+        name: directive.name.clone(),
+        value: directive.value.clone(),
+    }
+}
+
+fn render_model_directive(directive: &ModelDirective) -> String {
+    if directive.value.is_empty() {
+        format!("{};", directive.name)
+    } else {
+        format!("{} {};", directive.name, directive.value)
+    }
+}
+
+/// 首个域名@首个监听端口，例如 example.com@443
+fn derived_server_key(server_name: &[String], listen: &[String]) -> String {
+    let name = server_name.first().map(String::as_str).unwrap_or("_");
+    let port = listen
+        .first()
+        .and_then(|listen| listen.split_whitespace().next())
+        .unwrap_or("80");
+    format!("{}@{}", name, port)
+}
+
+/// 为重复的标识追加 #2、#3 后缀
+fn unique_keys(keys: impl Iterator<Item = String>) -> Vec<String> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    keys.map(|key| {
+        let count = seen.entry(key.clone()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            key
+        } else {
+            format!("{}#{}", key, count)
+        }
+    })
+    .collect()
+}
+
+fn location_model(location: &LocationBlock) -> LocationModel {
+    LocationModel {
+        modifier: location.modifier.clone(),
+        path: location.path.clone(),
+        directives: location.directives.iter().map(model_directive).collect(),
+    }
+}
+
+fn server_model(server: &ServerBlock, key: Option<String>) -> ServerModel {
+    ServerModel {
+        key,
+        enabled: server.enabled,
+        listen: server.listen.clone(),
+        server_name: server.server_name.clone(),
+        directives: server
+            .directives
+            .iter()
+            .filter(|directive| directive.name != "listen" && directive.name != "server_name")
+            .map(model_directive)
+            .collect(),
+        locations: server.locations.iter().map(location_model).collect(),
+    }
+}
+
+/// server 级指令的期望顺序：listen、server_name、其余指令
+fn server_level_directives(model: &ServerModel) -> Vec<ModelDirective> {
+    let mut directives: Vec<ModelDirective> = model
+        .listen
+        .iter()
+        .map(|listen| ModelDirective {
+            name: "listen".to_string(),
+            value: listen.clone(),
+        })
+        .collect();
+    if !model.server_name.is_empty() {
+        directives.push(ModelDirective {
+            name: "server_name".to_string(),
+            value: model.server_name.join(" "),
+        });
+    }
+    directives.extend(model.directives.iter().cloned());
+    directives
+}
+
+fn directive_inputs(directives: &[ModelDirective]) -> Vec<DirectiveInput> {
+    directives
+        .iter()
+        .map(|directive| DirectiveInput {
+            name: directive.name.clone(),
+            value: directive.value.clone(),
+        })
+        .collect()
+}
+
+fn location_input(location: &LocationModel) -> LocationBlockInput {
+    LocationBlockInput {
+        path: location.path.clone(),
+        modifier: location.modifier.clone(),
+        directives: directive_inputs(&location.directives),
+    }
+}
+
+/// 渲染 server 模型（停用时带停用标记），每行加上 indent
+fn render_server_model(model: &ServerModel, indent: &str) -> String {
+    let input = ServerBlockInput {
+        listen: model.listen.clone(),
+        server_name: model.server_name.clone(),
+        directives: directive_inputs(&model.directives),
+    };
+    let locations: Vec<LocationBlockInput> = model.locations.iter().map(location_input).collect();
+    let text = render_server_block_by_state(&render_server_text(&input, &locations), model.enabled);
+    indent_lines(&text, indent)
+}
+
+fn render_location_model(location: &LocationModel, indent: &str) -> String {
+    format!("\n{}", render_location_block(indent, &location_input(location)))
+}
+
+fn render_upstream_model(upstream: &UpstreamModel, indent: &str) -> String {
+    let mut lines = vec![format!("{}upstream {} {{", indent, upstream.name)];
+    for directive in &upstream.directives {
+        lines.push(format!("{}{}{}", indent, INDENT_UNIT, render_model_directive(directive)));
+    }
+    lines.push(format!("{}}}", indent));
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+// ==================== 现有配置扫描 ====================
+
+struct UpstreamBlock {
+    name: String,
+    start: usize,
+    end: usize,
+    directives: Vec<Directive>,
+}
+
+/// 解析器不识别 upstream 块，这里单独扫描
+fn scan_upstreams(lines: &[&str]) -> Result<Vec<UpstreamBlock>, String> {
+    let mut upstreams = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let trimmed = lines[index].trim();
+        let name = trimmed
+            .strip_prefix("upstream")
+            .filter(|rest| rest.starts_with(char::is_whitespace) && trimmed.ends_with('{'))
+            .map(|rest| rest.trim_end_matches('{').trim().to_string());
+        let Some(name) = name else {
+            index += 1;
+            continue;
+        };
+
+        let end = find_block_end(lines, index).map_err(|e| format!("解析 upstream {} 失败: {}", name, e))?;
+        let directives = ((index + 1)..end)
+            .filter_map(|line| {
+                let text = lines[line].trim();
+                (!text.starts_with('#')).then(|| parse_directive(text, line + 1)).flatten()
+            })
+            .collect();
+        upstreams.push(UpstreamBlock {
+            name,
+            start: index,
+            end,
+            directives,
+        });
+        index = end + 1;
+    }
+    Ok(upstreams)
+}
+
+/// 将已解析的配置转换为模型
+pub(crate) fn build_config_model(config: &NginxConfig) -> Result<ConfigModel, String> {
+    let lines: Vec<&str> = config.raw_content.lines().collect();
+    let upstreams = scan_upstreams(&lines)?;
+    let in_upstream = |line: usize| upstreams.iter().any(|upstream| line > upstream.start && line <= upstream.end + 1);
+
+    let keys = unique_keys(config.servers.iter().map(|server| derived_server_key(&server.server_name, &server.listen)));
+    Ok(ConfigModel {
+        version: CONFIG_MODEL_VERSION,
+        prune: false,
+        global_directives: config
+            .global_directives
+            .iter()
+            .filter(|directive| !in_upstream(directive.line))
+            .map(model_directive)
+            .collect(),
+        upstreams: upstreams
+            .iter()
+            .map(|upstream| UpstreamModel {
+                name: upstream.name.clone(),
+                directives: upstream.directives.iter().map(model_directive).collect(),
+            })
+            .collect(),
+        servers: config
+            .servers
+            .iter()
+            .zip(keys)
+            .map(|(server, key)| server_model(server, Some(key)))
+            .collect(),
+    })
+}
+
+// ==================== 差异计算 ====================
+
+/// 基于最长公共子序列比较指令，生成删除与插入编辑，未变化的行（含注释）保持原样
+fn diff_directives(
+    current: &[Directive],
+    desired: &[ModelDirective],
+    open_line: usize,
+    indent: &str,
+    edits: &mut Vec<TextEdit>,
+    details: &mut Vec<String>,
+) {
+    let current_models: Vec<ModelDirective> = current.iter().map(model_directive).collect();
+    let (rows, cols) = (current_models.len(), desired.len());
+    let mut table = vec![vec![0usize; cols + 1]; rows + 1];
+    for i in (0..rows).rev() {
+        for j in (0..cols).rev() {
+            table[i][j] = if current_models[i] == desired[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut anchor = open_line;
+    while i < rows || j < cols {
+        if i < rows && j < cols && current_models[i] == desired[j] {
+            anchor = current[i].line - 1;
+            i += 1;
+            j += 1;
+        } else if i < rows && (j == cols || table[i + 1][j] >= table[i][j + 1]) {
+            anchor = current[i].line - 1;
+            edits.push(TextEdit::Delete(anchor));
+            details.push(format!("- {}", render_model_directive(&current_models[i])));
+            i += 1;
+        } else {
+            let text = render_model_directive(&desired[j]);
+            edits.push(TextEdit::InsertAfter(anchor, format!("{}{}", indent, text)));
+            details.push(format!("+ {}", text));
+            j += 1;
+        }
+    }
+}
+
+fn directive_indent(lines: &[&str], directives: &[Directive], open_line: usize) -> String {
+    directives
+        .first()
+        .map(|directive| indent_of(lines[directive.line - 1]).to_string())
+        .unwrap_or_else(|| format!("{}{}", indent_of(lines[open_line]), INDENT_UNIT))
+}
+
+fn location_label(modifier: &Option<String>, path: &str) -> String {
+    match modifier {
+        Some(modifier) => format!("location {} {}", modifier, path),
+        None => format!("location {}", path),
+    }
+}
+
+fn delete_range(edits: &mut Vec<TextEdit>, start: usize, end: usize) {
+    edits.extend((start..=end).map(TextEdit::Delete));
+}
+
+/// 比较已启用的 server 与模型，逐条指令与 location 生成编辑
+fn diff_enabled_server(lines: &[&str], server: &ServerBlock, desired: &ServerModel, edits: &mut Vec<TextEdit>) -> Vec<String> {
+    let mut details = Vec::new();
+    let open_line = server.start_line - 1;
+    let indent = directive_indent(lines, &server.directives, open_line);
+    diff_directives(
+        &server.directives,
+        &server_level_directives(desired),
+        open_line,
+        &indent,
+        edits,
+        &mut details,
+    );
+
+    for location in &server.locations {
+        let kept = desired
+            .locations
+            .iter()
+            .any(|model| model.path == location.path && model.modifier == location.modifier);
+        if !kept {
+            delete_range(edits, location.start_line - 1, location.end_line - 1);
+            details.push(format!("- {}", location_label(&location.modifier, &location.path)));
+        }
+    }
+
+    let location_indent = format!("{}{}", indent_of(lines[open_line]), INDENT_UNIT);
+    for model in &desired.locations {
+        let current = server
+            .locations
+            .iter()
+            .find(|location| location.path == model.path && location.modifier == model.modifier);
+        match current {
+            Some(location) => {
+                let mut location_details = Vec::new();
+                let location_open = location.start_line - 1;
+                let indent = directive_indent(lines, &location.directives, location_open);
+                diff_directives(
+                    &location.directives,
+                    &model.directives,
+                    location_open,
+                    &indent,
+                    edits,
+                    &mut location_details,
+                );
+                let label = location_label(&location.modifier, &location.path);
+                details.extend(location_details.into_iter().map(|detail| format!("{}: {}", label, detail)));
+            }
+            None => {
+                edits.push(TextEdit::InsertAfter(server.end_line - 2, render_location_model(model, &location_indent)));
+                details.push(format!("+ {}", location_label(&model.modifier, &model.path)));
+            }
+        }
+    }
+    details
+}
+
+fn validate_model(model: &ConfigModel) -> Result<(), String> {
+    if model.version != CONFIG_MODEL_VERSION {
+        return Err(format!("不支持的模型版本 {}，当前版本为 {}", model.version, CONFIG_MODEL_VERSION));
+    }
+
+    let mut upstream_names = BTreeSet::new();
+    for upstream in &model.upstreams {
+        if upstream.name.trim().is_empty() || !upstream_names.insert(upstream.name.as_str()) {
+            return Err(format!("upstream 名称为空或重复：{}", upstream.name));
+        }
+    }
+    for server in &model.servers {
+        let mut locations = BTreeSet::new();
+        for location in &server.locations {
+            if !locations.insert((location.modifier.as_deref(), location.path.as_str())) {
+                return Err(format!("server {:?} 中存在重复的 location {}", server.server_name, location.path));
+            }
+        }
+    }
+
+    let directives = model
+        .upstreams
+        .iter()
+        .flat_map(|upstream| upstream.directives.iter())
+        .chain(model.servers.iter().flat_map(|server| {
+            server
+                .directives
+                .iter()
+                .chain(server.locations.iter().flat_map(|location| location.directives.iter()))
+        }));
+    for directive in directives {
+        if directive.name.trim().is_empty() || directive.name.contains(char::is_whitespace) {
+            return Err(format!("指令名称无效：{:?}", directive.name));
+        }
+        if directive.value.contains(['\n', '{', '}']) {
+            return Err(format!("指令 {} 的值不能包含换行或花括号", directive.name));
+        }
+    }
+    Ok(())
+}
+
+/// 计算将现有配置调整为模型所需的最小编辑
+pub(crate) fn plan_config_model_changes(config_path: &str, content: &str, model: &ConfigModel) -> Result<ConfigModelPlan, String> {
+    validate_model(model)?;
+    let config = parse_nginx_config(content, config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let upstreams = scan_upstreams(&lines)?;
+
+    let mut edits = Vec::new();
+    let mut changes = Vec::new();
+    let mut unmanaged = Vec::new();
+
+    // upstream
+    let mut new_upstreams = Vec::new();
+    for desired in &model.upstreams {
+        match upstreams.iter().find(|upstream| upstream.name == desired.name) {
+            Some(current) => {
+                let mut details = Vec::new();
+                let indent = directive_indent(&lines, &current.directives, current.start);
+                diff_directives(&current.directives, &desired.directives, current.start, &indent, &mut edits, &mut details);
+                if !details.is_empty() {
+                    changes.push(ModelChange {
+                        kind: ModelChangeKind::Update,
+                        target: ModelTarget::Upstream,
+                        key: desired.name.clone(),
+                        details,
+                    });
+                }
+            }
+            None => new_upstreams.push(desired),
+        }
+    }
+    for current in &upstreams {
+        if model.upstreams.iter().any(|desired| desired.name == current.name) {
+            continue;
+        }
+        if model.prune {
+            delete_range(&mut edits, current.start, current.end);
+            changes.push(ModelChange {
+                kind: ModelChangeKind::Remove,
+                target: ModelTarget::Upstream,
+                key: current.name.clone(),
+                details: Vec::new(),
+            });
+        } else {
+            unmanaged.push(format!("upstream {}", current.name));
+        }
+    }
+
+    // server
+    let current_keys = unique_keys(config.servers.iter().map(|server| derived_server_key(&server.server_name, &server.listen)));
+    let desired_keys = unique_keys(model.servers.iter().map(|server| {
+        server
+            .key
+            .clone()
+            .unwrap_or_else(|| derived_server_key(&server.server_name, &server.listen))
+    }));
+    if desired_keys.iter().collect::<BTreeSet<_>>().len() != desired_keys.len() {
+        return Err("模型中的 server 标识重复".to_string());
+    }
+
+    let mut new_servers = Vec::new();
+    for (desired, key) in model.servers.iter().zip(&desired_keys) {
+        let Some(index) = current_keys.iter().position(|current| current == key) else {
+            new_servers.push((desired, key));
+            continue;
+        };
+        let current = &config.servers[index];
+        if server_model(current, None) == (ServerModel { key: None, ..desired.clone() }) {
+            continue;
+        }
+
+        let details = if current.enabled && desired.enabled {
+            diff_enabled_server(&lines, current, desired, &mut edits)
+        } else {
+            // 启停状态变化或停用块内容变化时整体重写该 server
+            let indent = indent_of(lines[current.start_line - 1]).to_string();
+            edits.push(TextEdit::Replace(current.start_line - 1, render_server_model(desired, &indent)));
+            edits.extend((current.start_line..current.end_line).map(TextEdit::Delete));
+            let state = if desired.enabled { "启用" } else { "停用" };
+            vec![format!("整体重写 server（{}）", state)]
+        };
+        changes.push(ModelChange {
+            kind: ModelChangeKind::Update,
+            target: ModelTarget::Server,
+            key: key.clone(),
+            details,
+        });
+    }
+    for (current, key) in config.servers.iter().zip(&current_keys) {
+        if desired_keys.contains(key) {
+            continue;
+        }
+        if model.prune {
+            delete_range(&mut edits, current.start_line - 1, current.end_line - 1);
+            changes.push(ModelChange {
+                kind: ModelChangeKind::Remove,
+                target: ModelTarget::Server,
+                key: key.clone(),
+                details: Vec::new(),
+            });
+        } else {
+            unmanaged.push(format!("server {}", key));
+        }
+    }
+
+    if !new_upstreams.is_empty() || !new_servers.is_empty() {
+        let (http_start, http_end) = find_http_block(&lines).map_err(|e| format!("定位 http 块失败: {}", e))?;
+        let indent = format!("{}{}", indent_of(lines[http_start]), INDENT_UNIT);
+        for upstream in new_upstreams {
+            edits.push(TextEdit::InsertAfter(http_start, render_upstream_model(upstream, &indent)));
+            changes.push(ModelChange {
+                kind: ModelChangeKind::Add,
+                target: ModelTarget::Upstream,
+                key: upstream.name.clone(),
+                details: upstream.directives.iter().map(|d| format!("+ {}", render_model_directive(d))).collect(),
+            });
+        }
+        for (server, key) in new_servers {
+            edits.push(TextEdit::InsertAfter(http_end - 1, format!("\n{}", render_server_model(server, &indent))));
+            changes.push(ModelChange {
+                kind: ModelChangeKind::Add,
+                target: ModelTarget::Server,
+                key: key.clone(),
+                details: Vec::new(),
+            });
+        }
+    }
+
+    let new_content = if edits.is_empty() {
+        content.to_string()
+    } else {
+        apply_edits(&lines, &edits)
+    };
+    Ok(ConfigModelPlan {
+        config_path: config_path.to_string(),
+        has_changes: !changes.is_empty(),
+        changes,
+        unmanaged,
+        digest: plan_digest(config_path, content, &new_content),
+        new_content,
+        validation: PlanValidation {
+            performed: false,
+            success: true,
+            message: "配置无变化，无需校验".to_string(),
+        },
+        uncovered_files: Vec::new(),
+    })
+}
+
+fn plan_digest(config_path: &str, before: &str, after: &str) -> String {
+    let files = BTreeMap::from([
+        (config_path.to_string(), before.to_string()),
+        (format!("{}#planned", config_path), after.to_string()),
+    ]);
+    fingerprint_config_files(&files)
+}
+
+/// 主配置 include 的文件中含有 server / upstream 的文件
+fn uncovered_include_files(config_path: &str) -> Vec<String> {
+    resolve_config_files(config_path)
+        .into_iter()
+        .skip(1)
+        .filter(|path| {
+            let Ok(content) = fs::read_to_string(path) else {
+                return false;
+            };
+            let lines: Vec<&str> = content.lines().collect();
+            parse_nginx_config(&content, &path.to_string_lossy()).is_ok_and(|config| !config.servers.is_empty())
+                || scan_upstreams(&lines).is_ok_and(|upstreams| !upstreams.is_empty())
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+fn read_config_model(spec_path: &str) -> Result<ConfigModel, String> {
+    let content = fs::read_to_string(spec_path).map_err(|e| format!("读取模型文件失败: {}", e))?;
+    let extension = Path::new(spec_path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if matches!(extension.as_str(), "yaml" | "yml") {
+        serde_yaml::from_str(&content).map_err(|e| format!("解析 YAML 模型失败: {}", e))
+    } else {
+        serde_json::from_str(&content).map_err(|e| format!("解析 JSON 模型失败: {}", e))
+    }
+}
+
+/// 导出配置模型，format 为 json 或 yaml
+#[tauri::command]
+pub async fn export_config_model(config_path: String, output_path: String, format: String) -> Result<ConfigModelExport, String> {
+    let content = fs::read_to_string(&config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let config = parse_nginx_config(&content, &config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let model = build_config_model(&config)?;
+
+    let output = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&model).map_err(|e| format!("序列化模型失败: {}", e))?,
+        "yaml" => serde_yaml::to_string(&model).map_err(|e| format!("序列化模型失败: {}", e))?,
+        other => return Err(format!("不支持的导出格式：{}", other)),
+    };
+    fs::write(&output_path, output).map_err(|e| format!("写入导出文件失败: {}", e))?;
+
+    Ok(ConfigModelExport {
+        uncovered_files: uncovered_include_files(&config_path),
+        output_path,
+    })
+}
+
+/// 计算应用模型的变更计划并校验变更后的配置，不修改配置文件
+#[tauri::command]
+pub async fn plan_config_model(config_path: String, spec_path: String) -> Result<ConfigModelPlan, String> {
+    let model = read_config_model(&spec_path)?;
+    let content = fs::read_to_string(&config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let mut plan = plan_config_model_changes(&config_path, &content, &model)?;
+    if plan.has_changes {
+        plan.validation = validate_changes(&[(&config_path, &plan.new_content)]).await;
+    }
+    plan.uncovered_files = uncovered_include_files(&config_path);
+    Ok(plan)
+}
+
+/// 按模型修改配置文件，expected_digest 为已审阅计划的摘要
+#[tauri::command]
+pub async fn apply_config_model(
+    app: AppHandle,
+    config_path: String,
+    spec_path: String,
+    expected_digest: String,
+) -> Result<EditResult, String> {
+    let label = format!("应用配置模型 {}", spec_path);
+    audited(
+        &app,
+        "apply_config_model",
+        &label,
+        apply_config_model_internal(config_path, spec_path, expected_digest),
+    )
+    .await
+}
+
+async fn apply_config_model_internal(
+    config_path: String,
+    spec_path: String,
+    expected_digest: String,
+) -> Result<EditResult, String> {
+    if expected_digest.trim().is_empty() {
+        return Err("缺少已审阅的变更计划，请先生成变更计划".to_string());
+    }
+    let plan = plan_config_model(config_path.clone(), spec_path).await?;
+    if expected_digest != plan.digest {
+        return Err("配置文件或模型已变化，请重新生成变更计划".to_string());
+    }
+    if !plan.has_changes {
+        return Ok(EditResult {
+            success: true,
+            message: "配置已与模型一致，无需修改".to_string(),
+        });
+    }
+    ensure_plan_valid(&plan.validation)?;
+    write_config_file(&config_path, &plan.new_content)?;
+
+    Ok(EditResult {
+        success: true,
+        message: format!("已应用配置模型，共 {} 项变更", plan.changes.len()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"events {}
+http {
+    upstream app {
+        server 127.0.0.1:3000;
+    }
+
+    server {
+        # 主站
+        listen 80;
+        server_name example.com;
+        root html;
+
+        location / {
+            proxy_pass http://app;
+        }
+
+        location /old/ {
+            return 410;
+        }
+    }
+
+    server {
+        listen 8080;
+        server_name legacy.local;
+    }
+}"#;
+
+    fn model_of(content: &str) -> ConfigModel {
+        build_config_model(&parse_nginx_config(content, "nginx.conf").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn exported_model_should_round_trip_without_changes() {
+        let model = model_of(CONFIG);
+        assert_eq!(model.upstreams.len(), 1);
+        assert_eq!(model.upstreams[0].directives[0].value, "127.0.0.1:3000");
+        assert!(model.global_directives.iter().all(|directive| directive.name != "server"));
+        assert_eq!(model.servers[0].key.as_deref(), Some("example.com@80"));
+        assert_eq!(model.servers[0].directives, vec![ModelDirective { name: "root".to_string(), value: "html".to_string() }]);
+
+        let yaml = serde_yaml::to_string(&model).unwrap();
+        let parsed: ConfigModel = serde_yaml::from_str(&yaml).unwrap();
+        let plan = plan_config_model_changes("nginx.conf", CONFIG, &parsed).unwrap();
+        assert!(!plan.has_changes);
+        assert_eq!(plan.new_content, CONFIG);
+    }
+
+    #[test]
+    fn plan_should_apply_minimal_edits_and_keep_unmanaged_blocks() {
+        let mut model = model_of(CONFIG);
+        model.servers.truncate(1);
+        let server = &mut model.servers[0];
+        server.directives[0].value = "/srv/www".to_string();
+        server.locations.retain(|location| location.path != "/old/");
+        server.locations[0].directives.push(ModelDirective {
+            name: "proxy_set_header".to_string(),
+            value: "Host $host".to_string(),
+        });
+        server.locations.push(LocationModel {
+            modifier: Some("=".to_string()),
+            path: "/health".to_string(),
+            directives: vec![ModelDirective { name: "return".to_string(), value: "200".to_string() }],
+        });
+        model.upstreams[0].directives.push(ModelDirective {
+            name: "keepalive".to_string(),
+            value: "16".to_string(),
+        });
+        model.servers.push(ServerModel {
+            key: None,
+            enabled: false,
+            listen: vec!["9000".to_string()],
+            server_name: vec!["new.local".to_string()],
+            directives: Vec::new(),
+            locations: Vec::new(),
+        });
+
+        let plan = plan_config_model_changes("nginx.conf", CONFIG, &model).unwrap();
+        assert_eq!(plan.unmanaged, vec!["server legacy.local@8080"]);
+        let kinds: Vec<(ModelChangeKind, &str)> = plan.changes.iter().map(|change| (change.kind, change.key.as_str())).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (ModelChangeKind::Update, "app"),
+                (ModelChangeKind::Update, "example.com@80"),
+                (ModelChangeKind::Add, "new.local@9000"),
+            ]
+        );
+
+        let content = &plan.new_content;
+        assert!(content.contains("        # 主站\n        listen 80;\n        server_name example.com;\n        root /srv/www;\n"));
+        assert!(content.contains("        server 127.0.0.1:3000;\n        keepalive 16;\n"));
+        assert!(content.contains("            proxy_pass http://app;\n            proxy_set_header Host $host;\n"));
+        assert!(!content.contains("/old/"));
+        assert!(content.contains("        location = /health {\n            return 200;\n        }\n    }"));
+        assert!(content.contains("server_name legacy.local;"));
+        assert!(content.contains("    # nginx-config-manager managed-disabled-server begin\n    # server {\n    #     listen 9000;"));
+
+        let applied = model_of(content);
+        let mut expected = model.clone();
+        expected.servers.insert(1, model_of(CONFIG).servers.remove(1));
+        let strip = |servers: &[ServerModel]| servers.iter().map(|server| ServerModel { key: None, ..server.clone() }).collect::<Vec<_>>();
+        assert_eq!(strip(&applied.servers), strip(&expected.servers));
+        assert_eq!(applied.upstreams, expected.upstreams);
+
+        let again = plan_config_model_changes("nginx.conf", content, &model).unwrap();
+        assert!(!again.has_changes);
+        assert_ne!(again.digest, plan.digest);
+    }
+
+    #[test]
+    fn uncovered_include_files_should_list_includes_with_servers_or_upstreams() {
+        let dir = std::env::temp_dir().join(format!("ncm_config_model_includes_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        let config_path = dir.join("nginx.conf");
+        fs::write(&config_path, "events {}\nhttp {\n    include mime.types;\n    include conf.d/*.conf;\n}\n").unwrap();
+        fs::write(dir.join("mime.types"), "types {\n    text/html html;\n}\n").unwrap();
+        fs::write(dir.join("conf.d/app.conf"), "server {\n    listen 8080;\n}\n").unwrap();
+        fs::write(dir.join("conf.d/pool.conf"), "upstream pool {\n    server 127.0.0.1:3000;\n}\n").unwrap();
+
+        let files = uncovered_include_files(&config_path.to_string_lossy());
+        let names: Vec<String> = files
+            .iter()
+            .map(|file| Path::new(file).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["app.conf", "pool.conf"]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
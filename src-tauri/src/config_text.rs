@@ -0,0 +1,112 @@
+use crate::config::{DirectiveInput, LocationBlockInput};
+
+/// 生成配置文本时每一级的缩进
+pub(crate) const INDENT_UNIT: &str = "    ";
+
+/// 基于原始文本的逐行编辑，行号为文本内的下标
+pub(crate) enum TextEdit {
+    Replace(usize, String),
+    InsertAfter(usize, String),
+    Delete(usize),
+}
+
+pub(crate) fn indent_of(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// 为每个非空行加上 indent
+pub(crate) fn indent_lines(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", indent, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub(crate) fn apply_edits(lines: &[&str], edits: &[TextEdit]) -> String {
+    let mut output = Vec::with_capacity(lines.len() + edits.len());
+
+    for (index, line) in lines.iter().enumerate() {
+        let deleted = edits.iter().any(|edit| matches!(edit, TextEdit::Delete(target) if *target == index));
+        let replacement = edits.iter().find_map(|edit| match edit {
+            TextEdit::Replace(target, text) if *target == index => Some(text.clone()),
+            _ => None,
+        });
+
+        if !deleted {
+            output.push(replacement.unwrap_or_else(|| line.to_string()));
+        }
+        output.extend(edits.iter().filter_map(|edit| match edit {
+            TextEdit::InsertAfter(target, text) if *target == index => Some(text.clone()),
+            _ => None,
+        }));
+    }
+
+    output.join("\n")
+}
+
+/// 渲染单条指令，值为空时只输出指令名
+pub(crate) fn render_directive_line(indent: &str, directive: &DirectiveInput) -> String {
+    if directive.value.is_empty() {
+        format!("{}{};", indent, directive.name)
+    } else {
+        format!("{}{} {};", indent, directive.name, directive.value)
+    }
+}
+
+/// 渲染 location 块，块内指令比 indent 多缩进一级
+pub(crate) fn render_location_block(indent: &str, location: &LocationBlockInput) -> String {
+    let mut lines = Vec::with_capacity(location.directives.len() + 2);
+    match &location.modifier {
+        Some(modifier) => lines.push(format!("{}location {} {} {{", indent, modifier, location.path)),
+        None => lines.push(format!("{}location {} {{", indent, location.path)),
+    }
+    let nested = format!("{}{}", indent, INDENT_UNIT);
+    for directive in &location.directives {
+        lines.push(render_directive_line(&nested, directive));
+    }
+    lines.push(format!("{}}}", indent));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_edits_should_replace_insert_and_delete_by_line() {
+        let lines = vec!["server {", "    listen 80;", "    root html;", "}"];
+        let edits = vec![
+            TextEdit::Replace(1, "    listen 8080;".to_string()),
+            TextEdit::InsertAfter(1, "    server_name example.com;".to_string()),
+            TextEdit::Delete(2),
+        ];
+
+        assert_eq!(
+            apply_edits(&lines, &edits),
+            "server {\n    listen 8080;\n    server_name example.com;\n}"
+        );
+    }
+
+    #[test]
+    fn render_location_block_should_nest_directives_one_level() {
+        let location = LocationBlockInput {
+            path: "/api/".to_string(),
+            modifier: Some("^~".to_string()),
+            directives: vec![
+                DirectiveInput {
+                    name: "proxy_pass".to_string(),
+                    value: "http://127.0.0.1:3000".to_string(),
+                },
+                DirectiveInput {
+                    name: "internal".to_string(),
+                    value: String::new(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            render_location_block(INDENT_UNIT, &location),
+            "    location ^~ /api/ {\n        proxy_pass http://127.0.0.1:3000;\n        internal;\n    }"
+        );
+    }
+}
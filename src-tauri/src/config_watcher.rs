@@ -0,0 +1,695 @@
+use crate::config::{parse_nginx_config, ServerBlock};
+use crate::config_drift::{check_config_drift, ConfigDriftStatus};
+use crate::settings::read_app_settings;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::time::sleep;
+
+/// 配置文件被外部修改后推送给前端的事件名
+pub const CONFIG_FILE_CHANGED_EVENT: &str = "config-file-changed";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 文件状态需保持不变的时长，避免编辑器分多次写入时重复触发
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(800);
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+#[derive(Default)]
+pub struct ConfigWatcherState {
+    watcher: Mutex<Option<(String, Arc<AtomicBool>)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ConfigFileStamp {
+    pub path: PathBuf,
+    pub exists: bool,
+    pub modified_ms: i64,
+    pub len: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerBlockRef {
+    pub file_path: String,
+    pub id: String,
+    pub listen: Vec<String>,
+    pub server_name: Vec<String>,
+    pub enabled: bool,
+    pub start_line: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigStructureSummary {
+    pub added: Vec<ServerBlockRef>,
+    pub removed: Vec<ServerBlockRef>,
+    pub changed: Vec<ServerBlockRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFileChangedEvent {
+    pub config_path: String,
+    pub watched_files: Vec<String>,
+    pub changed_files: Vec<String>,
+    pub summary: ConfigStructureSummary,
+    /// 解析失败的文件及原因
+    pub parse_errors: Vec<String>,
+    /// 磁盘上的配置是否已与运行中的 Nginx 不一致；无法判断时为 None
+    pub disk_differs_from_running: Option<bool>,
+    /// 变化全部来自应用自身的写入，前端无需再次提示和重新加载
+    pub own_write: bool,
+    pub timestamp_ms: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWatchStatus {
+    pub config_path: String,
+    pub watched_files: Vec<String>,
+    pub disk_differs_from_running: Option<bool>,
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// 去掉注释后提取 include 指令的参数
+fn extract_include_patterns(content: &str) -> Vec<String> {
+    let mut patterns = Vec::new();
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        for statement in line.split([';', '{', '}']) {
+            let statement = statement.trim();
+            let Some(rest) = statement.strip_prefix("include") else {
+                continue;
+            };
+            if !rest.starts_with(char::is_whitespace) {
+                continue;
+            }
+
+            let value = rest.trim().trim_matches(|c| c == '"' || c == '\'');
+            if !value.is_empty() {
+                patterns.push(value.to_string());
+            }
+        }
+    }
+
+    patterns
+}
+
+/// 支持 `*` 与 `?` 的文件名通配匹配
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// 将 include 参数展开为实际文件，相对路径以主配置文件所在目录为基准
+fn expand_include_pattern(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let path = Path::new(pattern);
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    };
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if !file_name.contains(['*', '?']) {
+        return vec![path];
+    }
+
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut matched: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| wildcard_match(&file_name, &entry.file_name().to_string_lossy()))
+        .map(|entry| entry.path())
+        .collect();
+    matched.sort();
+    matched
+}
+
+/// 独占一行的 include 指令的参数，与其他语句写在同一行时返回 None
+fn standalone_include(line: &str) -> Option<String> {
+    let code = line.split('#').next().unwrap_or("").trim();
+    if !code.ends_with(';') || code.matches(';').count() != 1 || code.contains(['{', '}']) {
+        return None;
+    }
+    extract_include_patterns(code).into_iter().next()
+}
+
+fn flatten_config_file(
+    path: &Path,
+    base_dir: &Path,
+    overrides: &HashMap<PathBuf, &str>,
+    depth: usize,
+) -> Result<String, String> {
+    let content = match overrides.get(&normalize_watch_path(path)) {
+        Some(content) => content.to_string(),
+        None => fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?,
+    };
+    if depth >= MAX_INCLUDE_DEPTH {
+        return Ok(content);
+    }
+
+    let mut output = Vec::new();
+    for line in content.lines() {
+        match standalone_include(line) {
+            Some(pattern) => {
+                for include in expand_include_pattern(&pattern, base_dir) {
+                    output.push(flatten_config_file(&include, base_dir, overrides, depth + 1)?);
+                }
+            }
+            None => output.push(line.to_string()),
+        }
+    }
+    Ok(output.join("\n"))
+}
+
+/// 将主配置中的 include 指令就地展开，overrides 中的文件使用给定内容代替磁盘内容；
+/// 用于在写入前对 include 文件的修改执行 nginx -t
+pub(crate) fn flatten_config(config_path: &str, overrides: &[(&str, &str)]) -> Result<String, String> {
+    let root = Path::new(config_path);
+    let base_dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+    let overrides: HashMap<PathBuf, &str> = overrides
+        .iter()
+        .map(|(path, content)| (normalize_watch_path(Path::new(path)), *content))
+        .collect();
+    flatten_config_file(root, &base_dir, &overrides, 0)
+}
+
+/// file_path 是否为主配置文件本身或被其递归 include
+pub(crate) fn is_config_file_of(config_path: &str, file_path: &str) -> bool {
+    let target = normalize_watch_path(Path::new(file_path));
+    resolve_config_files(config_path)
+        .iter()
+        .any(|path| normalize_watch_path(path) == target)
+}
+
+/// 文件所属的主配置：应用设置中的主配置 include 了该文件时为主配置，否则为文件本身
+pub(crate) fn main_config_for(config_path: &str) -> String {
+    read_app_settings()
+        .ok()
+        .map(|settings| settings.config_path)
+        .filter(|main| !main.trim().is_empty() && is_config_file_of(main, config_path))
+        .unwrap_or_else(|| config_path.to_string())
+}
+
+/// 解析主配置文件及其递归引用的全部 include 文件，主配置文件排在首位
+pub(crate) fn resolve_config_files(config_path: &str) -> Vec<PathBuf> {
+    let root = PathBuf::from(config_path);
+    let base_dir = root.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    let mut pending = vec![(root, 0usize)];
+
+    while let Some((path, depth)) = pending.pop() {
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        files.push(path.clone());
+
+        if depth >= MAX_INCLUDE_DEPTH {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        let mut includes: Vec<PathBuf> = extract_include_patterns(&content)
+            .iter()
+            .flat_map(|pattern| expand_include_pattern(pattern, &base_dir))
+            .collect();
+        includes.reverse();
+        pending.extend(includes.into_iter().map(|include| (include, depth + 1)));
+    }
+
+    files
+}
+
+pub(crate) fn stamp_config_files(files: &[PathBuf]) -> Vec<ConfigFileStamp> {
+    files
+        .iter()
+        .map(|path| match fs::metadata(path) {
+            Ok(metadata) => ConfigFileStamp {
+                path: path.clone(),
+                exists: true,
+                modified_ms: metadata
+                    .modified()
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_millis() as i64)
+                    .unwrap_or(0),
+                len: metadata.len(),
+            },
+            Err(_) => ConfigFileStamp {
+                path: path.clone(),
+                exists: false,
+                modified_ms: 0,
+                len: 0,
+            },
+        })
+        .collect()
+}
+
+fn own_write_registry() -> &'static Mutex<HashMap<PathBuf, ConfigFileStamp>> {
+    static OWN_WRITES: OnceLock<Mutex<HashMap<PathBuf, ConfigFileStamp>>> = OnceLock::new();
+    OWN_WRITES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub(crate) fn normalize_watch_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// 记录应用自身写入后的文件状态，监听到相同状态时不再视为外部修改
+pub(crate) fn record_own_write(path: &Path) {
+    let path = normalize_watch_path(path);
+    if let Some(stamp) = stamp_config_files(std::slice::from_ref(&path)).pop() {
+        if let Ok(mut registry) = own_write_registry().lock() {
+            registry.insert(path, stamp);
+        }
+    }
+}
+
+/// 变化的文件是否全部与应用自身的写入一致，一致时消费对应登记
+fn take_own_writes(previous: &[ConfigFileStamp], current: &[ConfigFileStamp]) -> bool {
+    if previous
+        .iter()
+        .any(|stamp| !current.iter().any(|current| current.path == stamp.path))
+    {
+        return false;
+    }
+
+    let changed: Vec<&ConfigFileStamp> = current
+        .iter()
+        .filter(|stamp| !previous.contains(stamp))
+        .collect();
+    let Ok(mut registry) = own_write_registry().lock() else {
+        return false;
+    };
+    let matched: Vec<PathBuf> = changed
+        .iter()
+        .filter_map(|stamp| {
+            let path = normalize_watch_path(&stamp.path);
+            let recorded = registry.get(&path)?;
+            (recorded.exists == stamp.exists
+                && recorded.modified_ms == stamp.modified_ms
+                && recorded.len == stamp.len)
+                .then_some(path)
+        })
+        .collect();
+    if changed.is_empty() || matched.len() != changed.len() {
+        return false;
+    }
+    for path in matched {
+        registry.remove(&path);
+    }
+    true
+}
+
+/// 以最近一次生效的配置指纹判断磁盘配置是否已变化，无法判断时返回 None
+fn disk_differs_from_running(config_path: &str) -> Option<bool> {
+    match check_config_drift(config_path).status {
+        ConfigDriftStatus::InSync => Some(false),
+        ConfigDriftStatus::Drifted => Some(true),
+        ConfigDriftStatus::Unknown => None,
+    }
+}
+
+/// 解析全部配置文件中的 server 块，按文件分组
+pub(crate) fn parse_config_servers(files: &[PathBuf]) -> (Vec<(String, ServerBlock)>, Vec<String>) {
+    let mut servers = Vec::new();
+    let mut errors = Vec::new();
+
+    for path in files {
+        let file_path = path.to_string_lossy().to_string();
+        let Ok(content) = fs::read_to_string(path) else {
+            continue;
+        };
+        match parse_nginx_config(&content, &file_path) {
+            Ok(config) => servers.extend(config.servers.into_iter().map(|server| (file_path.clone(), server))),
+            Err(e) => errors.push(format!("{}: {}", file_path, e)),
+        }
+    }
+
+    (servers, errors)
+}
+
+pub(crate) fn server_ref(file_path: &str, server: &ServerBlock) -> ServerBlockRef {
+    ServerBlockRef {
+        file_path: file_path.to_string(),
+        id: server.id.clone(),
+        listen: server.listen.clone(),
+        server_name: server.server_name.clone(),
+        enabled: server.enabled,
+        start_line: server.start_line,
+    }
+}
+
+/// 以 文件 + listen + server_name 作为 server 的身份，同一身份出现多次时按出现顺序区分
+pub(crate) fn keyed_servers(servers: &[(String, ServerBlock)]) -> BTreeMap<(String, usize), (String, &ServerBlock)> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut keyed = BTreeMap::new();
+
+    for (file_path, server) in servers {
+        let key = format!(
+            "{}|{}|{}",
+            file_path,
+            server.listen.join(","),
+            server.server_name.join(",")
+        );
+        let index = occurrences.entry(key.clone()).or_insert(0);
+        keyed.insert((key, *index), (file_path.clone(), server));
+        *index += 1;
+    }
+
+    keyed
+}
+
+/// 对比前后两次解析结果，得出新增、删除与内容变化的 server
+pub(crate) fn diff_server_blocks(
+    previous: &[(String, ServerBlock)],
+    current: &[(String, ServerBlock)],
+) -> ConfigStructureSummary {
+    let previous = keyed_servers(previous);
+    let current = keyed_servers(current);
+    let mut summary = ConfigStructureSummary::default();
+
+    for (key, (file_path, server)) in &current {
+        match previous.get(key) {
+            None => summary.added.push(server_ref(file_path, server)),
+            Some((_, old)) => {
+                if old.raw_content.trim() != server.raw_content.trim() || old.enabled != server.enabled {
+                    summary.changed.push(server_ref(file_path, server));
+                }
+            }
+        }
+    }
+
+    for (key, (file_path, server)) in &previous {
+        if !current.contains_key(key) {
+            summary.removed.push(server_ref(file_path, server));
+        }
+    }
+
+    summary
+}
+
+fn changed_file_paths(previous: &[ConfigFileStamp], current: &[ConfigFileStamp]) -> Vec<String> {
+    let previous: HashMap<&PathBuf, &ConfigFileStamp> =
+        previous.iter().map(|stamp| (&stamp.path, stamp)).collect();
+    let current_paths: HashSet<&PathBuf> = current.iter().map(|stamp| &stamp.path).collect();
+
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|stamp| previous.get(&stamp.path) != Some(stamp))
+        .map(|stamp| stamp.path.to_string_lossy().to_string())
+        .collect();
+    changed.extend(
+        previous
+            .keys()
+            .filter(|path| !current_paths.contains(*path))
+            .map(|path| path.to_string_lossy().to_string()),
+    );
+    changed
+}
+
+fn path_strings(files: &[PathBuf]) -> Vec<String> {
+    files.iter().map(|path| path.to_string_lossy().to_string()).collect()
+}
+
+fn spawn_config_watcher(app: AppHandle, config_path: String, stop_flag: Arc<AtomicBool>) {
+    tauri::async_runtime::spawn(async move {
+        let files = resolve_config_files(&config_path);
+        let mut stamps = stamp_config_files(&files);
+        let (mut servers, _) = parse_config_servers(&files);
+        let mut pending: Option<(Vec<ConfigFileStamp>, Instant)> = None;
+
+        while !stop_flag.load(Ordering::SeqCst) {
+            sleep(POLL_INTERVAL).await;
+            if stop_flag.load(Ordering::SeqCst) {
+                break;
+            }
+
+            let current_files = resolve_config_files(&config_path);
+            let current_stamps = stamp_config_files(&current_files);
+            if current_stamps == stamps {
+                pending = None;
+                continue;
+            }
+
+            // 变化后需连续保持一段时间不变才处理
+            match &pending {
+                Some((pending_stamps, since))
+                    if *pending_stamps == current_stamps && since.elapsed() >= DEBOUNCE_DURATION => {}
+                Some((pending_stamps, _)) if *pending_stamps == current_stamps => continue,
+                _ => {
+                    pending = Some((current_stamps, Instant::now()));
+                    continue;
+                }
+            }
+            pending = None;
+
+            let own_write = take_own_writes(&stamps, &current_stamps);
+            let (current_servers, parse_errors) = parse_config_servers(&current_files);
+            let event = ConfigFileChangedEvent {
+                config_path: config_path.clone(),
+                watched_files: path_strings(&current_files),
+                changed_files: changed_file_paths(&stamps, &current_stamps),
+                summary: diff_server_blocks(&servers, &current_servers),
+                parse_errors,
+                disk_differs_from_running: disk_differs_from_running(&config_path),
+                own_write,
+                timestamp_ms: now_timestamp_ms(),
+            };
+            let _ = app.emit(CONFIG_FILE_CHANGED_EVENT, event);
+
+            stamps = current_stamps;
+            servers = current_servers;
+        }
+    });
+}
+
+/// 开始监听配置文件及其 include 文件，重复调用同一路径时不会重复启动
+#[tauri::command]
+pub async fn start_config_watch(
+    app: AppHandle,
+    state: State<'_, ConfigWatcherState>,
+    config_path: String,
+) -> Result<ConfigWatchStatus, String> {
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+    if !Path::new(&config_path).exists() {
+        return Err(format!("配置文件不存在: {}", config_path));
+    }
+
+    {
+        let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+        let already_watching = watcher
+            .as_ref()
+            .is_some_and(|(path, flag)| *path == config_path && !flag.load(Ordering::SeqCst));
+
+        if !already_watching {
+            if let Some((_, flag)) = watcher.take() {
+                flag.store(true, Ordering::SeqCst);
+            }
+            let stop_flag = Arc::new(AtomicBool::new(false));
+            spawn_config_watcher(app, config_path.clone(), stop_flag.clone());
+            *watcher = Some((config_path.clone(), stop_flag));
+        }
+    }
+
+    get_config_watch_status_internal(&config_path)
+}
+
+#[tauri::command]
+pub async fn stop_config_watch(state: State<'_, ConfigWatcherState>) -> Result<(), String> {
+    let mut watcher = state.watcher.lock().map_err(|e| e.to_string())?;
+    if let Some((_, flag)) = watcher.take() {
+        flag.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+fn get_config_watch_status_internal(config_path: &str) -> Result<ConfigWatchStatus, String> {
+    let files = resolve_config_files(config_path);
+
+    Ok(ConfigWatchStatus {
+        config_path: config_path.to_string(),
+        watched_files: path_strings(&files),
+        disk_differs_from_running: disk_differs_from_running(config_path),
+    })
+}
+
+/// 查询配置文件集合及磁盘与运行配置是否一致
+#[tauri::command]
+pub async fn get_config_watch_status(config_path: String) -> Result<ConfigWatchStatus, String> {
+    get_config_watch_status_internal(&config_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "ncm_config_watcher_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolve_config_files_should_follow_nested_and_wildcard_includes() {
+        let dir = temp_dir("includes");
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        fs::write(
+            dir.join("nginx.conf"),
+            "http {\n    include mime.types;\n    # include ignored.conf;\n    include conf.d/*.conf;\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("mime.types"), "types {}\n").unwrap();
+        fs::write(dir.join("conf.d/a.conf"), "include snippets/common.conf;\n").unwrap();
+        fs::write(dir.join("conf.d/b.conf"), "server { listen 80; }\n").unwrap();
+        fs::write(dir.join("conf.d/readme.txt"), "").unwrap();
+
+        let files = resolve_config_files(dir.join("nginx.conf").to_str().unwrap());
+        let relative: Vec<String> = files
+            .iter()
+            .map(|path| path.strip_prefix(&dir).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+
+        assert_eq!(
+            relative,
+            vec![
+                "nginx.conf",
+                "mime.types",
+                "conf.d/a.conf",
+                "snippets/common.conf",
+                "conf.d/b.conf",
+            ]
+        );
+        assert!(wildcard_match("*.conf", "site.conf"));
+        assert!(!wildcard_match("*.conf", "site.conf.bak"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn diff_server_blocks_should_report_added_removed_and_changed() {
+        let parse = |content: &str| -> Vec<(String, ServerBlock)> {
+            parse_nginx_config(content, "nginx.conf")
+                .unwrap()
+                .servers
+                .into_iter()
+                .map(|server| ("nginx.conf".to_string(), server))
+                .collect()
+        };
+
+        let previous = parse(
+            "http {\n    server {\n        listen 80;\n        server_name a.test;\n    }\n    server {\n        listen 81;\n        server_name b.test;\n    }\n}\n",
+        );
+        let current = parse(
+            "http {\n    server {\n        listen 80;\n        server_name a.test;\n        root /srv;\n    }\n    server {\n        listen 82;\n        server_name c.test;\n    }\n}\n",
+        );
+
+        let summary = diff_server_blocks(&previous, &current);
+        assert_eq!(summary.changed.len(), 1);
+        assert_eq!(summary.changed[0].server_name, vec!["a.test"]);
+        assert_eq!(summary.added.len(), 1);
+        assert_eq!(summary.added[0].server_name, vec!["c.test"]);
+        assert_eq!(summary.removed.len(), 1);
+        assert_eq!(summary.removed[0].server_name, vec!["b.test"]);
+        let unchanged = diff_server_blocks(&current, &current);
+        assert!(unchanged.added.is_empty() && unchanged.removed.is_empty() && unchanged.changed.is_empty());
+    }
+
+    #[test]
+    fn take_own_writes_should_only_match_recorded_app_writes() {
+        let dir = temp_dir("own_writes");
+        let config = dir.join("nginx.conf");
+        fs::write(&config, "events {}\n").unwrap();
+        let files = vec![config.clone()];
+        let before = stamp_config_files(&files);
+
+        fs::write(&config, "events {}\nhttp {}\n").unwrap();
+        record_own_write(&config);
+        let after = stamp_config_files(&files);
+        assert!(take_own_writes(&before, &after));
+        // 登记只生效一次，同样的变化再次出现时按外部修改处理
+        assert!(!take_own_writes(&before, &after));
+
+        fs::write(&config, "events {}\nhttp { server {} }\n").unwrap();
+        assert!(!take_own_writes(&after, &stamp_config_files(&files)));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn flatten_config_should_inline_includes_with_overrides() {
+        let dir = temp_dir("flatten");
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        let main = dir.join("nginx.conf");
+        fs::write(&main, "events {}\nhttp {\n    include conf.d/*.conf; # sites\n}\n").unwrap();
+        fs::write(dir.join("conf.d/a.conf"), "server { listen 80; }\n").unwrap();
+        fs::write(dir.join("conf.d/b.conf"), "server { listen 81; }\n").unwrap();
+
+        let edited = dir.join("conf.d/b.conf");
+        let flattened = flatten_config(
+            main.to_str().unwrap(),
+            &[(edited.to_str().unwrap(), "server { listen 8081; }\n")],
+        )
+        .unwrap();
+        assert_eq!(
+            flattened,
+            "events {}\nhttp {\nserver { listen 80; }\nserver { listen 8081; }\n}"
+        );
+        assert!(is_config_file_of(main.to_str().unwrap(), edited.to_str().unwrap()));
+        assert!(!is_config_file_of(main.to_str().unwrap(), dir.join("other.conf").to_str().unwrap()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,417 @@
+use crate::audit::audited;
+use crate::cert_inventory::names_requiring_coverage;
+use crate::change_plan::{build_change_plan, ensure_plan_valid};
+use crate::config::{build_update_server_content, parse_nginx_config, write_config_file, ServerBlock};
+use crate::config_text::{apply_edits, indent_of, TextEdit, INDENT_UNIT};
+use crate::settings::get_app_config_dir;
+use chrono::{Datelike, Duration, Utc};
+use rcgen::{
+    date_time_ymd, BasicConstraints, Certificate, CertificateParams, DistinguishedName, DnType,
+    ExtendedKeyUsagePurpose, IsCa, KeyPair, KeyUsagePurpose,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+const CERTS_DIR_NAME: &str = "certs";
+const DEV_CA_CERT_FILE: &str = "dev-ca.crt";
+const DEV_CA_KEY_FILE: &str = "dev-ca.key";
+const DEV_CA_COMMON_NAME: &str = "Nginx Config Manager Dev CA";
+const DEV_CA_VALIDITY_DAYS: i64 = 3650;
+/// 825 天是主流浏览器接受的证书最长有效期
+const DEFAULT_VALIDITY_DAYS: u32 = 825;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevCertificateRequest {
+    pub config_path: String,
+    pub server_id: String,
+    /// 使用本地开发 CA 签发（不存在时自动创建），否则生成自签名证书
+    #[serde(default)]
+    pub use_dev_ca: bool,
+    pub validity_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevCertificateResult {
+    pub success: bool,
+    pub message: String,
+    pub certificate_path: String,
+    pub key_path: String,
+    pub subject_alt_names: Vec<String>,
+    pub not_after: String,
+    pub ca_certificate_path: Option<String>,
+    /// 本次是否新建了开发 CA
+    pub ca_created: bool,
+}
+
+/// 已写入磁盘的证书与私钥
+struct IssuedCertificate {
+    certificate_path: PathBuf,
+    key_path: PathBuf,
+    not_after: String,
+    ca_certificate_path: Option<PathBuf>,
+    ca_created: bool,
+}
+
+fn managed_certs_dir() -> Result<PathBuf, String> {
+    let dir = get_app_config_dir()?.join(CERTS_DIR_NAME);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建证书目录失败: {}", e))?;
+    Ok(dir)
+}
+
+fn write_private_key(path: &Path, pem: &str) -> Result<(), String> {
+    fs::write(path, pem).map_err(|e| format!("写入私钥 {} 失败: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("设置私钥权限失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 有效期从昨天开始，避免客户端时钟略慢时证书尚未生效
+fn set_validity(params: &mut CertificateParams, days: i64) {
+    let date = |offset: i64| {
+        let date = (Utc::now() + Duration::days(offset)).date_naive();
+        date_time_ymd(date.year(), date.month() as u8, date.day() as u8)
+    };
+    params.not_before = date(-1);
+    params.not_after = date(days);
+}
+
+/// 开发 CA 的参数固定，便于用已保存的私钥重建签发者
+fn dev_ca_params() -> Result<CertificateParams, String> {
+    let mut params = CertificateParams::new(Vec::<String>::new()).map_err(|e| format!("生成 CA 参数失败: {}", e))?;
+    params.distinguished_name = DistinguishedName::new();
+    params.distinguished_name.push(DnType::CommonName, DEV_CA_COMMON_NAME);
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign, KeyUsagePurpose::DigitalSignature];
+    set_validity(&mut params, DEV_CA_VALIDITY_DAYS);
+    Ok(params)
+}
+
+/// 读取或创建本地开发 CA，返回用于签发的证书、私钥以及是否新建
+fn load_or_create_dev_ca(dir: &Path) -> Result<(Certificate, KeyPair, bool), String> {
+    let cert_path = dir.join(DEV_CA_CERT_FILE);
+    let key_path = dir.join(DEV_CA_KEY_FILE);
+
+    if cert_path.exists() && key_path.exists() {
+        let key_pem = fs::read_to_string(&key_path).map_err(|e| format!("读取开发 CA 私钥失败: {}", e))?;
+        let key = KeyPair::from_pem(&key_pem).map_err(|e| format!("解析开发 CA 私钥失败: {}", e))?;
+        // 仅用于签发：主题与密钥标识均与磁盘上的 CA 证书一致
+        let certificate = dev_ca_params()?.self_signed(&key).map_err(|e| format!("加载开发 CA 失败: {}", e))?;
+        return Ok((certificate, key, false));
+    }
+
+    let key = KeyPair::generate().map_err(|e| format!("生成开发 CA 私钥失败: {}", e))?;
+    let certificate = dev_ca_params()?.self_signed(&key).map_err(|e| format!("生成开发 CA 证书失败: {}", e))?;
+    write_private_key(&key_path, &key.serialize_pem())?;
+    fs::write(&cert_path, certificate.pem()).map_err(|e| format!("写入开发 CA 证书失败: {}", e))?;
+    Ok((certificate, key, true))
+}
+
+fn certificate_file_stem(name: &str) -> String {
+    name.replace('*', "wildcard")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+/// 主域名对应的证书与私钥路径
+fn certificate_paths(dir: &Path, primary: &str) -> (PathBuf, PathBuf) {
+    let stem = certificate_file_stem(primary);
+    (dir.join(format!("{}.crt", stem)), dir.join(format!("{}.key", stem)))
+}
+
+/// 证书相关文件写入前的内容，配置校验或写入失败时据此恢复
+struct FileBackup(Vec<(PathBuf, Option<Vec<u8>>)>);
+
+impl FileBackup {
+    fn capture(paths: Vec<PathBuf>) -> Self {
+        Self(
+            paths
+                .into_iter()
+                .map(|path| {
+                    let content = fs::read(&path).ok();
+                    (path, content)
+                })
+                .collect(),
+        )
+    }
+
+    /// 恢复原有内容，原本不存在的文件直接删除
+    fn restore(self) {
+        for (path, content) in self.0 {
+            let _ = match content {
+                Some(content) => fs::write(&path, content),
+                None => fs::remove_file(&path),
+            };
+        }
+    }
+}
+
+/// 为 names 生成私钥与证书并写入 dir
+fn issue_dev_certificate(dir: &Path, names: &[String], use_dev_ca: bool, validity_days: u32) -> Result<IssuedCertificate, String> {
+    let primary = names.first().ok_or_else(|| "没有可用于证书的域名".to_string())?;
+    let mut params = CertificateParams::new(names.to_vec()).map_err(|e| format!("无效的证书域名: {}", e))?;
+    params.distinguished_name = DistinguishedName::new();
+    params.distinguished_name.push(DnType::CommonName, primary.as_str());
+    params.is_ca = IsCa::NoCa;
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    set_validity(&mut params, i64::from(validity_days));
+    let not_after = (Utc::now() + Duration::days(i64::from(validity_days))).format("%Y-%m-%d").to_string();
+
+    let key = KeyPair::generate().map_err(|e| format!("生成私钥失败: {}", e))?;
+    let (certificate, ca_certificate_path, ca_created) = if use_dev_ca {
+        let (ca_certificate, ca_key, created) = load_or_create_dev_ca(dir)?;
+        let certificate = params
+            .signed_by(&key, &ca_certificate, &ca_key)
+            .map_err(|e| format!("签发证书失败: {}", e))?;
+        (certificate, Some(dir.join(DEV_CA_CERT_FILE)), created)
+    } else {
+        let certificate = params.self_signed(&key).map_err(|e| format!("生成自签名证书失败: {}", e))?;
+        (certificate, None, false)
+    };
+
+    let (certificate_path, key_path) = certificate_paths(dir, primary);
+    write_private_key(&key_path, &key.serialize_pem())?;
+    fs::write(&certificate_path, certificate.pem()).map_err(|e| format!("写入证书失败: {}", e))?;
+
+    Ok(IssuedCertificate {
+        certificate_path,
+        key_path,
+        not_after,
+        ca_certificate_path,
+        ca_created,
+    })
+}
+
+/// 配置中使用的路径：统一为 / 分隔，包含空白时加引号
+fn config_path_value(path: &Path) -> String {
+    let value = path.to_string_lossy().replace('\\', "/");
+    if value.chars().any(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value
+    }
+}
+
+/// 在 server 文本中启用 443 ssl 并指向新证书，已有的 ssl_certificate 会被替换
+pub(crate) fn apply_certificate_directives(server_text: &str, certificate: &str, key: &str) -> Result<String, String> {
+    let parsed = parse_nginx_config(server_text, "")?;
+    let server = parsed.servers.first().ok_or_else(|| "Server 块文本无效".to_string())?;
+    let lines: Vec<&str> = server_text.lines().collect();
+    let mut edits = Vec::new();
+
+    let server_directives = |name: &'static str| server.directives.iter().filter(move |directive| directive.name == name);
+    let anchor = server
+        .directives
+        .iter()
+        .filter(|directive| matches!(directive.name.as_str(), "listen" | "server_name"))
+        .map(|directive| directive.line - 1)
+        .max();
+    let indent = anchor
+        .map(|line| indent_of(lines[line]).to_string())
+        .unwrap_or_else(|| format!("{}{}", indent_of(lines[0]), INDENT_UNIT));
+
+    let has_ssl_listen = server_directives("listen").any(|directive| directive.value.split_whitespace().skip(1).any(|param| param == "ssl"));
+    if !has_ssl_listen {
+        let listen_anchor = server_directives("listen").map(|directive| directive.line - 1).max().or(anchor).unwrap_or(0);
+        edits.push(TextEdit::InsertAfter(listen_anchor, format!("{}listen 443 ssl;", indent)));
+        if server_directives("listen").any(|directive| directive.value.starts_with("[::]")) {
+            edits.push(TextEdit::InsertAfter(listen_anchor, format!("{}listen [::]:443 ssl;", indent)));
+        }
+    }
+
+    for (name, value) in [("ssl_certificate", certificate), ("ssl_certificate_key", key)] {
+        let mut existing = server_directives(name);
+        match existing.next() {
+            Some(first) => {
+                let line = first.line - 1;
+                edits.push(TextEdit::Replace(line, format!("{}{} {};", indent_of(lines[line]), name, value)));
+                edits.extend(existing.map(|directive| TextEdit::Delete(directive.line - 1)));
+            }
+            None => edits.push(TextEdit::InsertAfter(anchor.unwrap_or(0), format!("{}{} {};", indent, name, value))),
+        }
+    }
+
+    Ok(apply_edits(&lines, &edits))
+}
+
+fn certificate_names(server: &ServerBlock) -> Vec<String> {
+    let mut names = Vec::new();
+    for name in names_requiring_coverage(server) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if names.is_empty() {
+        names.push("localhost".to_string());
+    }
+    names
+}
+
+async fn generate_dev_certificate_internal(request: DevCertificateRequest) -> Result<DevCertificateResult, String> {
+    let content = fs::read_to_string(&request.config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let config = parse_nginx_config(&content, &request.config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let server = config
+        .servers
+        .iter()
+        .find(|server| server.id == request.server_id)
+        .ok_or_else(|| format!("未找到 ID 为 {} 的 Server 块", request.server_id))?;
+
+    let names = certificate_names(server);
+    let validity_days = request.validity_days.filter(|days| *days > 0).unwrap_or(DEFAULT_VALIDITY_DAYS);
+    let dir = managed_certs_dir()?;
+    let (certificate_path, key_path) = certificate_paths(&dir, &names[0]);
+
+    let server_text = apply_certificate_directives(
+        &server.raw_content,
+        &config_path_value(&certificate_path),
+        &config_path_value(&key_path),
+    )?;
+    let new_content = build_update_server_content(&content, &request.config_path, &request.server_id, &server_text)?;
+
+    let backup = FileBackup::capture(vec![
+        certificate_path,
+        key_path,
+        dir.join(DEV_CA_CERT_FILE),
+        dir.join(DEV_CA_KEY_FILE),
+    ]);
+    let outcome = async {
+        let issued = issue_dev_certificate(&dir, &names, request.use_dev_ca, validity_days)?;
+        // nginx -t 会读取证书文件，因此校验放在证书写入之后、配置写入之前
+        let plan = build_change_plan(&request.config_path, &content, new_content).await?;
+        ensure_plan_valid(&plan.validation)?;
+        write_config_file(&request.config_path, &plan.new_content)?;
+        Ok::<_, String>(issued)
+    }
+    .await;
+    let issued = match outcome {
+        Ok(issued) => issued,
+        Err(message) => {
+            backup.restore();
+            return Err(message);
+        }
+    };
+
+    let mut message = format!(
+        "已为 {} 生成开发证书，有效期至 {}",
+        names.join("、"),
+        issued.not_after
+    );
+    if let Some(ca_path) = &issued.ca_certificate_path {
+        message.push_str(&format!("；证书由本地开发 CA 签发，请将 {} 导入系统信任库", ca_path.display()));
+    }
+
+    Ok(DevCertificateResult {
+        success: true,
+        message,
+        certificate_path: issued.certificate_path.to_string_lossy().to_string(),
+        key_path: issued.key_path.to_string_lossy().to_string(),
+        subject_alt_names: names,
+        not_after: issued.not_after,
+        ca_certificate_path: issued.ca_certificate_path.map(|path| path.to_string_lossy().to_string()),
+        ca_created: issued.ca_created,
+    })
+}
+
+/// 为 server 生成开发用证书并启用 HTTPS
+#[tauri::command]
+pub async fn generate_dev_certificate(app: AppHandle, request: DevCertificateRequest) -> Result<DevCertificateResult, String> {
+    let label = format!("Server 块 {} 生成开发证书", request.server_id);
+    audited(&app, "generate_dev_certificate", &label, generate_dev_certificate_internal(request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert_inventory::{inspect_certificate, CertificateStatus};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let unique = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        let dir = std::env::temp_dir().join(format!("ncm_dev_certs_{}_{}", name, unique));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn issue_should_sign_with_reused_dev_ca() {
+        let dir = temp_dir("ca");
+        let names = vec!["app.local".to_string(), "*.app.local".to_string()];
+
+        let first = issue_dev_certificate(&dir, &names, true, 30).unwrap();
+        assert!(first.ca_created);
+        let ca_pem = fs::read_to_string(dir.join(DEV_CA_CERT_FILE)).unwrap();
+
+        let second = issue_dev_certificate(&dir, &names, true, 30).unwrap();
+        assert!(!second.ca_created);
+        assert_eq!(fs::read_to_string(dir.join(DEV_CA_CERT_FILE)).unwrap(), ca_pem);
+
+        let now = Utc::now().timestamp();
+        let (entry, details) = inspect_certificate(&second.certificate_path, Some(&second.key_path), 7, now);
+        let details = details.unwrap();
+        assert_eq!(entry.key_matches, Some(true));
+        assert_eq!(entry.status, CertificateStatus::Valid);
+        assert_eq!(details.subject_alt_names, names);
+        assert!(!details.self_signed);
+        assert!(details.issuer.contains(DEV_CA_COMMON_NAME));
+        assert_eq!(second.certificate_path.file_name().unwrap(), "app.local.crt");
+
+        let self_signed = issue_dev_certificate(&dir, &["localhost".to_string()], false, 30).unwrap();
+        assert!(self_signed.ca_certificate_path.is_none());
+        let (_, details) = inspect_certificate(&self_signed.certificate_path, Some(&self_signed.key_path), 7, now);
+        assert!(details.unwrap().self_signed);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn apply_certificate_directives_should_enable_ssl_listen() {
+        let updated = apply_certificate_directives(
+            "    server {\n        listen 80;\n        listen [::]:80;\n        server_name app.local;\n        root html;\n    }",
+            "/certs/app.local.crt",
+            "/certs/app.local.key",
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            "    server {\n        listen 80;\n        listen [::]:80;\n        listen 443 ssl;\n        listen [::]:443 ssl;\n        server_name app.local;\n        ssl_certificate /certs/app.local.crt;\n        ssl_certificate_key /certs/app.local.key;\n        root html;\n    }"
+        );
+
+        let replaced = apply_certificate_directives(
+            "server {\n    listen 443 ssl;\n    ssl_certificate old.crt;\n    ssl_certificate ecdsa.crt;\n    ssl_certificate_key old.key;\n}",
+            "new.crt",
+            "new.key",
+        )
+        .unwrap();
+        assert_eq!(
+            replaced,
+            "server {\n    listen 443 ssl;\n    ssl_certificate new.crt;\n    ssl_certificate_key new.key;\n}"
+        );
+    }
+
+    #[test]
+    fn file_backup_should_restore_previous_and_remove_new_files() {
+        let dir = temp_dir("backup");
+        let existing = dir.join("app.local.crt");
+        let created = dir.join("app.local.key");
+        fs::write(&existing, "old certificate").unwrap();
+
+        let backup = FileBackup::capture(vec![existing.clone(), created.clone()]);
+        fs::write(&existing, "new certificate").unwrap();
+        fs::write(&created, "new key").unwrap();
+        backup.restore();
+
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "old certificate");
+        assert!(!created.exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,384 @@
+use crate::audit::audited;
+use crate::change_plan::{build_change_plan, ensure_plan_valid, ChangePlan};
+use crate::config::{build_update_server_content, parse_nginx_config, write_config_file, EditResult, ServerBlock};
+use crate::config_text::{apply_edits, indent_of, TextEdit, INDENT_UNIT};
+use crate::config_watcher::main_config_for;
+use crate::dev_certs::apply_certificate_directives;
+use crate::port_preflight::{parse_listen_address, ListenProtocol};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use tauri::AppHandle;
+
+const HSTS_HEADER: &str = "Strict-Transport-Security";
+const HSTS_VALUE: &str = "Strict-Transport-Security \"max-age=63072000\" always";
+
+/// 参照 Mozilla SSL Configuration Generator 的三档配置
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsPreset {
+    /// 仅 TLSv1.3，适用于只需支持新客户端的服务
+    Modern,
+    /// TLSv1.2 + TLSv1.3，推荐的通用配置
+    Intermediate,
+    /// 兼容 TLSv1 / TLSv1.1 的旧客户端
+    Legacy,
+}
+
+const INTERMEDIATE_CIPHERS: &str = "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:DHE-RSA-CHACHA20-POLY1305";
+const LEGACY_CIPHERS: &str = "ECDHE-ECDSA-AES128-GCM-SHA256:ECDHE-RSA-AES128-GCM-SHA256:ECDHE-ECDSA-AES256-GCM-SHA384:ECDHE-RSA-AES256-GCM-SHA384:ECDHE-ECDSA-CHACHA20-POLY1305:ECDHE-RSA-CHACHA20-POLY1305:DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:DHE-RSA-CHACHA20-POLY1305:ECDHE-ECDSA-AES128-SHA256:ECDHE-RSA-AES128-SHA256:ECDHE-ECDSA-AES128-SHA:ECDHE-RSA-AES128-SHA:ECDHE-ECDSA-AES256-SHA384:ECDHE-RSA-AES256-SHA384:ECDHE-ECDSA-AES256-SHA:ECDHE-RSA-AES256-SHA:DHE-RSA-AES128-SHA256:DHE-RSA-AES256-SHA256:AES128-GCM-SHA256:AES256-GCM-SHA384:AES128-SHA256:AES256-SHA256:AES128-SHA:AES256-SHA:DES-CBC3-SHA";
+
+impl TlsPreset {
+    /// 预设对应的 TLS 指令（名称、值）
+    pub(crate) fn directives(self, hsts: bool) -> Vec<(&'static str, &'static str)> {
+        let mut directives = match self {
+            TlsPreset::Modern => vec![("ssl_protocols", "TLSv1.3"), ("ssl_prefer_server_ciphers", "off")],
+            TlsPreset::Intermediate => vec![
+                ("ssl_protocols", "TLSv1.2 TLSv1.3"),
+                ("ssl_ciphers", INTERMEDIATE_CIPHERS),
+                ("ssl_prefer_server_ciphers", "off"),
+            ],
+            TlsPreset::Legacy => vec![
+                ("ssl_protocols", "TLSv1 TLSv1.1 TLSv1.2 TLSv1.3"),
+                ("ssl_ciphers", LEGACY_CIPHERS),
+                ("ssl_prefer_server_ciphers", "on"),
+            ],
+        };
+        directives.extend([
+            ("ssl_session_cache", "shared:SSL:10m"),
+            ("ssl_session_timeout", "1d"),
+            ("ssl_session_tickets", "off"),
+            ("ssl_stapling", "on"),
+            ("ssl_stapling_verify", "on"),
+        ]);
+        if hsts {
+            directives.push(("add_header", HSTS_VALUE));
+        }
+        directives
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnableHttpsRequest {
+    pub server_id: String,
+    pub certificate_path: String,
+    pub key_path: String,
+    pub preset: TlsPreset,
+    pub hsts: bool,
+    /// 将 80 端口拆分为单独的 server，301 跳转到 HTTPS
+    pub redirect_http: bool,
+}
+
+/// 配置值中的路径包含空白时加引号
+fn quote_value(value: &str) -> String {
+    if value.chars().any(char::is_whitespace) && !value.starts_with('"') {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 将监听 80 端口的 listen 值改写为 443 ssl，其余 listen 返回 None
+fn https_listen_value(value: &str) -> Option<String> {
+    let address = parse_listen_address(value)?;
+    if address.port != 80 || address.protocol != ListenProtocol::Tcp {
+        return None;
+    }
+
+    let mut tokens = value.split_whitespace();
+    let target = tokens.next()?;
+    let https_target = if target == "80" {
+        "443".to_string()
+    } else if let Some(host) = target.strip_suffix(":80") {
+        format!("{}:443", host)
+    } else {
+        format!("{}:443", target)
+    };
+    let params: Vec<&str> = tokens.filter(|param| *param != "ssl").collect();
+    Some(
+        std::iter::once(https_target.as_str())
+            .chain(std::iter::once("ssl"))
+            .chain(params)
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn render_redirect_server(server_indent: &str, body_indent: &str, listens: &[String], server_names: &[String]) -> String {
+    let mut lines = vec![format!("{}server {{", server_indent)];
+    lines.extend(listens.iter().map(|listen| format!("{}listen {};", body_indent, listen)));
+    if !server_names.is_empty() {
+        lines.push(format!("{}server_name {};", body_indent, server_names.join(" ")));
+    }
+    lines.push(format!("{}return 301 https://$host$request_uri;", body_indent));
+    lines.push(format!("{}}}", server_indent));
+    lines.join("\n")
+}
+
+/// 用预设中的指令替换 server 级别的同名指令，缺少的插入到 ssl_certificate_key 之后
+fn apply_preset_directives(server_text: &str, directives: &[(&str, &str)]) -> Result<String, String> {
+    let parsed = parse_nginx_config(server_text, "")?;
+    let server = parsed.servers.first().ok_or_else(|| "Server 块文本无效".to_string())?;
+    let lines: Vec<&str> = server_text.lines().collect();
+    let anchor = server
+        .directives
+        .iter()
+        .rfind(|directive| directive.name == "ssl_certificate_key")
+        .map(|directive| directive.line - 1)
+        .ok_or_else(|| "缺少 ssl_certificate_key".to_string())?;
+    let indent = indent_of(lines[anchor]).to_string();
+
+    let mut edits = Vec::new();
+    for (name, value) in directives {
+        let existing = server.directives.iter().find(|directive| {
+            directive.name == *name
+                && (*name != "add_header" || directive.value.split_whitespace().next() == Some(HSTS_HEADER))
+        });
+        let line = format!("{}{} {};", indent, name, value);
+        edits.push(match existing {
+            Some(directive) => TextEdit::Replace(directive.line - 1, line),
+            None => TextEdit::InsertAfter(anchor, line),
+        });
+    }
+
+    Ok(apply_edits(&lines, &edits))
+}
+
+/// server 内指令的缩进，没有指令时比 server 行多一级
+fn server_body_indent(server: &ServerBlock, lines: &[&str]) -> String {
+    server
+        .directives
+        .first()
+        .map(|directive| indent_of(lines[directive.line - 1]).to_string())
+        .unwrap_or_else(|| format!("{}{}", indent_of(lines[0]), INDENT_UNIT))
+}
+
+/// server 从 http 块继承的 add_header 值，来自 server 所在文件与所属主配置中 server 之外的 add_header
+fn inherited_add_headers(content: &str, config_path: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    let main_config = main_config_for(config_path);
+    if main_config != config_path {
+        sources.extend(fs::read_to_string(&main_config).ok());
+    }
+    sources.push(content.to_string());
+
+    sources
+        .iter()
+        .filter_map(|source| parse_nginx_config(source, "").ok())
+        .flat_map(|config| config.global_directives)
+        .filter(|directive| directive.name == "add_header")
+        .map(|directive| directive.value)
+        .collect()
+}
+
+/// 将仅 HTTP 的 server 文本改写为 HTTPS，redirect_http 时追加一个跳转 server；
+/// inherited_headers 为 server 从 http 块继承的 add_header 值
+pub(crate) fn transform_server_to_https(
+    server_text: &str,
+    request: &EnableHttpsRequest,
+    inherited_headers: &[String],
+) -> Result<String, String> {
+    let parsed = parse_nginx_config(server_text, "")?;
+    let server = parsed.servers.first().ok_or_else(|| "Server 块文本无效".to_string())?;
+    if server
+        .listen
+        .iter()
+        .any(|listen| listen.split_whitespace().skip(1).any(|param| param == "ssl"))
+    {
+        return Err("该 Server 块已启用 HTTPS".to_string());
+    }
+
+    let lines: Vec<&str> = server_text.lines().collect();
+    let http_listens: Vec<(usize, String, String)> = server
+        .directives
+        .iter()
+        .filter(|directive| directive.name == "listen")
+        .filter_map(|directive| https_listen_value(&directive.value).map(|https| (directive.line - 1, directive.value.clone(), https)))
+        .collect();
+    if request.redirect_http && http_listens.is_empty() && !server.listen.is_empty() {
+        return Err("该 Server 块未监听 80 端口，无法拆分 HTTP 跳转 server".to_string());
+    }
+
+    // 80 端口的 listen：拆分时改为 443 ssl，否则追加对应的 443 ssl
+    let mut edits = Vec::new();
+    let last_listen = http_listens.iter().map(|(line, _, _)| *line).max();
+    for (line, _, https) in &http_listens {
+        let rendered = format!("{}listen {};", indent_of(lines[*line]), https);
+        if request.redirect_http {
+            edits.push(TextEdit::Replace(*line, rendered));
+        } else if let Some(anchor) = last_listen {
+            edits.push(TextEdit::InsertAfter(anchor, rendered));
+        }
+    }
+
+    // 没有可改写的 80 端口 listen 时补充 443 ssl；未声明 listen 且不拆分时保留 nginx 默认的 80 端口
+    if http_listens.is_empty() {
+        let body_indent = server_body_indent(server, &lines);
+        let anchor = server
+            .directives
+            .iter()
+            .filter(|directive| directive.name == "listen")
+            .map(|directive| directive.line - 1)
+            .max()
+            .unwrap_or(0);
+        if server.listen.is_empty() && !request.redirect_http {
+            edits.push(TextEdit::InsertAfter(anchor, format!("{}listen 80;", body_indent)));
+        }
+        edits.push(TextEdit::InsertAfter(anchor, format!("{}listen 443 ssl;", body_indent)));
+    }
+    let https_text = apply_edits(&lines, &edits);
+
+    let https_text = apply_certificate_directives(
+        &https_text,
+        &quote_value(&request.certificate_path),
+        &quote_value(&request.key_path),
+    )?;
+    // server 一旦声明 add_header 就不再继承 http 块的 add_header，加入 HSTS 时一并复制继承的响应头
+    let mut directives = request.preset.directives(request.hsts);
+    if request.hsts && !server.directives.iter().any(|directive| directive.name == "add_header") {
+        let position = directives.len() - 1;
+        let inherited = inherited_headers
+            .iter()
+            .filter(|value| value.split_whitespace().next() != Some(HSTS_HEADER))
+            .map(|value| ("add_header", value.as_str()));
+        directives.splice(position..position, inherited);
+    }
+    let https_text = apply_preset_directives(&https_text, &directives)?;
+
+    if !request.redirect_http {
+        return Ok(https_text);
+    }
+
+    let server_indent = indent_of(lines[0]);
+    let body_indent = server_body_indent(server, &lines);
+    let redirect_listens: Vec<String> = if http_listens.is_empty() {
+        vec!["80".to_string()]
+    } else {
+        http_listens.iter().map(|(_, value, _)| value.clone()).collect()
+    };
+    let redirect = render_redirect_server(server_indent, &body_indent, &redirect_listens, &server.server_name);
+
+    Ok(format!("{}\n\n{}", https_text, redirect))
+}
+
+fn build_https_server_text(content: &str, config_path: &str, request: &EnableHttpsRequest) -> Result<String, String> {
+    if request.certificate_path.trim().is_empty() || request.key_path.trim().is_empty() {
+        return Err("证书与私钥路径不能为空".to_string());
+    }
+
+    let config = parse_nginx_config(content, config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let server = config
+        .servers
+        .iter()
+        .find(|server| server.id == request.server_id)
+        .ok_or_else(|| format!("未找到 ID 为 {} 的 Server 块", request.server_id))?;
+    if !server.enabled {
+        return Err("请先启用该 Server 块".to_string());
+    }
+
+    transform_server_to_https(&server.raw_content, request, &inherited_add_headers(content, config_path))
+}
+
+async fn plan_enable_https(config_path: &str, request: &EnableHttpsRequest) -> Result<ChangePlan, String> {
+    let content = fs::read_to_string(config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let server_text = build_https_server_text(&content, config_path, request)?;
+    let new_content = build_update_server_content(&content, config_path, &request.server_id, &server_text)?;
+    build_change_plan(config_path, &content, new_content).await
+}
+
+/// 生成启用 HTTPS 的变更计划（不保存到文件）
+#[tauri::command]
+pub async fn generate_enable_https_content(config_path: String, request: EnableHttpsRequest) -> Result<ChangePlan, String> {
+    plan_enable_https(&config_path, &request).await
+}
+
+/// 为 server 启用 HTTPS 并写入配置文件
+#[tauri::command]
+pub async fn enable_server_https(app: AppHandle, config_path: String, request: EnableHttpsRequest) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 启用 HTTPS", request.server_id);
+    audited(&app, "enable_server_https", &label, enable_server_https_internal(config_path, request)).await
+}
+
+async fn enable_server_https_internal(config_path: String, request: EnableHttpsRequest) -> Result<EditResult, String> {
+    let plan = plan_enable_https(&config_path, &request).await?;
+    ensure_plan_valid(&plan.validation)?;
+    write_config_file(&config_path, &plan.new_content)?;
+
+    Ok(EditResult {
+        success: true,
+        message: "已为 Server 块启用 HTTPS".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(preset: TlsPreset, hsts: bool, redirect_http: bool) -> EnableHttpsRequest {
+        EnableHttpsRequest {
+            server_id: "server".to_string(),
+            certificate_path: "/etc/ssl/app.crt".to_string(),
+            key_path: "/etc/ssl/app.key".to_string(),
+            preset,
+            hsts,
+            redirect_http,
+        }
+    }
+
+    #[test]
+    fn transform_should_split_redirect_server_with_intermediate_preset() {
+        let text = "    server {\n        listen 80 default_server;\n        listen [::]:80;\n        server_name app.local www.app.local;\n        root html;\n    }";
+        let inherited = vec!["X-Frame-Options DENY".to_string(), "Strict-Transport-Security \"max-age=300\"".to_string()];
+        let result = transform_server_to_https(text, &request(TlsPreset::Intermediate, true, true), &inherited).unwrap();
+
+        let (https, redirect) = result.split_once("\n\n").unwrap();
+        assert!(https.contains("        listen 443 ssl default_server;\n        listen [::]:443 ssl;\n"));
+        assert!(!https.contains("listen 80"));
+        assert!(https.contains("        ssl_certificate_key /etc/ssl/app.key;\n        ssl_protocols TLSv1.2 TLSv1.3;\n        ssl_ciphers ECDHE-"));
+        assert!(https.contains("ssl_stapling on;"));
+        assert!(https.contains(
+            "        add_header X-Frame-Options DENY;\n        add_header Strict-Transport-Security \"max-age=63072000\" always;\n"
+        ));
+        assert_eq!(https.matches(HSTS_HEADER).count(), 1);
+        assert!(https.ends_with("        root html;\n    }"));
+        assert_eq!(
+            redirect,
+            "    server {\n        listen 80 default_server;\n        listen [::]:80;\n        server_name app.local www.app.local;\n        return 301 https://$host$request_uri;\n    }"
+        );
+    }
+
+    #[test]
+    fn transform_should_keep_http_listener_without_redirect() {
+        let text = "server {\n    listen 127.0.0.1:80;\n    server_name app.local;\n    ssl_protocols TLSv1 TLSv1.2;\n}";
+        let result = transform_server_to_https(text, &request(TlsPreset::Modern, false, false), &[]).unwrap();
+        assert!(result.contains("    listen 127.0.0.1:80;\n    listen 127.0.0.1:443 ssl;\n"));
+        assert!(result.contains("    ssl_protocols TLSv1.3;\n"));
+        assert_eq!(result.matches("ssl_protocols").count(), 1);
+        assert!(!result.contains(HSTS_HEADER));
+        assert!(!result.contains("ssl_ciphers"));
+
+        let https = "server {\n    listen 443 ssl;\n}";
+        assert!(transform_server_to_https(https, &request(TlsPreset::Modern, true, false), &[]).is_err());
+        let other_port = "server {\n    listen 8080;\n}";
+        assert!(transform_server_to_https(other_port, &request(TlsPreset::Modern, true, true), &[]).is_err());
+    }
+
+    #[test]
+    fn transform_should_add_ssl_listener_when_no_port_80_listen() {
+        let other_port = "server {\n    listen 8080;\n    server_name app.local;\n}";
+        let result = transform_server_to_https(other_port, &request(TlsPreset::Modern, false, false), &[]).unwrap();
+        assert!(result.contains("    listen 8080;\n    listen 443 ssl;\n    server_name app.local;\n"));
+        assert_eq!(result.matches("listen 443 ssl;").count(), 1);
+
+        // 未声明 listen 时 nginx 默认监听 80，不拆分跳转时需保留
+        let implicit = "server {\n    server_name app.local;\n}";
+        let result = transform_server_to_https(implicit, &request(TlsPreset::Modern, false, false), &[]).unwrap();
+        assert!(result.starts_with("server {\n    listen 80;\n    listen 443 ssl;\n    server_name app.local;\n"));
+
+        let result = transform_server_to_https(implicit, &request(TlsPreset::Modern, false, true), &[]).unwrap();
+        let (https, redirect) = result.split_once("\n\n").unwrap();
+        assert!(https.starts_with("server {\n    listen 443 ssl;\n    server_name app.local;\n"));
+        assert!(!https.contains("listen 80"));
+        assert!(redirect.contains("    listen 80;\n"));
+    }
+}
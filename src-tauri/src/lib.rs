@@ -1,8 +1,34 @@
+mod audit;
 mod nginx;
 mod settings;
 mod config;
+mod config_text;
 mod file_ops;
 mod logs;
+mod nginx_logs;
+mod nginx_log_format;
+mod nginx_traffic;
+mod nginx_error_analysis;
+mod nginx_metrics;
+mod nginx_watcher;
+mod server_match;
+mod config_watcher;
+mod config_drift;
+mod reload_verification;
+mod port_preflight;
+mod server_conflicts;
+mod route_simulator;
+mod nginx_lint;
+mod security_audit;
+mod cert_inventory;
+mod dev_certs;
+mod enable_https;
+mod server_templates;
+mod proxy_wizard;
+mod config_import;
+mod config_model;
+mod change_plan;
+mod cli;
 
 use nginx::{
     check_nginx_status,
@@ -37,8 +63,54 @@ use config::{
     write_formatted_config,
 };
 use file_ops::open_file_in_system;
+use nginx_logs::{
+    discover_nginx_log_files,
+    search_nginx_log,
+    start_nginx_log_tail,
+    stop_nginx_log_tail,
+    NginxLogTailState,
+};
+use nginx_log_format::{list_nginx_log_formats, parse_nginx_access_log};
+use nginx_traffic::aggregate_nginx_traffic;
+use nginx_error_analysis::analyze_nginx_error_log;
+use nginx_metrics::{
+    detect_stub_status,
+    enable_stub_status,
+    get_nginx_metrics_endpoint,
+    get_nginx_metrics_history,
+    start_nginx_metrics,
+    stop_nginx_metrics,
+    NginxMetricsState,
+};
+use nginx_watcher::spawn_status_watcher;
+use config_watcher::{
+    get_config_watch_status,
+    start_config_watch,
+    stop_config_watch,
+    ConfigWatcherState,
+};
+use config_drift::get_config_drift;
+use port_preflight::preflight_nginx_ports;
+use server_conflicts::analyze_server_conflicts;
+use route_simulator::simulate_nginx_route;
+use nginx_lint::{lint_nginx_config, list_nginx_lint_rules};
+use security_audit::{audit_nginx_security, export_security_audit};
+use cert_inventory::get_certificate_inventory;
+use dev_certs::generate_dev_certificate;
+use enable_https::{enable_server_https, generate_enable_https_content};
+use config_import::import_foreign_config;
+use config_model::{apply_config_model, export_config_model, plan_config_model};
+use proxy_wizard::{add_proxy_location, build_proxy_location, generate_proxy_location_content};
+use server_templates::{
+    delete_server_template, list_server_templates, render_server_template, save_server_template,
+};
 use tauri::Manager;
 
+/// 命令行模式入口：识别到子命令时执行并返回退出码，否则返回 None 以启动窗口
+pub fn run_cli(args: Vec<String>) -> Option<i32> {
+    cli::is_cli_invocation(&args).then(|| cli::run(args))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let mut builder = tauri::Builder::default();
@@ -58,6 +130,14 @@ pub fn run() {
     builder
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(NginxLogTailState::default())
+        .manage(NginxMetricsState::default())
+        .manage(ConfigWatcherState::default())
+        .setup(|app| {
+            // 后台监听 Nginx 进程状态，变化时主动推送给前端
+            spawn_status_watcher(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             start_nginx,
             stop_nginx,
@@ -91,6 +171,46 @@ pub fn run() {
             delete_temp_config,
             write_formatted_config,
             open_file_in_system,
+            discover_nginx_log_files,
+            start_nginx_log_tail,
+            stop_nginx_log_tail,
+            search_nginx_log,
+            list_nginx_log_formats,
+            parse_nginx_access_log,
+            aggregate_nginx_traffic,
+            analyze_nginx_error_log,
+            detect_stub_status,
+            enable_stub_status,
+            start_nginx_metrics,
+            stop_nginx_metrics,
+            get_nginx_metrics_history,
+            get_nginx_metrics_endpoint,
+            start_config_watch,
+            stop_config_watch,
+            get_config_watch_status,
+            get_config_drift,
+            preflight_nginx_ports,
+            analyze_server_conflicts,
+            simulate_nginx_route,
+            list_nginx_lint_rules,
+            lint_nginx_config,
+            audit_nginx_security,
+            export_security_audit,
+            get_certificate_inventory,
+            generate_dev_certificate,
+            generate_enable_https_content,
+            enable_server_https,
+            list_server_templates,
+            save_server_template,
+            delete_server_template,
+            render_server_template,
+            build_proxy_location,
+            generate_proxy_location_content,
+            add_proxy_location,
+            import_foreign_config,
+            export_config_model,
+            plan_config_model,
+            apply_config_model,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
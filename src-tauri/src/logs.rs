@@ -1,8 +1,9 @@
-use crate::settings::get_app_config_dir;
+use crate::nginx::query_nginx_version;
+use crate::settings::{get_app_config_dir, read_app_settings, AppSettings};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
 const MAX_FILE_CHANGE_LOGS: usize = 3;
@@ -34,6 +35,27 @@ pub struct FileChangeLogDetail {
     pub location_diffs: Vec<FileChangeScopeDiff>,
 }
 
+/// 日志产生时所针对的 Nginx 实例
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InstanceProfile {
+    pub nginx_path: String,
+    pub config_path: String,
+}
+
+/// 日志审计上下文，统一由后端采集，不采信前端传入的内容
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LogContext {
+    pub os_user: String,
+    pub hostname: String,
+    pub instance_profile: InstanceProfile,
+    pub nginx_version: Option<String>,
+    pub command: String,
+    pub duration_ms: Option<u64>,
+    pub exit_status: Option<i32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
 #[serde(default, rename_all = "camelCase")]
 pub struct StoredLogEntry {
@@ -44,6 +66,7 @@ pub struct StoredLogEntry {
     pub timestamp_ms: i64,
     pub kind: LogKind,
     pub detail: Option<FileChangeLogDetail>,
+    pub context: Option<LogContext>,
 }
 
 fn get_logs_path() -> Result<PathBuf, String> {
@@ -57,6 +80,57 @@ fn now_timestamp_ms() -> i64 {
         .as_millis() as i64
 }
 
+fn current_os_user() -> String {
+    let user = whoami::username();
+    if user.trim().is_empty() {
+        "unknown".to_string()
+    } else {
+        user
+    }
+}
+
+fn current_hostname() -> String {
+    whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn build_log_context(
+    settings: &AppSettings,
+    command: &str,
+    duration: Option<Duration>,
+    exit_status: Option<i32>,
+) -> LogContext {
+    LogContext {
+        os_user: current_os_user(),
+        hostname: current_hostname(),
+        nginx_version: query_nginx_version(&settings.nginx_path),
+        instance_profile: InstanceProfile {
+            nginx_path: settings.nginx_path.clone(),
+            config_path: settings.config_path.clone(),
+        },
+        command: command.to_string(),
+        duration_ms: duration.map(|value| value.as_millis() as u64),
+        exit_status,
+    }
+}
+
+/// 采集审计上下文：当前系统用户、主机名、实例配置与 Nginx 版本
+pub fn capture_log_context(
+    command: &str,
+    duration: Option<Duration>,
+    exit_status: Option<i32>,
+) -> LogContext {
+    let settings = read_app_settings().unwrap_or_default();
+    build_log_context(&settings, command, duration, exit_status)
+}
+
+/// 前端未声明命令名时记为 `frontend`
+fn frontend_log_command(command: Option<String>) -> String {
+    command
+        .map(|command| command.trim().to_string())
+        .filter(|command| !command.is_empty())
+        .unwrap_or_else(|| "frontend".to_string())
+}
+
 fn normalize_retention_days(retention_days: u32) -> u32 {
     retention_days.clamp(1, 365)
 }
@@ -138,19 +212,31 @@ pub async fn load_operation_logs(retention_days: u32) -> Result<Vec<StoredLogEnt
     Ok(entries)
 }
 
-#[tauri::command]
-pub async fn append_operation_log(
-    entry: StoredLogEntry,
-    retention_days: u32,
-) -> Result<bool, String> {
+/// 追加一条日志并按保留策略整理后写盘
+pub fn append_log_entry(entry: StoredLogEntry, retention_days: u32) -> Result<(), String> {
     let logs_path = get_logs_path()?;
     let mut entries = load_logs_from_path(&logs_path, retention_days)?;
     entries.push(entry);
 
     let normalized = normalize_logs(entries, retention_days, now_timestamp_ms());
-    save_logs_to_path(&logs_path, &normalized)?;
+    save_logs_to_path(&logs_path, &normalized)
+}
 
-    Ok(true)
+#[tauri::command]
+pub async fn append_operation_log(
+    mut entry: StoredLogEntry,
+    retention_days: u32,
+    command: Option<String>,
+) -> Result<StoredLogEntry, String> {
+    // 前端传入的上下文不可信，统一在后端重新采集，仅保留触发日志的命令名
+    entry.context = Some(capture_log_context(
+        &frontend_log_command(command),
+        None,
+        None,
+    ));
+    append_log_entry(entry.clone(), retention_days)?;
+
+    Ok(entry)
 }
 
 #[tauri::command]
@@ -185,6 +271,7 @@ mod tests {
             timestamp_ms,
             kind: LogKind::Operation,
             detail: None,
+            context: None,
         }
     }
 
@@ -207,6 +294,7 @@ mod tests {
                 server_diff: None,
                 location_diffs: Vec::new(),
             }),
+            context: None,
         }
     }
 
@@ -256,6 +344,25 @@ mod tests {
 
         assert_eq!(entries[0].summary, "old message");
         assert_eq!(entries[0].kind, LogKind::Operation);
+        assert!(entries[0].context.is_none());
+    }
+
+    #[test]
+    fn build_log_context_should_record_command_duration_and_exit_status() {
+        let settings = AppSettings {
+            config_path: "conf/nginx.conf".to_string(),
+            ..AppSettings::default()
+        };
+
+        let context = build_log_context(&settings, "reload_nginx", Some(Duration::from_millis(1500)), Some(0));
+
+        assert_eq!(context.command, "reload_nginx");
+        assert_eq!(context.duration_ms, Some(1500));
+        assert_eq!(context.exit_status, Some(0));
+        assert_eq!(context.instance_profile.config_path, "conf/nginx.conf");
+        assert!(context.nginx_version.is_none());
+        assert!(!context.os_user.is_empty());
+        assert!(!context.hostname.is_empty());
     }
 
     #[test]
@@ -277,4 +384,14 @@ mod tests {
         assert!(entries.is_empty());
         let _ = fs::remove_file(path);
     }
+
+    #[test]
+    fn frontend_log_command_should_keep_invoked_command() {
+        assert_eq!(
+            frontend_log_command(Some("set_server_enabled_state".to_string())),
+            "set_server_enabled_state"
+        );
+        assert_eq!(frontend_log_command(Some("  ".to_string())), "frontend");
+        assert_eq!(frontend_log_command(None), "frontend");
+    }
 }
@@ -1,9 +1,17 @@
+use crate::audit::{audited, audited_headless, build_system_log_entry, publish_log_entry};
+use crate::nginx_watcher::managed_operation;
+use crate::config_drift::record_applied_config;
+use crate::reload_verification::{verify_reload, ReloadBaseline};
+use crate::port_preflight::find_port_conflicts;
+use crate::settings::read_app_settings;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{BTreeSet, HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::Duration;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
 use tokio::time::sleep;
 
 #[cfg(target_os = "windows")]
@@ -72,7 +80,7 @@ fn parse_nginx_path(nginx_path: &str) -> (String, String) {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NginxStatus {
     pub is_running: bool,
     pub process_count: u32,
@@ -647,61 +655,208 @@ pub fn is_nginx_running() -> bool {
     }
 }
 
-/// 获取 Nginx 进程数量
-pub fn get_nginx_process_count() -> u32 {
+/// Nginx 进程快照：master 与 worker 的 PID
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxProcessSnapshot {
+    pub master_pid: Option<u32>,
+    pub worker_pids: Vec<u32>,
+    pub process_count: u32,
+}
+
+impl NginxProcessSnapshot {
+    pub fn is_running(&self) -> bool {
+        self.process_count > 0
+    }
+}
+
+#[cfg(target_os = "windows")]
+#[derive(Debug, Deserialize)]
+struct NginxProcessEntry {
+    pid: u32,
+    ppid: u32,
+}
+
+/// 由 (pid, ppid) 列表推断进程树：父进程不是 nginx 的为 master，其子进程为 worker
+fn build_process_snapshot(processes: &[(u32, u32)]) -> NginxProcessSnapshot {
+    let pids: HashSet<u32> = processes.iter().map(|(pid, _)| *pid).collect();
+    let master_pid = processes
+        .iter()
+        .filter(|(_, ppid)| !pids.contains(ppid))
+        .map(|(pid, _)| *pid)
+        .min();
+
+    let mut worker_pids: Vec<u32> = processes
+        .iter()
+        .filter(|(_, ppid)| Some(*ppid) == master_pid)
+        .map(|(pid, _)| *pid)
+        .collect();
+    worker_pids.sort_unstable();
+
+    NginxProcessSnapshot {
+        master_pid,
+        worker_pids,
+        process_count: processes.len() as u32,
+    }
+}
+
+/// 一次性列出所有 nginx 进程的 (pid, ppid)
+#[cfg(target_os = "windows")]
+fn list_nginx_processes() -> Result<Vec<(u32, u32)>, String> {
+    let script = r#"
+$items = Get-CimInstance Win32_Process -Filter "Name = 'nginx.exe'" -ErrorAction SilentlyContinue |
+    ForEach-Object { [PSCustomObject]@{ pid = [int]$_.ProcessId; ppid = [int]$_.ParentProcessId } }
+@($items) | ConvertTo-Json -Compress
+"#;
+
+    let output = run_powershell(script)?;
+    let entries: Vec<NginxProcessEntry> = deserialize_json_vec(&output)?;
+    Ok(entries.into_iter().map(|entry| (entry.pid, entry.ppid)).collect())
+}
+
+/// 一次性列出所有 nginx 进程的 (pid, ppid)
+#[cfg(not(target_os = "windows"))]
+fn list_nginx_processes() -> Result<Vec<(u32, u32)>, String> {
+    let output = Command::new("ps")
+        .args(["-eo", "pid=,ppid=,comm="])
+        .output()
+        .map_err(|e| format!("执行 ps 失败: {}", e))?;
+
+    let stdout = decode_output(&output.stdout);
+    Ok(stdout
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let pid = columns.next()?.parse::<u32>().ok()?;
+            let ppid = columns.next()?.parse::<u32>().ok()?;
+            let command: Vec<&str> = columns.collect();
+            let name = Path::new(&command.join(" "))
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())?;
+            (name == "nginx").then_some((pid, ppid))
+        })
+        .collect())
+}
+
+/// 查询当前 Nginx 进程快照，只启动一次外部命令；查询失败与“未运行”需区分开
+pub fn query_nginx_processes() -> Result<NginxProcessSnapshot, String> {
+    list_nginx_processes().map(|processes| build_process_snapshot(&processes))
+}
+
+/// `nginx -V` 输出的构建信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NginxBuildInfo {
+    pub version: Option<String>,
+    pub prefix: Option<String>,
+}
+
+/// 从 `nginx -v` 的输出中提取版本号，例如 `nginx version: nginx/1.24.0` -> `nginx/1.24.0`
+fn parse_nginx_version_output(text: &str) -> Option<String> {
+    text.lines()
+        .find_map(|line| line.trim().strip_prefix("nginx version:"))
+        .map(|version| version.trim().to_string())
+        .filter(|version| !version.is_empty())
+}
+
+/// 从 `nginx -V` 的 configure arguments 中提取 `--prefix`
+fn parse_nginx_prefix_output(text: &str) -> Option<String> {
+    text.split_whitespace()
+        .find_map(|token| token.strip_prefix("--prefix="))
+        .map(|prefix| prefix.trim_matches('"').to_string())
+        .filter(|prefix| !prefix.is_empty())
+}
+
+/// 查询失败的结果只短暂缓存，路径修正或 Nginx 安装后可重新获取
+const BUILD_INFO_FAILURE_TTL: Duration = Duration::from_secs(30);
+
+/// 查询 Nginx 构建信息，按路径缓存结果，避免每条日志都重复执行 `nginx -V`
+pub fn query_nginx_build_info(nginx_path: &str) -> NginxBuildInfo {
+    static BUILD_INFO_CACHE: OnceLock<Mutex<HashMap<String, (NginxBuildInfo, Instant)>>> =
+        OnceLock::new();
+
+    if nginx_path.is_empty() {
+        return NginxBuildInfo::default();
+    }
+
+    let cache = BUILD_INFO_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let cached = cache.lock().ok().and_then(|map| {
+        map.get(nginx_path)
+            .filter(|(info, queried_at)| {
+                info.version.is_some() || queried_at.elapsed() < BUILD_INFO_FAILURE_TTL
+            })
+            .map(|(info, _)| info.clone())
+    });
+    if let Some(info) = cached {
+        return info;
+    }
+
+    let (nginx_exe, _working_dir) = parse_nginx_path(nginx_path);
+
     #[cfg(target_os = "windows")]
-    {
-        let output = Command::new("cmd")
-            .creation_flags(CREATE_NO_WINDOW)
-            .args(["/C", "tasklist | findstr /i nginx.exe"])
-            .output();
+    let output = build_hidden_nginx_command(&nginx_exe)
+        .arg("-V")
+        .current_dir(&_working_dir)
+        .output();
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                let stdout = decode_output(&output.stdout);
-                return stdout.lines().count() as u32;
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new(&nginx_exe).arg("-V").output();
+
+    // nginx -V 的输出在 stderr 中
+    let info = output
+        .map(|output| {
+            let text = decode_output(&output.stderr);
+            NginxBuildInfo {
+                version: parse_nginx_version_output(&text),
+                prefix: parse_nginx_prefix_output(&text),
             }
-        }
-        0
+        })
+        .unwrap_or_default();
+
+    if let Ok(mut map) = cache.lock() {
+        map.insert(nginx_path.to_string(), (info.clone(), Instant::now()));
     }
 
-    #[cfg(not(target_os = "windows"))]
-    {
-        let output = Command::new("sh")
-            .args(["-c", "pgrep -x nginx | wc -l"])
-            .output();
+    info
+}
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                let stdout = decode_output(&output.stdout);
-                return stdout.trim().parse().unwrap_or(0);
-            }
-        }
-        0
+/// 查询 Nginx 版本
+pub fn query_nginx_version(nginx_path: &str) -> Option<String> {
+    query_nginx_build_info(nginx_path).version
+}
+
+/// 获取 Nginx 的 prefix 目录，配置中的相对路径都以此为基准；
+/// 无法从 `nginx -V` 获取时退回到可执行文件所在目录
+pub fn resolve_nginx_prefix(nginx_path: &str) -> Option<PathBuf> {
+    if nginx_path.is_empty() {
+        return None;
     }
+
+    query_nginx_build_info(nginx_path)
+        .prefix
+        .map(PathBuf::from)
+        .or_else(|| Some(PathBuf::from(parse_nginx_path(nginx_path).1)))
 }
 
 /// 检查 Nginx 状态
 #[tauri::command]
 pub async fn check_nginx_status() -> Result<NginxStatus, String> {
-    let is_running = is_nginx_running();
-    let process_count = if is_running {
-        get_nginx_process_count()
-    } else {
-        0
-    };
+    Ok(build_nginx_status(&query_nginx_processes()?))
+}
 
+/// 由进程快照生成前端使用的状态
+pub fn build_nginx_status(snapshot: &NginxProcessSnapshot) -> NginxStatus {
+    let is_running = snapshot.is_running();
     let message = if is_running {
-        format!("Nginx 正在运行 ({} 个进程)", process_count)
+        format!("Nginx 正在运行 ({} 个进程)", snapshot.process_count)
     } else {
         "Nginx 未运行".to_string()
     };
 
-    Ok(NginxStatus {
+    NginxStatus {
         is_running,
-        process_count,
+        process_count: snapshot.process_count,
         message,
-    })
+    }
 }
 
 #[tauri::command]
@@ -733,12 +888,19 @@ pub async fn inspect_ports(ports: Vec<u16>) -> Result<Vec<PortInspectionResult>,
 }
 
 #[tauri::command]
-pub async fn terminate_process(pid: u32) -> Result<ProcessOperationResult, String> {
-    Ok(terminate_process_internal(pid))
+pub async fn terminate_process(app: AppHandle, pid: u32) -> Result<ProcessOperationResult, String> {
+    audited(&app, "terminate_process", &format!("进程 {} 结束", pid), managed_operation(async move {
+        Ok(terminate_process_internal(pid))
+    }))
+    .await
 }
 
 #[tauri::command]
-pub async fn release_port(port: u16) -> Result<ProcessOperationResult, String> {
+pub async fn release_port(app: AppHandle, port: u16) -> Result<ProcessOperationResult, String> {
+    audited(&app, "release_port", &format!("端口 {} 释放", port), managed_operation(release_port_internal(port))).await
+}
+
+async fn release_port_internal(port: u16) -> Result<ProcessOperationResult, String> {
     let inspections = inspect_ports(vec![port]).await?;
     let port_result = inspections.into_iter().next().ok_or_else(|| "未获取到端口查询结果".to_string())?;
 
@@ -784,9 +946,42 @@ pub async fn release_port(port: u16) -> Result<ProcessOperationResult, String> {
     }
 }
 
+/// 本次操作生效的配置路径，未传入时使用应用设置中的路径
+fn effective_config_path(config_path: Option<String>) -> String {
+    config_path
+        .filter(|path| !path.trim().is_empty())
+        .or_else(|| read_app_settings().ok().map(|settings| settings.config_path))
+        .unwrap_or_default()
+}
+
+/// 启动、重启、重载成功后，记录生效配置的指纹作为运行中配置；返回快照落盘失败的原因
+fn record_config_applied(result: &Result<OperationResult, String>, config_path: &str) -> Option<String> {
+    if !matches!(result, Ok(result) if result.success) {
+        return None;
+    }
+    record_applied_config(config_path).err()
+}
+
+fn publish_config_applied(app: &AppHandle, result: &Result<OperationResult, String>, config_path: &str) {
+    if let Some(error) = record_config_applied(result, config_path) {
+        publish_log_entry(app, build_system_log_entry("config_drift", "error", error));
+    }
+}
+
 /// 启动 Nginx
 #[tauri::command]
-pub async fn start_nginx(nginx_path: String) -> Result<OperationResult, String> {
+pub async fn start_nginx(
+    app: AppHandle,
+    nginx_path: String,
+    config_path: Option<String>,
+) -> Result<OperationResult, String> {
+    let config_path = effective_config_path(config_path);
+    let result = audited(&app, "start_nginx", "Nginx 启动", managed_operation(start_nginx_internal(nginx_path, config_path.clone()))).await;
+    publish_config_applied(&app, &result, &config_path);
+    result
+}
+
+async fn start_nginx_internal(nginx_path: String, config_path: String) -> Result<OperationResult, String> {
     if nginx_path.is_empty() {
         return Ok(OperationResult {
             success: false,
@@ -801,6 +996,14 @@ pub async fn start_nginx(nginx_path: String) -> Result<OperationResult, String>
         });
     }
 
+    // 端口预检：被其他进程占用的监听端口会导致启动或重载失败
+    if let Some(report) = find_port_conflicts(&config_path).await {
+        return Ok(OperationResult {
+            success: false,
+            message: report.message,
+        });
+    }
+
     #[cfg(target_os = "windows")]
     {
         let (nginx_exe, working_dir) = parse_nginx_path(&nginx_path);
@@ -915,7 +1118,11 @@ pub async fn start_nginx(nginx_path: String) -> Result<OperationResult, String>
 
 /// 停止 Nginx
 #[tauri::command]
-pub async fn stop_nginx() -> Result<OperationResult, String> {
+pub async fn stop_nginx(app: AppHandle) -> Result<OperationResult, String> {
+    audited(&app, "stop_nginx", "Nginx 停止", managed_operation(stop_nginx_internal())).await
+}
+
+async fn stop_nginx_internal() -> Result<OperationResult, String> {
     if !is_nginx_running() {
         return Ok(OperationResult {
             success: false,
@@ -985,7 +1192,18 @@ pub async fn stop_nginx() -> Result<OperationResult, String> {
 
 /// 重启 Nginx
 #[tauri::command]
-pub async fn restart_nginx(nginx_path: String) -> Result<OperationResult, String> {
+pub async fn restart_nginx(
+    app: AppHandle,
+    nginx_path: String,
+    config_path: Option<String>,
+) -> Result<OperationResult, String> {
+    let config_path = effective_config_path(config_path);
+    let result = audited(&app, "restart_nginx", "Nginx 重启", managed_operation(restart_nginx_internal(nginx_path, config_path.clone()))).await;
+    publish_config_applied(&app, &result, &config_path);
+    result
+}
+
+async fn restart_nginx_internal(nginx_path: String, config_path: String) -> Result<OperationResult, String> {
     if nginx_path.is_empty() {
         return Ok(OperationResult {
             success: false,
@@ -993,21 +1211,53 @@ pub async fn restart_nginx(nginx_path: String) -> Result<OperationResult, String
         });
     }
 
+    // 停止前先做端口预检，避免停止后才发现无法启动
+    if let Some(report) = find_port_conflicts(&config_path).await {
+        return Ok(OperationResult {
+            success: false,
+            message: report.message,
+        });
+    }
+
     // 先停止
     if is_nginx_running() {
-        let stop_result = stop_nginx().await?;
+        let stop_result = stop_nginx_internal().await?;
         if !stop_result.success {
             return Ok(stop_result);
         }
     }
 
     // 再启动
-    start_nginx(nginx_path).await
+    start_nginx_internal(nginx_path, config_path).await
 }
 
 /// 重新加载配置
 #[tauri::command]
-pub async fn reload_nginx(nginx_path: String) -> Result<OperationResult, String> {
+pub async fn reload_nginx(
+    app: AppHandle,
+    nginx_path: String,
+    config_path: Option<String>,
+) -> Result<OperationResult, String> {
+    let config_path = effective_config_path(config_path);
+    let operation = managed_operation(reload_nginx_internal(nginx_path, config_path.clone()));
+    let result = audited(&app, "reload_nginx", "配置重载", operation).await;
+    publish_config_applied(&app, &result, &config_path);
+    result
+}
+
+/// 命令行模式下的配置重载，不依赖窗口句柄；
+/// 后两个返回值分别为操作日志、运行配置快照落盘失败的原因
+pub(crate) async fn reload_nginx_headless(
+    nginx_path: String,
+    config_path: String,
+) -> (Result<OperationResult, String>, Option<String>, Option<String>) {
+    let operation = managed_operation(reload_nginx_internal(nginx_path, config_path.clone()));
+    let (result, log_error) = audited_headless("reload_nginx", "配置重载", operation).await;
+    let snapshot_error = record_config_applied(&result, &config_path);
+    (result, log_error, snapshot_error)
+}
+
+async fn reload_nginx_internal(nginx_path: String, config_path: String) -> Result<OperationResult, String> {
     if nginx_path.is_empty() {
         return Ok(OperationResult {
             success: false,
@@ -1022,6 +1272,16 @@ pub async fn reload_nginx(nginx_path: String) -> Result<OperationResult, String>
         });
     }
 
+    // 端口预检：被其他进程占用的监听端口会导致启动或重载失败
+    if let Some(report) = find_port_conflicts(&config_path).await {
+        return Ok(OperationResult {
+            success: false,
+            message: report.message,
+        });
+    }
+
+    let baseline = ReloadBaseline::capture(&nginx_path, &config_path);
+
     #[cfg(target_os = "windows")]
     {
         let (nginx_exe, working_dir) = parse_nginx_path(&nginx_path);
@@ -1036,15 +1296,7 @@ pub async fn reload_nginx(nginx_path: String) -> Result<OperationResult, String>
                 let stdout = decode_output(&output.stdout);
 
                 if output.status.success() {
-                    let msg = if !stderr.is_empty() {
-                        format!("✓ 配置重新加载成功\n{}", stderr)
-                    } else {
-                        "✓ 配置重新加载成功".to_string()
-                    };
-                    Ok(OperationResult {
-                        success: true,
-                        message: msg,
-                    })
+                    Ok(verify_reload(baseline, &stderr).await)
                 } else {
                     let error_msg = if !stderr.is_empty() {
                         stderr
@@ -1053,7 +1305,7 @@ pub async fn reload_nginx(nginx_path: String) -> Result<OperationResult, String>
                     };
 
                     if is_nginx_signal_access_denied(&error_msg) {
-                        let restart_result = restart_nginx(nginx_path.clone()).await?;
+                        let restart_result = restart_nginx_internal(nginx_path.clone(), config_path.clone()).await?;
                         if restart_result.success {
                             Ok(OperationResult {
                                 success: true,
@@ -1100,15 +1352,7 @@ pub async fn reload_nginx(nginx_path: String) -> Result<OperationResult, String>
                 let stdout = decode_output(&output.stdout);
 
                 if output.status.success() {
-                    let msg = if !stderr.is_empty() {
-                        format!("✓ 配置重新加载成功\n{}", stderr)
-                    } else {
-                        "✓ 配置重新加载成功".to_string()
-                    };
-                    Ok(OperationResult {
-                        success: true,
-                        message: msg,
-                    })
+                    Ok(verify_reload(baseline, &stderr).await)
                 } else {
                     let error_msg = if !stderr.is_empty() {
                         stderr
@@ -1373,4 +1617,30 @@ mod tests {
         assert_eq!(results[2].entries[0].pid, 2000);
         assert!(results[0].message.contains("1 个监听进程"));
     }
+
+    #[test]
+    fn parse_nginx_version_output_should_extract_version() {
+        let output = "nginx version: nginx/1.24.0\n";
+
+        assert_eq!(parse_nginx_version_output(output).as_deref(), Some("nginx/1.24.0"));
+        assert_eq!(parse_nginx_version_output("command not found"), None);
+    }
+
+    #[test]
+    fn build_process_snapshot_should_split_master_and_workers() {
+        let snapshot = build_process_snapshot(&[(100, 1), (101, 100), (102, 100)]);
+
+        assert_eq!(snapshot.master_pid, Some(100));
+        assert_eq!(snapshot.worker_pids, vec![101, 102]);
+        assert_eq!(snapshot.process_count, 3);
+        assert!(!build_process_snapshot(&[]).is_running());
+    }
+
+    #[test]
+    fn parse_nginx_prefix_output_should_read_configure_arguments() {
+        let output = "nginx version: nginx/1.24.0\nconfigure arguments: --prefix=/etc/nginx --sbin-path=/usr/sbin/nginx\n";
+
+        assert_eq!(parse_nginx_prefix_output(output).as_deref(), Some("/etc/nginx"));
+        assert_eq!(parse_nginx_prefix_output("nginx version: nginx/1.24.0"), None);
+    }
 }
@@ -0,0 +1,718 @@
+use crate::config::{load_nginx_config, NginxConfig, ServerBlock};
+use crate::nginx_logs::{
+    discover_log_sources, extract_log_timestamp_ms, for_each_log_line, list_log_files, resolve_log_prefix,
+    NginxLogKind,
+};
+use crate::server_match::{is_default_server, match_location, select_server, ServerSelectionReason};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::OnceLock;
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+const DEFAULT_BUCKET_MINUTES: u32 = 60;
+const BYTES_PER_MEGABYTE: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DirectiveScope {
+    Main,
+    Events,
+    Http,
+    Server,
+    Location,
+    Upstream,
+}
+
+/// 针对某类错误建议调整的指令
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectiveSuggestion {
+    pub directive: String,
+    pub value: String,
+    pub scope: DirectiveScope,
+}
+
+/// 错误关联到的配置位置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLogTarget {
+    pub server_id: Option<String>,
+    pub server_name: Vec<String>,
+    pub location_id: Option<String>,
+    pub location_path: Option<String>,
+    /// location 中 proxy_pass 等指向的上游
+    pub upstream: Option<String>,
+    /// 错误日志中记录的实际上游地址
+    pub upstream_peer: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLogBucket {
+    pub bucket_ms: i64,
+    pub count: usize,
+}
+
+/// 同一签名的错误汇总
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLogGroup {
+    pub signature: String,
+    /// 命中内置规则时为规则 ID
+    pub rule_id: Option<String>,
+    pub title: String,
+    pub level: String,
+    pub count: usize,
+    pub first_seen_ms: Option<i64>,
+    pub last_seen_ms: Option<i64>,
+    pub timeline: Vec<ErrorLogBucket>,
+    pub sample: String,
+    pub hint: Option<String>,
+    pub suggestion: Option<DirectiveSuggestion>,
+    pub targets: Vec<ErrorLogTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ErrorLogAnalysisRequest {
+    pub config_path: String,
+    pub nginx_path: String,
+    /// 为空时分析配置中声明的全部 error_log
+    pub paths: Vec<String>,
+    pub include_rotated: bool,
+    pub start_time_ms: Option<i64>,
+    pub end_time_ms: Option<i64>,
+    pub bucket_minutes: u32,
+}
+
+impl Default for ErrorLogAnalysisRequest {
+    fn default() -> Self {
+        Self {
+            config_path: String::new(),
+            nginx_path: String::new(),
+            paths: Vec::new(),
+            include_rotated: false,
+            start_time_ms: None,
+            end_time_ms: None,
+            bucket_minutes: DEFAULT_BUCKET_MINUTES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorLogAnalysisResult {
+    pub groups: Vec<ErrorLogGroup>,
+    pub scanned_files: Vec<String>,
+    pub total_lines: usize,
+    pub warnings: Vec<String>,
+}
+
+// ==================== 行解析 ====================
+
+/// error_log 中的一行：`时间 [级别] pid#tid: *连接号 消息, client: ..., server: ...`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct ErrorLogLine {
+    pub timestamp_ms: Option<i64>,
+    pub level: String,
+    pub message: String,
+    /// client / server / request / upstream / host 等上下文
+    pub context: HashMap<String, String>,
+}
+
+fn error_line_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r"^(\d{4}/\d{2}/\d{2} \d{2}:\d{2}:\d{2}) \[(\w+)\] \d+#\d+: (?:\*\d+ )?(.*)$")
+            .expect("error log line regex")
+    })
+}
+
+fn context_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(r#", (client|server|request|upstream|host|referrer|subrequest): ("(?:[^"\\]|\\.)*"|[^,]*)"#)
+            .expect("error log context regex")
+    })
+}
+
+pub(crate) fn parse_error_log_line(line: &str) -> Option<ErrorLogLine> {
+    let captures = error_line_regex().captures(line)?;
+    let body = captures.get(3).map(|value| value.as_str()).unwrap_or_default();
+
+    let context_start = context_regex().find(body).map(|found| found.start()).unwrap_or(body.len());
+    let context = context_regex()
+        .captures_iter(&body[context_start..])
+        .map(|pair| (pair[1].to_string(), pair[2].trim_matches('"').to_string()))
+        .collect();
+
+    Some(ErrorLogLine {
+        timestamp_ms: extract_log_timestamp_ms(line),
+        level: captures[2].to_string(),
+        message: body[..context_start].trim().to_string(),
+        context,
+    })
+}
+
+// ==================== 规则分类 ====================
+
+struct ErrorRule {
+    id: &'static str,
+    /// 消息需同时包含的片段
+    needles: &'static [&'static str],
+    title: &'static str,
+    hint: &'static str,
+    suggestion: Option<(&'static str, &'static str, DirectiveScope)>,
+}
+
+const ERROR_RULES: &[ErrorRule] = &[
+    ErrorRule {
+        id: "upstream-connection-refused",
+        needles: &["connect() failed", "Connection refused", "upstream"],
+        title: "上游拒绝连接",
+        hint: "上游服务未启动或端口不正确，请检查 proxy_pass / upstream 中的地址与后端进程",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "upstream-connect-timeout",
+        needles: &["upstream timed out", "while connecting to upstream"],
+        title: "连接上游超时",
+        hint: "后端不可达或网络阻塞，可适当调大连接超时并检查防火墙",
+        suggestion: Some(("proxy_connect_timeout", "10s", DirectiveScope::Location)),
+    },
+    ErrorRule {
+        id: "upstream-read-timeout",
+        needles: &["upstream timed out", "while reading"],
+        title: "读取上游响应超时",
+        hint: "后端处理耗时超过 proxy_read_timeout（默认 60s），可调大超时或优化接口",
+        suggestion: Some(("proxy_read_timeout", "120s", DirectiveScope::Location)),
+    },
+    ErrorRule {
+        id: "no-live-upstreams",
+        needles: &["no live upstreams"],
+        title: "没有可用的上游节点",
+        hint: "upstream 中所有节点都被标记为失败，请检查后端健康状况以及 max_fails / fail_timeout 设置",
+        suggestion: Some(("server", "<地址> max_fails=3 fail_timeout=30s", DirectiveScope::Upstream)),
+    },
+    ErrorRule {
+        id: "upstream-prematurely-closed",
+        needles: &["upstream prematurely closed connection"],
+        title: "上游提前关闭连接",
+        hint: "后端在响应完成前断开，常见于后端崩溃或自身超时，请检查后端日志",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "upstream-header-too-big",
+        needles: &["upstream sent too big header"],
+        title: "上游响应头过大",
+        hint: "响应头（常见为大量 Cookie）超过代理缓冲区，需要调大 proxy_buffer_size",
+        suggestion: Some(("proxy_buffer_size", "16k", DirectiveScope::Location)),
+    },
+    ErrorRule {
+        id: "client-body-too-large",
+        needles: &["client intended to send too large body"],
+        title: "请求体超过限制",
+        hint: "上传内容超过 client_max_body_size（默认 1m）",
+        suggestion: Some(("client_max_body_size", "10m", DirectiveScope::Server)),
+    },
+    ErrorRule {
+        id: "file-not-found",
+        needles: &["open()", "No such file or directory"],
+        title: "文件不存在",
+        hint: "请求的静态文件不存在，请检查 root / alias 路径或使用 try_files 兜底",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "permission-denied",
+        needles: &["Permission denied"],
+        title: "权限不足",
+        hint: "worker 进程用户无权访问文件或目录，请检查 user 指令与文件权限",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "directory-index-forbidden",
+        needles: &["directory index of", "is forbidden"],
+        title: "目录索引被禁止",
+        hint: "目录下没有 index 文件且未开启 autoindex",
+        suggestion: Some(("index", "index.html index.htm", DirectiveScope::Location)),
+    },
+    ErrorRule {
+        id: "rewrite-cycle",
+        needles: &["rewrite or internal redirection cycle"],
+        title: "重写或内部跳转循环",
+        hint: "try_files / rewrite 的最终目标又回到了同一 location，请检查兜底路径",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "worker-connections-exhausted",
+        needles: &["worker_connections are not enough"],
+        title: "worker 连接数不足",
+        hint: "并发连接超过 worker_connections 上限",
+        suggestion: Some(("worker_connections", "4096", DirectiveScope::Events)),
+    },
+    ErrorRule {
+        id: "too-many-open-files",
+        needles: &["Too many open files"],
+        title: "打开文件数超限",
+        hint: "进程文件描述符耗尽，需要同时调高系统 ulimit",
+        suggestion: Some(("worker_rlimit_nofile", "65535", DirectiveScope::Main)),
+    },
+    ErrorRule {
+        id: "address-in-use",
+        needles: &["bind()", "Address already in use"],
+        title: "端口已被占用",
+        hint: "listen 的端口被其他进程占用，可在进程管理中查看并释放端口",
+        suggestion: None,
+    },
+    ErrorRule {
+        id: "ssl-handshake-failed",
+        needles: &["SSL_do_handshake() failed"],
+        title: "SSL 握手失败",
+        hint: "客户端与服务端的协议或加密套件不兼容，或证书链不完整",
+        suggestion: Some(("ssl_protocols", "TLSv1.2 TLSv1.3", DirectiveScope::Server)),
+    },
+    ErrorRule {
+        id: "limiting-requests",
+        needles: &["limiting requests"],
+        title: "请求被限流",
+        hint: "请求速率超过 limit_req 配置，必要时可增加 burst",
+        suggestion: Some(("limit_req", "zone=<zone> burst=20 nodelay", DirectiveScope::Location)),
+    },
+    ErrorRule {
+        id: "client-closed-connection",
+        needles: &["client prematurely closed connection"],
+        title: "客户端提前断开",
+        hint: "客户端在响应完成前关闭连接，通常由用户取消或客户端超时导致",
+        suggestion: None,
+    },
+];
+
+/// 从 “client intended to send too large body: 12345678 bytes” 推算建议值，向上取整到 10m
+fn suggest_body_size(message: &str) -> Option<String> {
+    let bytes: u64 = message
+        .split("too large body:")
+        .nth(1)?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+
+    let megabytes = bytes.div_ceil(BYTES_PER_MEGABYTE).max(1);
+    Some(format!("{}m", megabytes.div_ceil(10) * 10))
+}
+
+/// 未命中规则时的签名：引号内容与数字替换为占位符
+fn normalize_signature(message: &str) -> String {
+    static QUOTED: OnceLock<Regex> = OnceLock::new();
+    static NUMBERS: OnceLock<Regex> = OnceLock::new();
+
+    let quoted = QUOTED.get_or_init(|| Regex::new(r#""[^"]*""#).expect("quoted regex"));
+    let numbers = NUMBERS.get_or_init(|| Regex::new(r"\d+").expect("number regex"));
+
+    let replaced = quoted.replace_all(message, "\"*\"");
+    numbers.replace_all(&replaced, "N").to_string()
+}
+
+struct Classification {
+    signature: String,
+    rule_id: Option<String>,
+    title: String,
+    hint: Option<String>,
+    suggestion: Option<DirectiveSuggestion>,
+}
+
+fn classify_message(message: &str) -> Classification {
+    let Some(rule) = ERROR_RULES
+        .iter()
+        .find(|rule| rule.needles.iter().all(|needle| message.contains(needle)))
+    else {
+        let signature = normalize_signature(message);
+        return Classification {
+            title: signature.clone(),
+            signature,
+            rule_id: None,
+            hint: None,
+            suggestion: None,
+        };
+    };
+
+    let suggestion = rule.suggestion.map(|(directive, value, scope)| DirectiveSuggestion {
+        directive: directive.to_string(),
+        value: if rule.id == "client-body-too-large" {
+            suggest_body_size(message).unwrap_or_else(|| value.to_string())
+        } else {
+            value.to_string()
+        },
+        scope,
+    });
+
+    Classification {
+        signature: rule.id.to_string(),
+        rule_id: Some(rule.id.to_string()),
+        title: rule.title.to_string(),
+        hint: Some(rule.hint.to_string()),
+        suggestion,
+    }
+}
+
+// ==================== 关联配置 ====================
+
+const PASS_DIRECTIVES: &[&str] = &["proxy_pass", "fastcgi_pass", "grpc_pass", "uwsgi_pass", "scgi_pass"];
+
+/// 取 proxy_pass 等指令的目标主机，`http://backend/api` -> `backend`
+fn pass_target(value: &str) -> String {
+    let without_scheme = value.split("://").nth(1).unwrap_or(value);
+    without_scheme
+        .split('/')
+        .next()
+        .unwrap_or(without_scheme)
+        .trim()
+        .to_string()
+}
+
+struct TargetResolver<'a> {
+    servers: Vec<&'a ServerBlock>,
+    /// (上下文字段, 名称) 到选中 server 的缓存，避免逐行重复编译 server_name 正则
+    selections: HashMap<(&'static str, String), Option<usize>>,
+}
+
+impl<'a> TargetResolver<'a> {
+    fn new(config: &'a NginxConfig) -> Self {
+        Self {
+            servers: config.servers.iter().filter(|server| server.enabled).collect(),
+            selections: HashMap::new(),
+        }
+    }
+
+    /// 按 nginx 的虚拟主机选择规则查找 server；`server` 字段是 nginx 已选中 server 的名称，
+    /// 只接受名称命中，`host` 字段未命中任何名称时回退到 default_server 或第一个 server
+    fn select(&mut self, key: &'static str, name: &str) -> Option<&'a ServerBlock> {
+        let servers = &self.servers;
+        let index = *self.selections.entry((key, name.to_string())).or_insert_with(|| {
+            let candidates: Vec<(&ServerBlock, bool)> =
+                servers.iter().map(|server| (*server, is_default_server(server, None))).collect();
+            match select_server(&candidates, name) {
+                Some((index, ServerSelectionReason::Name(..))) => Some(index),
+                Some((index, _)) if key == "host" => Some(index),
+                _ => None,
+            }
+        });
+        index.map(|index| self.servers[index])
+    }
+
+    fn resolve(&mut self, line: &ErrorLogLine) -> ErrorLogTarget {
+        let server = line
+            .context
+            .get("server")
+            .and_then(|name| self.select("server", name))
+            .or_else(|| line.context.get("host").and_then(|host| self.select("host", host)));
+
+        let uri = line
+            .context
+            .get("request")
+            .and_then(|request| request.split_whitespace().nth(1));
+        let location = server.zip(uri).and_then(|(server, uri)| match_location(server, uri));
+
+        let upstream = location
+            .and_then(|location| {
+                location
+                    .directives
+                    .iter()
+                    .find(|directive| PASS_DIRECTIVES.contains(&directive.name.as_str()))
+            })
+            .map(|directive| pass_target(&directive.value));
+        let upstream_peer = line.context.get("upstream").map(|value| pass_target(value));
+
+        ErrorLogTarget {
+            server_id: server.map(|server| server.id.clone()),
+            server_name: server.map(|server| server.server_name.clone()).unwrap_or_default(),
+            location_id: location.map(|location| location.id.clone()),
+            location_path: location.map(|location| location.path.clone()),
+            upstream: upstream.or_else(|| upstream_peer.clone()),
+            upstream_peer,
+            count: 1,
+        }
+    }
+}
+
+// ==================== 汇总 ====================
+
+struct GroupAccumulator {
+    group: ErrorLogGroup,
+    buckets: BTreeMap<i64, usize>,
+}
+
+struct ErrorLogAnalyzer<'a> {
+    resolver: TargetResolver<'a>,
+    bucket_ms: i64,
+    start_time_ms: Option<i64>,
+    end_time_ms: Option<i64>,
+    groups: HashMap<String, GroupAccumulator>,
+    total_lines: usize,
+}
+
+impl<'a> ErrorLogAnalyzer<'a> {
+    fn new(config: &'a NginxConfig, request: &ErrorLogAnalysisRequest) -> Self {
+        let bucket_minutes = if request.bucket_minutes == 0 {
+            DEFAULT_BUCKET_MINUTES
+        } else {
+            request.bucket_minutes
+        };
+
+        Self {
+            resolver: TargetResolver::new(config),
+            bucket_ms: i64::from(bucket_minutes) * MILLIS_PER_MINUTE,
+            start_time_ms: request.start_time_ms,
+            end_time_ms: request.end_time_ms,
+            groups: HashMap::new(),
+            total_lines: 0,
+        }
+    }
+
+    fn add_line(&mut self, text: &str) {
+        let Some(line) = parse_error_log_line(text) else {
+            return;
+        };
+
+        if let Some(timestamp) = line.timestamp_ms {
+            if self.start_time_ms.is_some_and(|start| timestamp < start)
+                || self.end_time_ms.is_some_and(|end| timestamp > end)
+            {
+                return;
+            }
+        }
+
+        self.total_lines += 1;
+        let classification = classify_message(&line.message);
+        let target = self.resolver.resolve(&line);
+
+        let accumulator = self
+            .groups
+            .entry(classification.signature.clone())
+            .or_insert_with(|| GroupAccumulator {
+                group: ErrorLogGroup {
+                    signature: classification.signature,
+                    rule_id: classification.rule_id,
+                    title: classification.title,
+                    level: line.level.clone(),
+                    count: 0,
+                    first_seen_ms: None,
+                    last_seen_ms: None,
+                    timeline: Vec::new(),
+                    sample: text.to_string(),
+                    hint: classification.hint,
+                    suggestion: classification.suggestion,
+                    targets: Vec::new(),
+                },
+                buckets: BTreeMap::new(),
+            });
+
+        let group = &mut accumulator.group;
+        group.count += 1;
+        if let Some(timestamp) = line.timestamp_ms {
+            group.first_seen_ms = Some(group.first_seen_ms.map_or(timestamp, |first| first.min(timestamp)));
+            if group.last_seen_ms.is_none_or(|last| timestamp >= last) {
+                group.last_seen_ms = Some(timestamp);
+                group.sample = text.to_string();
+            }
+            *accumulator
+                .buckets
+                .entry(timestamp.div_euclid(self.bucket_ms) * self.bucket_ms)
+                .or_default() += 1;
+        }
+
+        match group.targets.iter_mut().find(|existing| {
+            existing.server_id == target.server_id
+                && existing.location_id == target.location_id
+                && existing.upstream_peer == target.upstream_peer
+        }) {
+            Some(existing) => existing.count += 1,
+            None => group.targets.push(target),
+        }
+    }
+
+    fn finish(self) -> (Vec<ErrorLogGroup>, usize) {
+        let mut groups: Vec<ErrorLogGroup> = self
+            .groups
+            .into_values()
+            .map(|accumulator| {
+                let mut group = accumulator.group;
+                group.timeline = accumulator
+                    .buckets
+                    .into_iter()
+                    .map(|(bucket_ms, count)| ErrorLogBucket { bucket_ms, count })
+                    .collect();
+                group.targets.sort_by_key(|target| std::cmp::Reverse(target.count));
+                group
+            })
+            .collect();
+
+        groups.sort_by(|left, right| {
+            right
+                .count
+                .cmp(&left.count)
+                .then_with(|| left.signature.cmp(&right.signature))
+        });
+        (groups, self.total_lines)
+    }
+}
+
+/// 按签名归类 error_log，关联到 server / location / upstream 并给出调整建议
+#[tauri::command]
+pub async fn analyze_nginx_error_log(request: ErrorLogAnalysisRequest) -> Result<ErrorLogAnalysisResult, String> {
+    // 需要完整读取 error_log 及其轮转文件，在阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || analyze_error_log_files(request))
+        .await
+        .map_err(|e| format!("错误日志分析任务异常: {}", e))?
+}
+
+fn analyze_error_log_files(request: ErrorLogAnalysisRequest) -> Result<ErrorLogAnalysisResult, String> {
+    let config = load_nginx_config(&request.config_path)?;
+
+    let mut paths = request.paths.clone();
+    if paths.is_empty() {
+        let prefix = resolve_log_prefix(&request.config_path, &request.nginx_path);
+        for source in discover_log_sources(&config, &prefix) {
+            if source.kind == NginxLogKind::Error && !paths.contains(&source.path) {
+                paths.push(source.path);
+            }
+        }
+    }
+
+    let mut analyzer = ErrorLogAnalyzer::new(&config, &request);
+    let mut scanned_files = Vec::new();
+    let mut warnings = Vec::new();
+
+    for path in &paths {
+        let files = list_log_files(Path::new(path), request.include_rotated);
+        if files.is_empty() {
+            warnings.push(format!("日志文件不存在: {}", path));
+            continue;
+        }
+
+        for file in files {
+            match for_each_log_line(&file, |_, text| analyzer.add_line(&text)) {
+                Ok(()) => scanned_files.push(file.to_string_lossy().to_string()),
+                Err(error) => warnings.push(format!("读取日志文件失败 {}: {}", file.display(), error)),
+            }
+        }
+    }
+
+    let (groups, total_lines) = analyzer.finish();
+    Ok(ErrorLogAnalysisResult {
+        groups,
+        scanned_files,
+        total_lines,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    const REFUSED_LINE: &str = r#"2024/01/02 13:55:36 [error] 1234#0: *5 connect() failed (111: Connection refused) while connecting to upstream, client: 10.0.0.1, server: api.local, request: "GET /api/users HTTP/1.1", upstream: "http://127.0.0.1:9000/api/users", host: "api.local""#;
+
+    #[test]
+    fn parse_error_log_line_should_split_message_and_context() {
+        let line = parse_error_log_line(REFUSED_LINE).expect("parse line");
+
+        assert_eq!(line.level, "error");
+        assert_eq!(
+            line.message,
+            "connect() failed (111: Connection refused) while connecting to upstream"
+        );
+        assert_eq!(line.context.get("server").map(String::as_str), Some("api.local"));
+        assert_eq!(
+            line.context.get("request").map(String::as_str),
+            Some("GET /api/users HTTP/1.1")
+        );
+        assert!(line.timestamp_ms.is_some());
+        assert!(parse_error_log_line("not an error log line").is_none());
+    }
+
+    #[test]
+    fn classify_message_should_match_rules_and_size_suggestion() {
+        let body = classify_message("client intended to send too large body: 15728640 bytes");
+        let timeout = classify_message(
+            "upstream timed out (110: Connection timed out) while reading response header from upstream",
+        );
+        let unknown = classify_message(r#"something odd "abc" at 42"#);
+
+        assert_eq!(body.rule_id.as_deref(), Some("client-body-too-large"));
+        assert_eq!(body.suggestion.expect("suggestion").value, "20m");
+        assert_eq!(
+            timeout.suggestion.map(|suggestion| suggestion.directive),
+            Some("proxy_read_timeout".to_string())
+        );
+        assert!(unknown.rule_id.is_none());
+        assert_eq!(unknown.signature, r#"something odd "*" at N"#);
+    }
+
+    #[test]
+    fn analyzer_should_group_lines_and_link_location_upstream() {
+        let content = r#"http {
+    server {
+        listen 80;
+        server_name api.local;
+
+        location /api/ {
+            proxy_pass http://backend;
+        }
+    }
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let mut analyzer = ErrorLogAnalyzer::new(&config, &ErrorLogAnalysisRequest::default());
+
+        analyzer.add_line(REFUSED_LINE);
+        analyzer.add_line(&REFUSED_LINE.replace("13:55:36", "14:10:00"));
+        analyzer.add_line("2024/01/02 15:00:00 [crit] 1#0: something odd 1");
+
+        let (groups, total_lines) = analyzer.finish();
+
+        assert_eq!(total_lines, 3);
+        assert_eq!(groups.len(), 2);
+        let refused = &groups[0];
+        assert_eq!(refused.rule_id.as_deref(), Some("upstream-connection-refused"));
+        assert_eq!(refused.count, 2);
+        assert_eq!(refused.timeline.len(), 2);
+        assert_eq!(refused.targets.len(), 1);
+        assert_eq!(refused.targets[0].count, 2);
+        assert_eq!(refused.targets[0].location_path.as_deref(), Some("/api/"));
+        assert_eq!(refused.targets[0].upstream.as_deref(), Some("backend"));
+        assert_eq!(refused.targets[0].upstream_peer.as_deref(), Some("127.0.0.1:9000"));
+    }
+
+    #[test]
+    fn resolver_should_follow_nginx_server_selection() {
+        let content = r#"http {
+    server {
+        listen 80;
+        server_name *.local;
+    }
+    server {
+        listen 80;
+        server_name api.local;
+    }
+    server {
+        listen 80 default_server;
+        server_name fallback.local;
+    }
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let mut resolver = TargetResolver::new(&config);
+        let resolve = |resolver: &mut TargetResolver, text: &str| {
+            resolver.resolve(&parse_error_log_line(text).expect("parse line")).server_name
+        };
+
+        assert_eq!(resolve(&mut resolver, REFUSED_LINE), vec!["api.local"]);
+        assert_eq!(
+            resolve(&mut resolver, r#"2024/01/02 13:55:36 [error] 1#0: *5 open() failed, client: 10.0.0.1, host: "unknown.test""#),
+            vec!["fallback.local"]
+        );
+    }
+}
@@ -0,0 +1,776 @@
+use crate::config::{parse_nginx_config, Directive, LocationBlock, NginxConfig, ServerBlock};
+use crate::config_text::{apply_edits, indent_of, TextEdit, INDENT_UNIT};
+use crate::config_watcher::{parse_config_servers, resolve_config_files};
+use crate::settings::{read_app_settings, LintRuleSetting};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl LintSeverity {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warning" => Some(Self::Warning),
+            "info" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+/// 自动修复：替换整个 server 块后的文本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFix {
+    pub description: String,
+    pub server_text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintFinding {
+    pub rule_id: String,
+    pub severity: LintSeverity,
+    pub message: String,
+    pub file_path: String,
+    pub server_id: String,
+    pub server_name: Vec<String>,
+    pub location_id: Option<String>,
+    /// 所在文件中的行号（从 1 开始）
+    pub line: usize,
+    pub fix: Option<LintFix>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintRuleInfo {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub default_severity: LintSeverity,
+    pub severity: LintSeverity,
+    pub enabled: bool,
+    pub has_autofix: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintReport {
+    pub config_path: String,
+    pub rules: Vec<LintRuleInfo>,
+    pub findings: Vec<LintFinding>,
+    pub parse_errors: Vec<String>,
+}
+
+struct RuleFinding {
+    location_index: Option<usize>,
+    /// server 文本内的行号（从 1 开始）
+    line: usize,
+    message: String,
+    fix: Option<(String, Vec<TextEdit>)>,
+}
+
+/// 单个 server 的检查上下文，server 由其原始文本单独解析，行号相对 server 文本
+struct LintContext<'a> {
+    server: &'a ServerBlock,
+    lines: Vec<&'a str>,
+    global_directives: &'a [Directive],
+}
+
+struct LintRule {
+    id: &'static str,
+    title: &'static str,
+    description: &'static str,
+    severity: LintSeverity,
+    has_autofix: bool,
+    check: fn(&LintContext) -> Vec<RuleFinding>,
+}
+
+const LINT_RULES: &[LintRule] = &[
+    LintRule {
+        id: "proxy-pass-trailing-slash",
+        title: "proxy_pass 与 location 的结尾斜杠不一致",
+        description: "proxy_pass 带 URI 时会替换 location 匹配的前缀，两者结尾斜杠不一致会拼出错误路径",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_proxy_pass_trailing_slash,
+    },
+    LintRule {
+        id: "add-header-inheritance",
+        title: "location 中的 add_header 覆盖了 server 级别的响应头",
+        description: "只要 location 声明了 add_header，就不再继承上层的全部 add_header",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_add_header_inheritance,
+    },
+    LintRule {
+        id: "alias-trailing-slash",
+        title: "alias 缺少结尾斜杠",
+        description: "location 以 / 结尾而 alias 不以 / 结尾时，文件路径会被错误拼接",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_alias_trailing_slash,
+    },
+    LintRule {
+        id: "if-in-location",
+        title: "location 中的 if 使用了不安全的指令",
+        description: "location 内的 if 中只有 return、rewrite、set、break 是安全的，其他指令可能产生意外行为",
+        severity: LintSeverity::Warning,
+        has_autofix: false,
+        check: check_if_in_location,
+    },
+    LintRule {
+        id: "missing-proxy-host-header",
+        title: "反向代理未设置 Host 请求头",
+        description: "未设置 proxy_set_header Host 时，上游收到的 Host 为 proxy_pass 中的地址",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_missing_proxy_host_header,
+    },
+    LintRule {
+        id: "root-in-location",
+        title: "root 写在 location 中而 server 未设置",
+        description: "未匹配这些 location 的请求会回退到默认根目录，通常应在 server 级别设置 root",
+        severity: LintSeverity::Info,
+        has_autofix: true,
+        check: check_root_in_location,
+    },
+    LintRule {
+        id: "server-tokens-on",
+        title: "server_tokens 已开启",
+        description: "响应头与错误页会暴露 nginx 版本号",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_server_tokens_on,
+    },
+    LintRule {
+        id: "autoindex-on",
+        title: "autoindex 已开启",
+        description: "目录列表会暴露站点文件结构",
+        severity: LintSeverity::Warning,
+        has_autofix: true,
+        check: check_autoindex_on,
+    },
+];
+
+// ==================== 文本辅助 ====================
+
+fn render_directive(indent: &str, name: &str, value: &str) -> String {
+    format!("{}{} {};", indent, name, value)
+}
+
+fn is_prefix_location(location: &LocationBlock) -> bool {
+    matches!(location.modifier.as_deref(), None | Some("^~"))
+}
+
+fn directives_named<'a>(directives: &'a [Directive], name: &'a str) -> impl Iterator<Item = &'a Directive> + 'a {
+    directives.iter().filter(move |directive| directive.name == name)
+}
+
+/// proxy_pass 中 URI 部分，没有 URI 或包含变量时返回 None
+fn proxy_pass_uri(value: &str) -> Option<&str> {
+    if value.contains('$') {
+        return None;
+    }
+    let (_, rest) = value.split_once("://")?;
+    if rest.starts_with("unix:") {
+        return None;
+    }
+    rest.find('/').map(|index| &rest[index..])
+}
+
+fn header_name(value: &str) -> String {
+    value.split_whitespace().next().unwrap_or_default().to_ascii_lowercase()
+}
+
+// ==================== 规则 ====================
+
+fn check_proxy_pass_trailing_slash(context: &LintContext) -> Vec<RuleFinding> {
+    let mut findings = Vec::new();
+
+    for (index, location) in context.server.locations.iter().enumerate() {
+        if !is_prefix_location(location) {
+            continue;
+        }
+        for directive in directives_named(&location.directives, "proxy_pass") {
+            let Some(uri) = proxy_pass_uri(&directive.value) else {
+                continue;
+            };
+            let path_slash = location.path.ends_with('/');
+            let uri_slash = uri.ends_with('/');
+            if path_slash == uri_slash {
+                continue;
+            }
+
+            let sample = if path_slash {
+                format!("{}users", location.path)
+            } else {
+                format!("{}/users", location.path)
+            };
+            let forwarded = format!("{}{}", uri, &sample[location.path.len()..]);
+            let message = format!(
+                "location {} 与 proxy_pass {} 的结尾斜杠不一致，请求 {} 将被转发为 {}",
+                location.path, directive.value, sample, forwarded
+            );
+
+            let line_index = directive.line - 1;
+            let indent = indent_of(context.lines[line_index]);
+            let fix = if path_slash {
+                (
+                    format!("将 proxy_pass 改为 {}/", directive.value),
+                    vec![TextEdit::Replace(
+                        line_index,
+                        render_directive(indent, "proxy_pass", &format!("{}/", directive.value)),
+                    )],
+                )
+            } else if uri == "/" {
+                // 去掉唯一的 / 会改变为透传完整 URI，改为给 location 补齐斜杠
+                let location_index = location.start_line - 1;
+                let location_line = context.lines[location_index];
+                (
+                    format!("将 location 改为 {}/", location.path),
+                    vec![TextEdit::Replace(
+                        location_index,
+                        location_line.replacen(
+                            &format!(" {} ", location.path),
+                            &format!(" {}/ ", location.path),
+                            1,
+                        ),
+                    )],
+                )
+            } else {
+                let value = directive.value.trim_end_matches('/');
+                (
+                    format!("将 proxy_pass 改为 {}", value),
+                    vec![TextEdit::Replace(line_index, render_directive(indent, "proxy_pass", value))],
+                )
+            };
+
+            findings.push(RuleFinding {
+                location_index: Some(index),
+                line: directive.line,
+                message,
+                fix: Some(fix),
+            });
+        }
+    }
+
+    findings
+}
+
+fn check_add_header_inheritance(context: &LintContext) -> Vec<RuleFinding> {
+    let server_headers: Vec<&Directive> = directives_named(&context.server.directives, "add_header").collect();
+    if server_headers.is_empty() {
+        return Vec::new();
+    }
+
+    let mut findings = Vec::new();
+    for (index, location) in context.server.locations.iter().enumerate() {
+        let location_headers: Vec<&Directive> = directives_named(&location.directives, "add_header").collect();
+        let Some(last) = location_headers.last() else {
+            continue;
+        };
+
+        let defined: Vec<String> = location_headers.iter().map(|directive| header_name(&directive.value)).collect();
+        let missing: Vec<&&Directive> = server_headers
+            .iter()
+            .filter(|directive| !defined.contains(&header_name(&directive.value)))
+            .collect();
+        if missing.is_empty() {
+            continue;
+        }
+
+        let names: Vec<String> = missing
+            .iter()
+            .map(|directive| directive.value.split_whitespace().next().unwrap_or_default().to_string())
+            .collect();
+        let indent = indent_of(context.lines[last.line - 1]);
+        let edits = missing
+            .iter()
+            .rev()
+            .map(|directive| TextEdit::InsertAfter(last.line - 1, render_directive(indent, "add_header", &directive.value)))
+            .collect();
+
+        findings.push(RuleFinding {
+            location_index: Some(index),
+            line: location_headers[0].line,
+            message: format!(
+                "location {} 声明了 add_header，server 级别的 {} 将不再生效",
+                location.path,
+                names.join("、")
+            ),
+            fix: Some((format!("在 location 中补充 {}", names.join("、")), edits)),
+        });
+    }
+
+    findings
+}
+
+fn check_alias_trailing_slash(context: &LintContext) -> Vec<RuleFinding> {
+    let mut findings = Vec::new();
+
+    for (index, location) in context.server.locations.iter().enumerate() {
+        if !is_prefix_location(location) || !location.path.ends_with('/') {
+            continue;
+        }
+        for directive in directives_named(&location.directives, "alias") {
+            if directive.value.ends_with('/') || directive.value.contains('$') {
+                continue;
+            }
+
+            let line_index = directive.line - 1;
+            let value = format!("{}/", directive.value);
+            findings.push(RuleFinding {
+                location_index: Some(index),
+                line: directive.line,
+                message: format!(
+                    "location {} 以 / 结尾而 alias {} 没有，{}a.html 将映射为 {}a.html",
+                    location.path, directive.value, location.path, directive.value
+                ),
+                fix: Some((
+                    format!("将 alias 改为 {}", value),
+                    vec![TextEdit::Replace(
+                        line_index,
+                        render_directive(indent_of(context.lines[line_index]), "alias", &value),
+                    )],
+                )),
+            });
+        }
+    }
+
+    findings
+}
+
+const SAFE_IF_DIRECTIVES: &[&str] = &["return", "rewrite", "set", "break"];
+
+fn check_if_in_location(context: &LintContext) -> Vec<RuleFinding> {
+    let mut findings = Vec::new();
+
+    for (index, location) in context.server.locations.iter().enumerate() {
+        let mut line_index = location.start_line;
+        while line_index < location.end_line - 1 {
+            let line = context.lines[line_index].trim();
+            let is_if = line.ends_with('{') && (line.starts_with("if ") || line.starts_with("if("));
+            if !is_if {
+                line_index += 1;
+                continue;
+            }
+
+            let if_line = line_index + 1;
+            let mut depth = 1;
+            let mut unsafe_directives = Vec::new();
+            line_index += 1;
+            while line_index < location.end_line - 1 && depth > 0 {
+                let inner = context.lines[line_index].split('#').next().unwrap_or("").trim();
+                depth += inner.matches('{').count();
+                depth -= inner.matches('}').count().min(depth);
+                let name = inner.split_whitespace().next().unwrap_or("").trim_end_matches(';');
+                if depth > 0 && !name.is_empty() && !inner.ends_with('{') && !SAFE_IF_DIRECTIVES.contains(&name) {
+                    unsafe_directives.push(name.to_string());
+                }
+                line_index += 1;
+            }
+
+            if !unsafe_directives.is_empty() {
+                unsafe_directives.dedup();
+                findings.push(RuleFinding {
+                    location_index: Some(index),
+                    line: if_line,
+                    message: format!(
+                        "location {} 的 if 中使用了 {}，建议改用 map、try_files 或独立的 location",
+                        location.path,
+                        unsafe_directives.join("、")
+                    ),
+                    fix: None,
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+fn sets_host_header(directives: &[Directive]) -> bool {
+    directives_named(directives, "proxy_set_header").any(|directive| header_name(&directive.value) == "host")
+}
+
+fn check_missing_proxy_host_header(context: &LintContext) -> Vec<RuleFinding> {
+    if sets_host_header(&context.server.directives) || sets_host_header(context.global_directives) {
+        return Vec::new();
+    }
+
+    let mut findings = Vec::new();
+    for (index, location) in context.server.locations.iter().enumerate() {
+        if sets_host_header(&location.directives) {
+            continue;
+        }
+        let Some(proxy_pass) = directives_named(&location.directives, "proxy_pass").next() else {
+            continue;
+        };
+
+        let line_index = proxy_pass.line - 1;
+        findings.push(RuleFinding {
+            location_index: Some(index),
+            line: proxy_pass.line,
+            message: format!(
+                "location {} 反向代理到 {}，但未设置 proxy_set_header Host，上游将收到代理地址作为 Host",
+                location.path, proxy_pass.value
+            ),
+            fix: Some((
+                "添加 proxy_set_header Host $host".to_string(),
+                vec![TextEdit::InsertAfter(
+                    line_index,
+                    render_directive(indent_of(context.lines[line_index]), "proxy_set_header", "Host $host"),
+                )],
+            )),
+        });
+    }
+
+    findings
+}
+
+fn check_root_in_location(context: &LintContext) -> Vec<RuleFinding> {
+    if directives_named(&context.server.directives, "root").next().is_some() {
+        return Vec::new();
+    }
+
+    let roots: Vec<(usize, &Directive)> = context
+        .server
+        .locations
+        .iter()
+        .enumerate()
+        .flat_map(|(index, location)| directives_named(&location.directives, "root").map(move |directive| (index, directive)))
+        .collect();
+    if roots.is_empty() {
+        return Vec::new();
+    }
+
+    // 出现次数最多的 root 提升到 server 级别
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, directive) in &roots {
+        *counts.entry(directive.value.as_str()).or_default() += 1;
+    }
+    let common = roots
+        .iter()
+        .map(|(_, directive)| directive.value.as_str())
+        .max_by_key(|value| (counts[value], std::cmp::Reverse(roots.iter().position(|(_, directive)| directive.value == *value))))
+        .unwrap_or_default();
+
+    let anchor = context
+        .server
+        .directives
+        .iter()
+        .filter(|directive| matches!(directive.name.as_str(), "listen" | "server_name"))
+        .map(|directive| directive.line - 1)
+        .max();
+    let indent = anchor
+        .map(|line| indent_of(context.lines[line]).to_string())
+        .unwrap_or_else(|| format!("{}{}", indent_of(context.lines[0]), INDENT_UNIT));
+    let mut edits = vec![TextEdit::InsertAfter(anchor.unwrap_or(0), render_directive(&indent, "root", common))];
+    edits.extend(
+        roots
+            .iter()
+            .filter(|(_, directive)| directive.value == common)
+            .map(|(_, directive)| TextEdit::Delete(directive.line - 1)),
+    );
+    let fix = (format!("将 root {} 移到 server 级别", common), edits);
+
+    roots
+        .iter()
+        .map(|(index, directive)| RuleFinding {
+            location_index: Some(*index),
+            line: directive.line,
+            message: format!(
+                "location {} 中设置了 root {}，但 server 级别未设置 root",
+                context.server.locations[*index].path, directive.value
+            ),
+            fix: Some((fix.0.clone(), fix.1.iter().map(clone_edit).collect())),
+        })
+        .collect()
+}
+
+fn clone_edit(edit: &TextEdit) -> TextEdit {
+    match edit {
+        TextEdit::Replace(line, text) => TextEdit::Replace(*line, text.clone()),
+        TextEdit::InsertAfter(line, text) => TextEdit::InsertAfter(*line, text.clone()),
+        TextEdit::Delete(line) => TextEdit::Delete(*line),
+    }
+}
+
+/// 查找 server 及其 location 中取值为 on 的开关指令，修复为 off
+fn check_switch_on(context: &LintContext, name: &str, message: &str) -> Vec<RuleFinding> {
+    let server_level = context.server.directives.iter().map(|directive| (None, directive));
+    let location_level = context
+        .server
+        .locations
+        .iter()
+        .enumerate()
+        .flat_map(|(index, location)| location.directives.iter().map(move |directive| (Some(index), directive)));
+
+    server_level
+        .chain(location_level)
+        .filter(|(_, directive)| directive.name == name && directive.value.eq_ignore_ascii_case("on"))
+        .map(|(location_index, directive)| {
+            let line_index = directive.line - 1;
+            RuleFinding {
+                location_index,
+                line: directive.line,
+                message: message.to_string(),
+                fix: Some((
+                    format!("改为 {} off", name),
+                    vec![TextEdit::Replace(
+                        line_index,
+                        render_directive(indent_of(context.lines[line_index]), name, "off"),
+                    )],
+                )),
+            }
+        })
+        .collect()
+}
+
+fn check_server_tokens_on(context: &LintContext) -> Vec<RuleFinding> {
+    check_switch_on(context, "server_tokens", "server_tokens on 会在响应头和错误页中暴露 nginx 版本号")
+}
+
+fn check_autoindex_on(context: &LintContext) -> Vec<RuleFinding> {
+    check_switch_on(context, "autoindex", "autoindex on 会列出目录内容，暴露站点文件结构")
+}
+
+// ==================== 引擎 ====================
+
+fn resolve_rule_info(rule: &LintRule, settings: &BTreeMap<String, LintRuleSetting>) -> LintRuleInfo {
+    let setting = settings.get(rule.id);
+    LintRuleInfo {
+        id: rule.id.to_string(),
+        title: rule.title.to_string(),
+        description: rule.description.to_string(),
+        default_severity: rule.severity,
+        severity: setting
+            .and_then(|setting| setting.severity.as_deref())
+            .and_then(LintSeverity::parse)
+            .unwrap_or(rule.severity),
+        enabled: !setting.is_some_and(|setting| setting.disabled),
+        has_autofix: rule.has_autofix,
+    }
+}
+
+/// 对单个 server 执行已启用的规则
+pub(crate) fn lint_server(
+    file_path: &str,
+    original: &ServerBlock,
+    global_directives: &[Directive],
+    rules: &[LintRuleInfo],
+) -> Vec<LintFinding> {
+    let Ok(parsed) = parse_nginx_config(&original.raw_content, file_path) else {
+        return Vec::new();
+    };
+    let Some(server) = parsed.servers.first() else {
+        return Vec::new();
+    };
+    let context = LintContext {
+        server,
+        lines: original.raw_content.lines().collect(),
+        global_directives,
+    };
+
+    let mut findings = Vec::new();
+    for rule in LINT_RULES {
+        let Some(info) = rules.iter().find(|info| info.id == rule.id && info.enabled) else {
+            continue;
+        };
+
+        for finding in (rule.check)(&context) {
+            findings.push(LintFinding {
+                rule_id: rule.id.to_string(),
+                severity: info.severity,
+                message: finding.message,
+                file_path: file_path.to_string(),
+                server_id: original.id.clone(),
+                server_name: original.server_name.clone(),
+                location_id: finding
+                    .location_index
+                    .and_then(|index| original.locations.get(index))
+                    .map(|location| location.id.clone()),
+                line: original.start_line + finding.line - 1,
+                fix: finding.fix.map(|(description, edits)| LintFix {
+                    description,
+                    server_text: apply_edits(&context.lines, &edits),
+                }),
+            });
+        }
+    }
+
+    findings
+}
+
+fn current_rule_settings() -> BTreeMap<String, LintRuleSetting> {
+    read_app_settings()
+        .map(|settings| settings.lint_rules)
+        .unwrap_or_default()
+}
+
+/// 列出全部 lint 规则及当前设置
+#[tauri::command]
+pub async fn list_nginx_lint_rules() -> Result<Vec<LintRuleInfo>, String> {
+    let settings = current_rule_settings();
+    Ok(LINT_RULES.iter().map(|rule| resolve_rule_info(rule, &settings)).collect())
+}
+
+/// 对配置文件及 include 文件中已启用的 server 执行 lint 检查
+#[tauri::command]
+pub async fn lint_nginx_config(config_path: String) -> Result<LintReport, String> {
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+
+    let settings = current_rule_settings();
+    let rules: Vec<LintRuleInfo> = LINT_RULES.iter().map(|rule| resolve_rule_info(rule, &settings)).collect();
+    let global_directives = crate::config::load_nginx_config(&config_path)
+        .map(|config: NginxConfig| config.global_directives)
+        .unwrap_or_default();
+    let (servers, parse_errors) = parse_config_servers(&resolve_config_files(&config_path));
+
+    let mut findings: Vec<LintFinding> = servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .flat_map(|(file_path, server)| lint_server(file_path, server, &global_directives, &rules))
+        .collect();
+    findings.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.file_path.cmp(&b.file_path)).then(a.line.cmp(&b.line)));
+
+    Ok(LintReport {
+        config_path,
+        rules,
+        findings,
+        parse_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(content: &str) -> Vec<LintFinding> {
+        let config = parse_nginx_config(content, "nginx.conf").expect("parse config");
+        let rules: Vec<LintRuleInfo> = LINT_RULES
+            .iter()
+            .map(|rule| resolve_rule_info(rule, &BTreeMap::new()))
+            .collect();
+        config
+            .servers
+            .iter()
+            .flat_map(|server| lint_server("nginx.conf", server, &config.global_directives, &rules))
+            .collect()
+    }
+
+    fn finding<'a>(findings: &'a [LintFinding], rule_id: &str) -> &'a LintFinding {
+        findings
+            .iter()
+            .find(|finding| finding.rule_id == rule_id)
+            .unwrap_or_else(|| panic!("missing finding {}", rule_id))
+    }
+
+    #[test]
+    fn lint_should_flag_proxy_and_alias_mistakes_with_fixes() {
+        let findings = lint(
+            r#"http {
+    server {
+        listen 80;
+        server_name example.com;
+        add_header X-Frame-Options DENY;
+        location /api/ {
+            proxy_pass http://backend/v1;
+            add_header X-Api yes;
+        }
+        location /static/ {
+            alias /srv/static;
+        }
+    }
+}"#,
+        );
+
+        let proxy = finding(&findings, "proxy-pass-trailing-slash");
+        assert_eq!(proxy.line, 7);
+        assert!(proxy.message.contains("/v1users"));
+        let fixed = &proxy.fix.as_ref().unwrap().server_text;
+        assert!(fixed.contains("            proxy_pass http://backend/v1/;"));
+        assert!(fixed.starts_with("    server {"));
+
+        let header = finding(&findings, "add-header-inheritance");
+        let fixed = &header.fix.as_ref().unwrap().server_text;
+        assert!(fixed.contains("            add_header X-Api yes;\n            add_header X-Frame-Options DENY;"));
+
+        let alias = finding(&findings, "alias-trailing-slash");
+        assert!(alias.fix.as_ref().unwrap().server_text.contains("alias /srv/static/;"));
+
+        let host = finding(&findings, "missing-proxy-host-header");
+        assert!(host
+            .fix
+            .as_ref()
+            .unwrap()
+            .server_text
+            .contains("proxy_pass http://backend/v1;\n            proxy_set_header Host $host;"));
+    }
+
+    #[test]
+    fn lint_should_flag_if_root_and_switches() {
+        let findings = lint(
+            r#"server {
+    listen 80;
+    server_name files.local;
+    server_tokens on;
+    location / {
+        root /srv/www;
+        autoindex on;
+        if ($request_method = POST) {
+            proxy_pass http://upload;
+        }
+        if ($http_x_block) {
+            return 403;
+        }
+    }
+    location /img/ {
+        root /srv/www;
+    }
+}"#,
+        );
+
+        let if_finding = finding(&findings, "if-in-location");
+        assert!(if_finding.message.contains("proxy_pass"));
+        assert_eq!(findings.iter().filter(|finding| finding.rule_id == "if-in-location").count(), 1);
+
+        let root = finding(&findings, "root-in-location");
+        assert_eq!(root.severity, LintSeverity::Info);
+        let fixed = &root.fix.as_ref().unwrap().server_text;
+        assert!(fixed.contains("    server_name files.local;\n    root /srv/www;"));
+        assert_eq!(fixed.matches("root /srv/www;").count(), 1);
+
+        assert!(finding(&findings, "server-tokens-on").fix.as_ref().unwrap().server_text.contains("server_tokens off;"));
+        assert!(finding(&findings, "autoindex-on").fix.as_ref().unwrap().server_text.contains("autoindex off;"));
+    }
+
+    #[test]
+    fn rule_settings_should_disable_and_override_severity() {
+        let settings: BTreeMap<String, LintRuleSetting> = [
+            ("autoindex-on".to_string(), LintRuleSetting { disabled: true, severity: None }),
+            (
+                "server-tokens-on".to_string(),
+                LintRuleSetting { disabled: false, severity: Some("error".to_string()) },
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        let rules: Vec<LintRuleInfo> = LINT_RULES.iter().map(|rule| resolve_rule_info(rule, &settings)).collect();
+        let autoindex = rules.iter().find(|rule| rule.id == "autoindex-on").unwrap();
+        let tokens = rules.iter().find(|rule| rule.id == "server-tokens-on").unwrap();
+        assert!(!autoindex.enabled);
+        assert_eq!(tokens.severity, LintSeverity::Error);
+        assert_eq!(tokens.default_severity, LintSeverity::Warning);
+    }
+}
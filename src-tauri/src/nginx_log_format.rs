@@ -0,0 +1,688 @@
+use crate::config_watcher::resolve_config_files;
+use crate::nginx_logs::{for_each_log_line, list_log_files};
+use chrono::DateTime;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// nginx 内置的 combined 格式，未声明 log_format 时 access_log 默认使用
+pub const COMBINED_LOG_FORMAT_NAME: &str = "combined";
+const COMBINED_LOG_FORMAT: &str = r#"$remote_addr - $remote_user [$time_local] "$request" $status $body_bytes_sent "$http_referer" "$http_user_agent""#;
+const DEFAULT_ESCAPE: &str = "default";
+const DEFAULT_PARSE_LIMIT: usize = 1000;
+
+/// 配置中的 log_format 定义
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFormatDefinition {
+    pub name: String,
+    /// default / json / none
+    pub escape: String,
+    pub template: String,
+    pub variables: Vec<String>,
+    /// 定义所在文件，内置格式为空
+    pub file_path: String,
+    /// 内置格式为 0
+    pub line: usize,
+    pub builtin: bool,
+}
+
+/// 按 log_format 解析出的访问日志记录
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessLogRecord {
+    pub remote_addr: Option<String>,
+    pub remote_user: Option<String>,
+    pub timestamp_ms: Option<i64>,
+    pub host: Option<String>,
+    pub method: Option<String>,
+    pub uri: Option<String>,
+    pub protocol: Option<String>,
+    pub status: Option<u16>,
+    pub body_bytes_sent: Option<u64>,
+    pub request_time: Option<f64>,
+    /// 多次尝试上游时为各次耗时之和
+    pub upstream_response_time: Option<f64>,
+    pub upstream_addr: Option<String>,
+    pub upstream_status: Option<String>,
+    pub http_referer: Option<String>,
+    pub http_user_agent: Option<String>,
+    /// 全部变量的原始值（已去掉 `-` 占位）
+    pub fields: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AccessLogParseRequest {
+    pub config_path: String,
+    pub path: String,
+    pub format: String,
+    pub include_rotated: bool,
+    pub limit: usize,
+}
+
+impl Default for AccessLogParseRequest {
+    fn default() -> Self {
+        Self {
+            config_path: String::new(),
+            path: String::new(),
+            format: COMBINED_LOG_FORMAT_NAME.to_string(),
+            include_rotated: false,
+            limit: DEFAULT_PARSE_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessLogParseResult {
+    pub format: String,
+    pub records: Vec<AccessLogRecord>,
+    pub parsed_lines: usize,
+    /// 与格式不匹配的行数
+    pub unmatched_lines: usize,
+    pub truncated: bool,
+}
+
+// ==================== log_format 提取 ====================
+
+#[derive(Debug)]
+struct Statement {
+    tokens: Vec<String>,
+    line: usize,
+}
+
+/// 把配置拆分为以 `;` 结尾的语句，处理引号、转义与注释；
+/// log_format 常跨多行书写，逐行解析的 Directive 无法完整保留
+fn split_statements(content: &str) -> Vec<Statement> {
+    let mut statements = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut quote: Option<char> = None;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if let Some(quote_char) = quote {
+            match c {
+                '\\' => {
+                    if let Some(next) = chars.next() {
+                        if next != quote_char {
+                            current.push('\\');
+                        }
+                        if next == '\n' {
+                            line += 1;
+                        }
+                        current.push(next);
+                    }
+                }
+                _ if c == quote_char => quote = None,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    current.push(c);
+                }
+            }
+            continue;
+        }
+
+        match c {
+            '"' | '\'' => {
+                if !in_token && tokens.is_empty() {
+                    start_line = line;
+                }
+                in_token = true;
+                quote = Some(c);
+            }
+            '#' if !in_token => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        line += 1;
+                        break;
+                    }
+                }
+            }
+            ';' | '{' | '}' => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+                if c == ';' && !tokens.is_empty() {
+                    statements.push(Statement {
+                        tokens: std::mem::take(&mut tokens),
+                        line: start_line,
+                    });
+                }
+                tokens.clear();
+            }
+            _ if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+                if c == '\n' {
+                    line += 1;
+                }
+            }
+            _ => {
+                if !in_token && tokens.is_empty() {
+                    start_line = line;
+                }
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+
+    statements
+}
+
+/// 提取模板中引用的变量名，支持 `$name` 与 `${name}`
+fn template_variables(template: &str) -> Vec<String> {
+    compile_segments(template)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            FormatSegment::Variable(name) => Some(name),
+            FormatSegment::Literal(_) => None,
+        })
+        .collect()
+}
+
+fn builtin_combined_format() -> LogFormatDefinition {
+    LogFormatDefinition {
+        name: COMBINED_LOG_FORMAT_NAME.to_string(),
+        escape: DEFAULT_ESCAPE.to_string(),
+        template: COMBINED_LOG_FORMAT.to_string(),
+        variables: template_variables(COMBINED_LOG_FORMAT),
+        file_path: String::new(),
+        line: 0,
+        builtin: true,
+    }
+}
+
+/// 从主配置及其 include 文件中读取全部 log_format（含内置的 combined），conf.d 中的定义同样生效
+pub(crate) fn extract_config_log_formats(config_path: &str) -> Vec<LogFormatDefinition> {
+    let mut formats = vec![builtin_combined_format()];
+    for path in resolve_config_files(config_path) {
+        if let Ok(content) = fs::read_to_string(&path) {
+            collect_log_formats(&mut formats, &content, &path.to_string_lossy());
+        }
+    }
+    formats
+}
+
+fn collect_log_formats(formats: &mut Vec<LogFormatDefinition>, content: &str, file_path: &str) {
+    for statement in split_statements(content) {
+        let mut tokens = statement.tokens.into_iter();
+        if tokens.next().as_deref() != Some("log_format") {
+            continue;
+        }
+        let Some(name) = tokens.next() else {
+            continue;
+        };
+
+        let mut escape = DEFAULT_ESCAPE.to_string();
+        let mut template = String::new();
+        for (index, token) in tokens.enumerate() {
+            match token.strip_prefix("escape=") {
+                Some(value) if index == 0 => escape = value.to_string(),
+                _ => template.push_str(&token),
+            }
+        }
+
+        let definition = LogFormatDefinition {
+            variables: template_variables(&template),
+            name,
+            escape,
+            template,
+            file_path: file_path.to_string(),
+            line: statement.line,
+            builtin: false,
+        };
+
+        // 同名定义以后出现的为准（nginx 会报重复，这里只做容错）
+        formats.retain(|format| format.name != definition.name);
+        formats.push(definition);
+    }
+}
+
+// ==================== 格式编译 ====================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FormatSegment {
+    Literal(String),
+    Variable(String),
+}
+
+fn is_variable_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn compile_segments(template: &str) -> Vec<FormatSegment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let chars: Vec<char> = template.chars().collect();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if chars[index] != '$' {
+            literal.push(chars[index]);
+            index += 1;
+            continue;
+        }
+
+        let braced = chars.get(index + 1) == Some(&'{');
+        let name_start = if braced { index + 2 } else { index + 1 };
+        let mut name_end = name_start;
+        while name_end < chars.len() && is_variable_char(chars[name_end]) {
+            name_end += 1;
+        }
+
+        let closed = !braced || chars.get(name_end) == Some(&'}');
+        if name_end == name_start || !closed {
+            literal.push('$');
+            index += 1;
+            continue;
+        }
+
+        if !literal.is_empty() {
+            segments.push(FormatSegment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(FormatSegment::Variable(chars[name_start..name_end].iter().collect()));
+        index = if braced { name_end + 1 } else { name_end };
+    }
+
+    if !literal.is_empty() {
+        segments.push(FormatSegment::Literal(literal));
+    }
+
+    segments
+}
+
+/// 由 log_format 生成的行解析器
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledLogFormat {
+    pub name: String,
+    escape: String,
+    regex: Regex,
+    variables: Vec<String>,
+}
+
+impl CompiledLogFormat {
+    pub(crate) fn compile(definition: &LogFormatDefinition) -> Result<Self, String> {
+        // escape=json 时值内的引号与反斜杠都会被转义，不会提前截断；
+        // 其他模式下由后续字面量界定变量边界
+        let value_pattern = if definition.escape == "json" {
+            r#"((?:[^"\\]|\\.)*?)"#
+        } else {
+            "(.*?)"
+        };
+
+        let mut pattern = String::from("^");
+        let mut variables = Vec::new();
+        for segment in compile_segments(&definition.template) {
+            match segment {
+                FormatSegment::Literal(text) => pattern.push_str(&regex::escape(&text)),
+                FormatSegment::Variable(name) => {
+                    pattern.push_str(value_pattern);
+                    variables.push(name);
+                }
+            }
+        }
+        pattern.push('$');
+
+        let regex = Regex::new(&pattern)
+            .map_err(|e| format!("编译 log_format {} 失败: {}", definition.name, e))?;
+
+        Ok(Self {
+            name: definition.name.clone(),
+            escape: definition.escape.clone(),
+            regex,
+            variables,
+        })
+    }
+
+    pub(crate) fn parse_line(&self, line: &str) -> Option<AccessLogRecord> {
+        let captures = self.regex.captures(line)?;
+        let mut fields = BTreeMap::new();
+
+        for (index, name) in self.variables.iter().enumerate() {
+            let Some(raw) = captures.get(index + 1) else {
+                continue;
+            };
+            let value = unescape_value(raw.as_str(), &self.escape);
+            if value.is_empty() || value == "-" {
+                continue;
+            }
+            fields.entry(name.clone()).or_insert(value);
+        }
+
+        Some(build_record(fields))
+    }
+}
+
+/// 还原 nginx 写日志时做的转义：json 模式为 JSON 字符串转义，default 模式为 `\xHH`
+fn unescape_value(value: &str, escape: &str) -> String {
+    if escape == "none" || !value.contains('\\') {
+        return value.to_string();
+    }
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        let Some(next) = chars.next() else {
+            bytes.push(b'\\');
+            break;
+        };
+
+        let decoded: Option<Vec<u8>> = match (escape, next) {
+            (_, 'x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                u8::from_str_radix(&hex, 16).ok().map(|byte| vec![byte])
+            }
+            ("json", 'u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(|ch| ch.to_string().into_bytes())
+            }
+            ("json", 'n') => Some(vec![b'\n']),
+            ("json", 'r') => Some(vec![b'\r']),
+            ("json", 't') => Some(vec![b'\t']),
+            ("json", 'b') => Some(vec![0x08]),
+            ("json", 'f') => Some(vec![0x0c]),
+            ("json", '"' | '\\' | '/') => Some(vec![next as u8]),
+            _ => None,
+        };
+
+        match decoded {
+            Some(decoded) => bytes.extend_from_slice(&decoded),
+            None => {
+                bytes.push(b'\\');
+                let mut buffer = [0u8; 4];
+                bytes.extend_from_slice(next.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// 多个上游时 nginx 以 `, ` 或 ` : ` 分隔各次耗时，取数值部分求和
+fn parse_upstream_time(value: &str) -> Option<f64> {
+    let times: Vec<f64> = value
+        .split([',', ':'])
+        .filter_map(|part| part.trim().parse::<f64>().ok())
+        .collect();
+
+    (!times.is_empty()).then(|| times.iter().sum())
+}
+
+fn parse_record_timestamp(fields: &BTreeMap<String, String>) -> Option<i64> {
+    if let Some(value) = fields.get("time_local") {
+        if let Ok(time) = DateTime::parse_from_str(value, "%d/%b/%Y:%H:%M:%S %z") {
+            return Some(time.timestamp_millis());
+        }
+    }
+    if let Some(value) = fields.get("time_iso8601") {
+        if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+            return Some(time.timestamp_millis());
+        }
+    }
+
+    fields
+        .get("msec")
+        .and_then(|value| value.parse::<f64>().ok())
+        .map(|seconds| (seconds * 1000.0).round() as i64)
+}
+
+fn build_record(fields: BTreeMap<String, String>) -> AccessLogRecord {
+    let field = |names: &[&str]| names.iter().find_map(|name| fields.get(*name).cloned());
+
+    // $request 形如 `GET /path?query HTTP/1.1`
+    let request = fields.get("request").map(|request| {
+        let mut parts = request.splitn(3, ' ');
+        (
+            parts.next().map(str::to_string),
+            parts.next().map(str::to_string),
+            parts.next().map(str::to_string),
+        )
+    });
+    let (request_method, request_uri, request_protocol) = request.unwrap_or((None, None, None));
+
+    AccessLogRecord {
+        // X-Forwarded-For 由客户端提供，不能当作来源地址
+        remote_addr: field(&["remote_addr", "realip_remote_addr"]),
+        remote_user: field(&["remote_user"]),
+        timestamp_ms: parse_record_timestamp(&fields),
+        host: field(&["host", "http_host", "server_name"]),
+        method: field(&["request_method"]).or(request_method),
+        uri: field(&["request_uri", "uri"]).or(request_uri),
+        protocol: field(&["server_protocol"]).or(request_protocol),
+        status: field(&["status"]).and_then(|value| value.parse().ok()),
+        body_bytes_sent: field(&["body_bytes_sent", "bytes_sent"]).and_then(|value| value.parse().ok()),
+        request_time: field(&["request_time"]).and_then(|value| value.parse().ok()),
+        upstream_response_time: field(&["upstream_response_time"])
+            .and_then(|value| parse_upstream_time(&value)),
+        upstream_addr: field(&["upstream_addr"]),
+        upstream_status: field(&["upstream_status"]),
+        http_referer: field(&["http_referer"]),
+        http_user_agent: field(&["http_user_agent"]),
+        fields,
+    }
+}
+
+/// 按名称管理编译好的 log_format
+pub(crate) struct AccessLogParser {
+    formats: HashMap<String, CompiledLogFormat>,
+}
+
+impl AccessLogParser {
+    pub(crate) fn from_definitions(definitions: &[LogFormatDefinition]) -> Result<Self, String> {
+        let formats = definitions
+            .iter()
+            .map(|definition| Ok((definition.name.clone(), CompiledLogFormat::compile(definition)?)))
+            .collect::<Result<HashMap<_, _>, String>>()?;
+
+        Ok(Self { formats })
+    }
+
+    pub(crate) fn from_config_path(config_path: &str) -> Result<Self, String> {
+        Self::from_definitions(&extract_config_log_formats(config_path))
+    }
+
+    pub(crate) fn format(&self, name: &str) -> Result<&CompiledLogFormat, String> {
+        self.formats
+            .get(name)
+            .ok_or_else(|| format!("未找到 log_format: {}", name))
+    }
+}
+
+// ==================== 命令 ====================
+
+/// 列出配置中的 log_format 定义
+#[tauri::command]
+pub async fn list_nginx_log_formats(config_path: String) -> Result<Vec<LogFormatDefinition>, String> {
+    if !Path::new(&config_path).is_file() {
+        return Err(format!("配置文件不存在: {}", config_path));
+    }
+    Ok(extract_config_log_formats(&config_path))
+}
+
+/// 按配置中的 log_format 将访问日志解析为结构化记录，保留最新的 limit 条
+#[tauri::command]
+pub async fn parse_nginx_access_log(request: AccessLogParseRequest) -> Result<AccessLogParseResult, String> {
+    // 大日志同步读取耗时较长，放到阻塞线程池，避免阻塞其他命令
+    tauri::async_runtime::spawn_blocking(move || parse_access_log_file(request))
+        .await
+        .map_err(|e| format!("访问日志解析任务异常: {}", e))?
+}
+
+fn parse_access_log_file(request: AccessLogParseRequest) -> Result<AccessLogParseResult, String> {
+    let parser = AccessLogParser::from_config_path(&request.config_path)?;
+    let format_name = if request.format.trim().is_empty() {
+        COMBINED_LOG_FORMAT_NAME
+    } else {
+        request.format.trim()
+    };
+    let format = parser.format(format_name)?;
+
+    let files = list_log_files(Path::new(&request.path), request.include_rotated);
+    if files.is_empty() {
+        return Err(format!("日志文件不存在: {}", request.path));
+    }
+
+    let limit = if request.limit == 0 { DEFAULT_PARSE_LIMIT } else { request.limit };
+    let mut records = VecDeque::new();
+    let mut parsed_lines = 0;
+    let mut unmatched_lines = 0;
+
+    for file in &files {
+        for_each_log_line(file, |_, text| {
+            if text.trim().is_empty() {
+                return;
+            }
+            match format.parse_line(&text) {
+                Some(record) => {
+                    parsed_lines += 1;
+                    if records.len() == limit {
+                        records.pop_front();
+                    }
+                    records.push_back(record);
+                }
+                None => unmatched_lines += 1,
+            }
+        })
+        .map_err(|e| format!("读取日志文件失败 {}: {}", file.display(), e))?;
+    }
+
+    Ok(AccessLogParseResult {
+        format: format.name.clone(),
+        truncated: parsed_lines > records.len(),
+        records: records.into_iter().collect(),
+        parsed_lines,
+        unmatched_lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{parse_nginx_config, NginxConfig};
+
+    fn extract_log_formats(config: &NginxConfig) -> Vec<LogFormatDefinition> {
+        let mut formats = vec![builtin_combined_format()];
+        collect_log_formats(&mut formats, &config.raw_content, &config.file_path);
+        formats
+    }
+
+    const SAMPLE_CONFIG: &str = r#"http {
+    log_format main '$remote_addr - $remote_user [$time_local] "$request" '
+                    '$status $body_bytes_sent "$http_referer" '
+                    '"$http_user_agent" rt=$request_time urt="$upstream_response_time" host=$host';
+
+    log_format json_log escape=json '{"addr":"$remote_addr","uri":"$uri",'
+        '"status":$status,"ua":"$http_user_agent","time":"$time_iso8601"}';
+
+    server {
+        listen 80;
+        access_log logs/access.log main;
+    }
+}"#;
+
+    fn sample_parser() -> AccessLogParser {
+        let config = parse_nginx_config(SAMPLE_CONFIG, "test.conf").expect("parse config");
+        AccessLogParser::from_definitions(&extract_log_formats(&config)).expect("compile formats")
+    }
+
+    #[test]
+    fn extract_log_formats_should_join_multiline_templates() {
+        let config = parse_nginx_config(SAMPLE_CONFIG, "test.conf").expect("parse config");
+
+        let formats = extract_log_formats(&config);
+
+        assert_eq!(formats.len(), 3);
+        assert!(formats[0].builtin);
+        assert_eq!(formats[1].name, "main");
+        assert_eq!(formats[1].line, 2);
+        assert!(formats[1].template.ends_with("host=$host"));
+        assert_eq!(formats[2].escape, "json");
+        assert!(formats[2].variables.contains(&"time_iso8601".to_string()));
+    }
+
+    #[test]
+    fn extract_config_log_formats_should_include_conf_d_definitions() {
+        let dir = std::env::temp_dir().join(format!("ncm_log_format_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        let main = dir.join("nginx.conf");
+        fs::write(&main, "http {\n    include conf.d/*.conf;\n}\n").unwrap();
+        fs::write(dir.join("conf.d").join("formats.conf"), "log_format edge '$remote_addr $status';\n").unwrap();
+
+        let formats = extract_config_log_formats(&main.to_string_lossy());
+        let _ = fs::remove_dir_all(&dir);
+
+        let edge = formats.iter().find(|format| format.name == "edge").expect("included log_format");
+        assert!(edge.file_path.ends_with("formats.conf"));
+        assert_eq!(edge.line, 1);
+        assert_eq!(edge.variables, vec!["remote_addr", "status"]);
+    }
+
+    #[test]
+    fn combined_format_should_parse_default_access_line() {
+        let parser = sample_parser();
+        let line = r#"10.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /index.html?a=1 HTTP/1.1" 200 612 "-" "curl/8.0""#;
+
+        let record = parser.format("combined").expect("format").parse_line(line).expect("parse line");
+
+        assert_eq!(record.remote_addr.as_deref(), Some("10.0.0.1"));
+        assert!(record.remote_user.is_none());
+        assert_eq!(record.timestamp_ms, Some(971_186_136_000));
+        assert_eq!(record.method.as_deref(), Some("GET"));
+        assert_eq!(record.uri.as_deref(), Some("/index.html?a=1"));
+        assert_eq!(record.status, Some(200));
+        assert_eq!(record.body_bytes_sent, Some(612));
+        assert_eq!(record.http_user_agent.as_deref(), Some("curl/8.0"));
+    }
+
+    #[test]
+    fn custom_format_should_extract_timings_and_host() {
+        let parser = sample_parser();
+        let line = r#"10.0.0.2 - bob [10/Oct/2000:13:55:36 +0800] "POST /api HTTP/1.1" 502 0 "-" "Mozilla \x22x\x22" rt=0.125 urt="0.050, 0.070" host=api.local"#;
+
+        let record = parser.format("main").expect("format").parse_line(line).expect("parse line");
+
+        assert_eq!(record.remote_user.as_deref(), Some("bob"));
+        assert_eq!(record.status, Some(502));
+        assert_eq!(record.request_time, Some(0.125));
+        assert!((record.upstream_response_time.expect("upstream time") - 0.12).abs() < 1e-9);
+        assert_eq!(record.host.as_deref(), Some("api.local"));
+        assert_eq!(record.http_user_agent.as_deref(), Some("Mozilla \"x\""));
+        assert!(parser.format("main").expect("format").parse_line("garbage").is_none());
+    }
+
+    #[test]
+    fn json_format_should_handle_escaped_quotes() {
+        let parser = sample_parser();
+        let line = r#"{"addr":"10.0.0.3","uri":"/a\"b","status":404,"ua":"UA 中","time":"2000-10-10T13:55:36+00:00"}"#;
+
+        let record = parser.format("json_log").expect("format").parse_line(line).expect("parse line");
+
+        assert_eq!(record.uri.as_deref(), Some("/a\"b"));
+        assert_eq!(record.status, Some(404));
+        assert_eq!(record.http_user_agent.as_deref(), Some("UA 中"));
+        assert_eq!(record.timestamp_ms, Some(971_186_136_000));
+    }
+}
@@ -0,0 +1,841 @@
+use crate::config::{load_nginx_config, Directive, NginxConfig};
+use crate::nginx::resolve_nginx_prefix;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use flate2::read::GzDecoder;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter, State};
+use tokio::time::sleep;
+
+#[cfg(not(target_os = "windows"))]
+use std::os::unix::fs::MetadataExt;
+
+/// 实时追踪到新日志行时推送给前端的事件名
+pub const NGINX_LOG_LINES_EVENT: &str = "nginx-log-lines";
+
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 单次轮询最多读取的字节数，避免日志突增时一次性推送过多内容
+const TAIL_MAX_READ_BYTES: u64 = 1024 * 1024;
+/// 开始追踪时回看历史行所读取的尾部字节数
+const TAIL_BACKLOG_BYTES: u64 = 64 * 1024;
+const DEFAULT_SEARCH_LIMIT: usize = 1000;
+const DEFAULT_ACCESS_LOG_PATH: &str = "logs/access.log";
+const DEFAULT_ERROR_LOG_PATH: &str = "logs/error.log";
+const DEFAULT_ACCESS_LOG_FORMAT: &str = "combined";
+const DEFAULT_ERROR_LOG_LEVEL: &str = "error";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum NginxLogKind {
+    Access,
+    Error,
+}
+
+/// 从配置中发现的 access_log / error_log 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogSource {
+    pub kind: NginxLogKind,
+    pub path: String,
+    pub configured_path: String,
+    /// access_log 使用的 log_format 名称
+    pub format: Option<String>,
+    /// error_log 的日志级别
+    pub level: Option<String>,
+    /// 为空表示 http / main 级别的全局日志
+    pub server_id: Option<String>,
+    pub server_name: Vec<String>,
+    pub listen: Vec<String>,
+    pub line: usize,
+    /// 配置中未声明时 nginx 使用的默认日志
+    pub is_default: bool,
+    pub exists: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogTailEvent {
+    pub tail_id: String,
+    pub path: String,
+    pub lines: Vec<String>,
+    pub rotated: bool,
+    pub truncated: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct NginxLogSearchRequest {
+    pub path: String,
+    pub include_rotated: bool,
+    pub pattern: Option<String>,
+    pub case_insensitive: bool,
+    pub start_time_ms: Option<i64>,
+    pub end_time_ms: Option<i64>,
+    pub limit: usize,
+}
+
+impl Default for NginxLogSearchRequest {
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            include_rotated: true,
+            pattern: None,
+            case_insensitive: false,
+            start_time_ms: None,
+            end_time_ms: None,
+            limit: DEFAULT_SEARCH_LIMIT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogSearchMatch {
+    pub file: String,
+    pub line_number: usize,
+    pub timestamp_ms: Option<i64>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxLogSearchResult {
+    pub files: Vec<String>,
+    pub matches: Vec<NginxLogSearchMatch>,
+    pub total_matches: usize,
+    pub truncated: bool,
+}
+
+/// 正在运行的日志追踪任务，key 为 tail_id
+#[derive(Default)]
+pub struct NginxLogTailState {
+    tails: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+static TAIL_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+// ==================== 日志文件发现 ====================
+
+/// 配置中的相对路径以 nginx prefix 为基准；未配置 nginx 路径时，
+/// 按常见目录结构（prefix/conf/nginx.conf）从配置文件位置反推
+pub(crate) fn resolve_log_prefix(config_path: &str, nginx_path: &str) -> PathBuf {
+    if let Some(prefix) = resolve_nginx_prefix(nginx_path) {
+        return prefix;
+    }
+
+    let config_dir = Path::new(config_path).parent().unwrap_or_else(|| Path::new(""));
+    config_dir
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| config_dir.to_path_buf())
+}
+
+fn resolve_log_path(prefix: &Path, configured_path: &str) -> PathBuf {
+    let path = Path::new(configured_path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        prefix.join(path)
+    }
+}
+
+/// 解析 access_log / error_log 指令，返回 (路径, 格式名或级别)；
+/// 关闭、syslog、内存缓冲以及包含变量的路径无法直接读取，返回 None
+fn parse_log_directive(kind: NginxLogKind, directive: &Directive) -> Option<(String, String)> {
+    let mut tokens = directive.value.split_whitespace();
+    let path = tokens.next()?.trim_matches(|c| c == '"' || c == '\'');
+
+    if path == "off"
+        || path == "stderr"
+        || path.starts_with("syslog:")
+        || path.starts_with("memory:")
+        || path.contains('$')
+    {
+        return None;
+    }
+
+    let second = tokens.next().filter(|token| !token.contains('='));
+    let extra = match kind {
+        NginxLogKind::Access => second.unwrap_or(DEFAULT_ACCESS_LOG_FORMAT),
+        NginxLogKind::Error => second.unwrap_or(DEFAULT_ERROR_LOG_LEVEL),
+    };
+
+    Some((path.to_string(), extra.to_string()))
+}
+
+fn directive_log_kind(name: &str) -> Option<NginxLogKind> {
+    match name {
+        "access_log" => Some(NginxLogKind::Access),
+        "error_log" => Some(NginxLogKind::Error),
+        _ => None,
+    }
+}
+
+fn build_log_source(
+    kind: NginxLogKind,
+    prefix: &Path,
+    configured_path: &str,
+    extra: String,
+    line: usize,
+    is_default: bool,
+) -> NginxLogSource {
+    let path = resolve_log_path(prefix, configured_path);
+
+    NginxLogSource {
+        kind,
+        exists: path.is_file(),
+        path: path.to_string_lossy().to_string(),
+        configured_path: configured_path.to_string(),
+        format: (kind == NginxLogKind::Access).then(|| extra.clone()),
+        level: (kind == NginxLogKind::Error).then_some(extra),
+        server_id: None,
+        server_name: Vec::new(),
+        listen: Vec::new(),
+        line,
+        is_default,
+    }
+}
+
+/// 从解析后的配置中收集所有日志文件（全局 + 各 server / location）
+pub(crate) fn discover_log_sources(config: &NginxConfig, prefix: &Path) -> Vec<NginxLogSource> {
+    let mut sources = Vec::new();
+    let mut seen = HashSet::new();
+
+    for directive in &config.global_directives {
+        let Some(kind) = directive_log_kind(&directive.name) else {
+            continue;
+        };
+        let Some((configured_path, extra)) = parse_log_directive(kind, directive) else {
+            continue;
+        };
+
+        let source = build_log_source(kind, prefix, &configured_path, extra, directive.line, false);
+        if seen.insert((kind, source.path.clone(), None::<String>)) {
+            sources.push(source);
+        }
+    }
+
+    // 全局未声明时，nginx 会写入默认路径
+    for (kind, default_path, extra) in [
+        (NginxLogKind::Access, DEFAULT_ACCESS_LOG_PATH, DEFAULT_ACCESS_LOG_FORMAT),
+        (NginxLogKind::Error, DEFAULT_ERROR_LOG_PATH, DEFAULT_ERROR_LOG_LEVEL),
+    ] {
+        let has_global = config
+            .global_directives
+            .iter()
+            .any(|directive| directive_log_kind(&directive.name) == Some(kind));
+        if !has_global {
+            sources.push(build_log_source(kind, prefix, default_path, extra.to_string(), 0, true));
+        }
+    }
+
+    for server in config.servers.iter().filter(|server| server.enabled) {
+        let location_directives = server
+            .locations
+            .iter()
+            .flat_map(|location| location.directives.iter());
+
+        for directive in server.directives.iter().chain(location_directives) {
+            let Some(kind) = directive_log_kind(&directive.name) else {
+                continue;
+            };
+            let Some((configured_path, extra)) = parse_log_directive(kind, directive) else {
+                continue;
+            };
+
+            let mut source = build_log_source(kind, prefix, &configured_path, extra, directive.line, false);
+            if !seen.insert((kind, source.path.clone(), Some(server.id.clone()))) {
+                continue;
+            }
+
+            source.server_id = Some(server.id.clone());
+            source.server_name = server.server_name.clone();
+            source.listen = server.listen.clone();
+            sources.push(source);
+        }
+    }
+
+    sources
+}
+
+// ==================== 实时追踪 ====================
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileIdentity {
+    #[cfg(not(target_os = "windows"))]
+    inode: (u64, u64),
+    #[cfg(target_os = "windows")]
+    created: Option<SystemTime>,
+}
+
+#[cfg(not(target_os = "windows"))]
+fn file_identity(metadata: &fs::Metadata) -> FileIdentity {
+    FileIdentity {
+        inode: (metadata.dev(), metadata.ino()),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn file_identity(metadata: &fs::Metadata) -> FileIdentity {
+    FileIdentity {
+        created: metadata.created().ok(),
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct TailChunk {
+    pub lines: Vec<String>,
+    pub rotated: bool,
+    pub truncated: bool,
+}
+
+impl TailChunk {
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty() && !self.rotated && !self.truncated
+    }
+}
+
+/// 基于偏移量的日志追踪器：文件被轮转（inode 变化）时从新文件开头读取，
+/// 被截断（长度小于已读偏移）时从头开始
+pub(crate) struct LogTailer {
+    path: PathBuf,
+    offset: u64,
+    identity: Option<FileIdentity>,
+    pending: Vec<u8>,
+}
+
+impl LogTailer {
+    pub(crate) fn open_at_end(path: &Path) -> Self {
+        let metadata = fs::metadata(path).ok();
+
+        Self {
+            path: path.to_path_buf(),
+            offset: metadata.as_ref().map(|meta| meta.len()).unwrap_or(0),
+            identity: metadata.as_ref().map(file_identity),
+            pending: Vec::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.offset = 0;
+        self.pending.clear();
+    }
+
+    pub(crate) fn poll(&mut self) -> io::Result<TailChunk> {
+        let mut chunk = TailChunk::default();
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            // 轮转过程中文件可能短暂不存在，下次轮询再读
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(chunk),
+            Err(error) => return Err(error),
+        };
+
+        let identity = file_identity(&metadata);
+        if self.identity.as_ref() != Some(&identity) {
+            chunk.rotated = self.identity.is_some();
+            self.identity = Some(identity);
+            self.reset();
+        } else if metadata.len() < self.offset {
+            chunk.truncated = true;
+            self.reset();
+        }
+
+        if metadata.len() == self.offset {
+            return Ok(chunk);
+        }
+
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buffer = Vec::new();
+        file.take(TAIL_MAX_READ_BYTES).read_to_end(&mut buffer)?;
+        self.offset += buffer.len() as u64;
+        self.pending.extend_from_slice(&buffer);
+
+        while let Some(position) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=position).collect();
+            chunk.lines.push(decode_log_line(&line));
+        }
+
+        Ok(chunk)
+    }
+}
+
+fn decode_log_line(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches(['\r', '\n'])
+        .to_string()
+}
+
+/// 读取文件末尾的若干行，用于开始追踪时展示上下文
+fn read_backlog_lines(path: &Path, count: usize) -> io::Result<Vec<String>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut file = File::open(path)?;
+    let length = file.metadata()?.len();
+    let start = length.saturating_sub(TAIL_BACKLOG_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    let mut lines: Vec<String> = buffer
+        .split(|byte| *byte == b'\n')
+        .map(decode_log_line)
+        .collect();
+
+    // 末尾换行产生的空串，以及从文件中部开始读取时不完整的首行都需要丢弃
+    if lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    let skip = lines.len().saturating_sub(count);
+    Ok(lines.split_off(skip))
+}
+
+fn emit_tail_event(app: &AppHandle, tail_id: &str, path: &Path, chunk: TailChunk, error: Option<String>) {
+    let _ = app.emit(
+        NGINX_LOG_LINES_EVENT,
+        NginxLogTailEvent {
+            tail_id: tail_id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            lines: chunk.lines,
+            rotated: chunk.rotated,
+            truncated: chunk.truncated,
+            error,
+        },
+    );
+}
+
+// ==================== 历史检索 ====================
+
+fn is_gzip_file(path: &Path) -> bool {
+    path.extension()
+        .map(|extension| extension.eq_ignore_ascii_case("gz"))
+        .unwrap_or(false)
+}
+
+/// 列出日志文件及其轮转文件（access.log.1、access.log.2.gz、access.log-20240101.gz 等），按从旧到新排序
+pub(crate) fn list_log_files(path: &Path, include_rotated: bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    if include_rotated {
+        if let (Some(dir), Some(base_name)) = (path.parent(), path.file_name()) {
+            let base_name = base_name.to_string_lossy().to_string();
+            let mut rotated: Vec<(SystemTime, PathBuf)> = fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok())
+                        .filter(|entry| {
+                            let name = entry.file_name().to_string_lossy().to_string();
+                            name != base_name
+                                && (name.starts_with(&format!("{}.", base_name))
+                                    || name.starts_with(&format!("{}-", base_name)))
+                        })
+                        .filter_map(|entry| {
+                            let modified = entry.metadata().and_then(|meta| meta.modified()).ok()?;
+                            Some((modified, entry.path()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            rotated.sort();
+            files.extend(rotated.into_iter().map(|(_, path)| path));
+        }
+    }
+
+    if path.is_file() {
+        files.push(path.to_path_buf());
+    }
+
+    files
+}
+
+fn open_log_reader(path: &Path) -> io::Result<Box<dyn BufRead>> {
+    let file = File::open(path)?;
+    if is_gzip_file(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// 逐行读取日志（兼容 .gz），行号从 1 开始
+pub(crate) fn for_each_log_line<F>(path: &Path, mut handle: F) -> io::Result<()>
+where
+    F: FnMut(usize, String),
+{
+    let mut reader = open_log_reader(path)?;
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    loop {
+        buffer.clear();
+        if reader.read_until(b'\n', &mut buffer)? == 0 {
+            break;
+        }
+        line_number += 1;
+        handle(line_number, decode_log_line(&buffer));
+    }
+
+    Ok(())
+}
+
+/// 提取日志行的时间戳，支持 access log 的 `[10/Oct/2000:13:55:36 +0800]`、
+/// error log 的 `2024/01/02 13:55:36`（本地时间）以及 `$time_iso8601`
+pub(crate) fn extract_log_timestamp_ms(line: &str) -> Option<i64> {
+    if let (Some(start), Some(end)) = (line.find('['), line.find(']')) {
+        if start < end {
+            if let Ok(time) = DateTime::parse_from_str(&line[start + 1..end], "%d/%b/%Y:%H:%M:%S %z") {
+                return Some(time.timestamp_millis());
+            }
+        }
+    }
+
+    if let Some(prefix) = line.get(..19) {
+        if let Ok(time) = NaiveDateTime::parse_from_str(prefix, "%Y/%m/%d %H:%M:%S") {
+            return Local
+                .from_local_datetime(&time)
+                .earliest()
+                .map(|time| time.timestamp_millis());
+        }
+    }
+
+    line.split(|c: char| c.is_whitespace() || c == '"' || c == '[' || c == ']')
+        .filter(|token| token.len() >= 20 && token.contains('T'))
+        .find_map(|token| DateTime::parse_from_rfc3339(token).ok())
+        .map(|time| time.timestamp_millis())
+}
+
+fn build_search_regex(request: &NginxLogSearchRequest) -> Result<Option<Regex>, String> {
+    let Some(pattern) = request.pattern.as_deref().filter(|pattern| !pattern.is_empty()) else {
+        return Ok(None);
+    };
+
+    RegexBuilder::new(pattern)
+        .case_insensitive(request.case_insensitive)
+        .build()
+        .map(Some)
+        .map_err(|e| format!("正则表达式无效: {}", e))
+}
+
+/// 在日志及其轮转文件中检索，保留时间上最新的 limit 条匹配
+pub(crate) fn search_log_files(request: &NginxLogSearchRequest) -> Result<NginxLogSearchResult, String> {
+    let regex = build_search_regex(request)?;
+    let files = list_log_files(Path::new(&request.path), request.include_rotated);
+    if files.is_empty() {
+        return Err(format!("日志文件不存在: {}", request.path));
+    }
+
+    let limit = if request.limit == 0 { DEFAULT_SEARCH_LIMIT } else { request.limit };
+    let has_time_range = request.start_time_ms.is_some() || request.end_time_ms.is_some();
+    let mut matches = VecDeque::new();
+    let mut total_matches = 0;
+
+    for file in &files {
+        let file_label = file.to_string_lossy().to_string();
+        for_each_log_line(file, |line_number, text| {
+            if let Some(regex) = &regex {
+                if !regex.is_match(&text) {
+                    return;
+                }
+            }
+
+            let timestamp_ms = extract_log_timestamp_ms(&text);
+            if has_time_range {
+                let Some(timestamp) = timestamp_ms else {
+                    return;
+                };
+                if request.start_time_ms.is_some_and(|start| timestamp < start)
+                    || request.end_time_ms.is_some_and(|end| timestamp > end)
+                {
+                    return;
+                }
+            }
+
+            total_matches += 1;
+            if matches.len() == limit {
+                matches.pop_front();
+            }
+            matches.push_back(NginxLogSearchMatch {
+                file: file_label.clone(),
+                line_number,
+                timestamp_ms,
+                text,
+            });
+        })
+        .map_err(|e| format!("读取日志文件失败 {}: {}", file_label, e))?;
+    }
+
+    Ok(NginxLogSearchResult {
+        files: files.iter().map(|file| file.to_string_lossy().to_string()).collect(),
+        truncated: total_matches > matches.len(),
+        matches: matches.into_iter().collect(),
+        total_matches,
+    })
+}
+
+// ==================== 命令 ====================
+
+/// 从配置中发现 access_log / error_log 文件
+#[tauri::command]
+pub async fn discover_nginx_log_files(
+    config_path: String,
+    nginx_path: String,
+) -> Result<Vec<NginxLogSource>, String> {
+    let config = load_nginx_config(&config_path)?;
+    let prefix = resolve_log_prefix(&config_path, &nginx_path);
+
+    Ok(discover_log_sources(&config, &prefix))
+}
+
+/// 开始实时追踪日志文件，新行通过 `nginx-log-lines` 事件推送，返回追踪 ID
+#[tauri::command]
+pub async fn start_nginx_log_tail(
+    app: AppHandle,
+    state: State<'_, NginxLogTailState>,
+    path: String,
+    backlog_lines: Option<usize>,
+) -> Result<String, String> {
+    let log_path = PathBuf::from(&path);
+    if !log_path.is_file() {
+        return Err(format!("日志文件不存在: {}", path));
+    }
+
+    // 先读取历史行，失败时不登记追踪，避免残留无人停止的条目
+    let backlog = read_backlog_lines(&log_path, backlog_lines.unwrap_or(0))
+        .map_err(|e| format!("读取日志文件失败: {}", e))?;
+
+    let tail_id = format!("tail-{}", TAIL_SEQUENCE.fetch_add(1, Ordering::Relaxed));
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    state
+        .tails
+        .lock()
+        .map_err(|_| "日志追踪状态异常".to_string())?
+        .insert(tail_id.clone(), stop_flag.clone());
+    let task_tail_id = tail_id.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut tailer = LogTailer::open_at_end(&log_path);
+        if !backlog.is_empty() {
+            let chunk = TailChunk {
+                lines: backlog,
+                ..TailChunk::default()
+            };
+            emit_tail_event(&app, &task_tail_id, &log_path, chunk, None);
+        }
+
+        while !stop_flag.load(Ordering::Relaxed) {
+            match tailer.poll() {
+                Ok(chunk) if !chunk.is_empty() => {
+                    emit_tail_event(&app, &task_tail_id, &log_path, chunk, None);
+                }
+                Ok(_) => {}
+                Err(error) => {
+                    let message = format!("读取日志文件失败: {}", error);
+                    emit_tail_event(&app, &task_tail_id, &log_path, TailChunk::default(), Some(message));
+                }
+            }
+
+            sleep(TAIL_POLL_INTERVAL).await;
+        }
+    });
+
+    Ok(tail_id)
+}
+
+/// 停止日志追踪
+#[tauri::command]
+pub async fn stop_nginx_log_tail(
+    state: State<'_, NginxLogTailState>,
+    tail_id: String,
+) -> Result<bool, String> {
+    let stop_flag = state
+        .tails
+        .lock()
+        .map_err(|_| "日志追踪状态异常".to_string())?
+        .remove(&tail_id);
+
+    match stop_flag {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// 检索日志（含 .gz 轮转文件），支持时间范围与正则过滤
+#[tauri::command]
+pub async fn search_nginx_log(request: NginxLogSearchRequest) -> Result<NginxLogSearchResult, String> {
+    // 检索会解压并逐行扫描轮转文件，放到阻塞线程池中执行
+    tauri::async_runtime::spawn_blocking(move || search_log_files(&request))
+        .await
+        .map_err(|e| format!("日志检索任务异常: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::env;
+    use std::io::Write;
+    use std::time::UNIX_EPOCH;
+
+    fn create_temp_dir(name: &str) -> PathBuf {
+        let unique = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("{}_{}", name, unique));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn discover_log_sources_should_collect_global_and_server_logs() {
+        let content = r#"error_log logs/error.log warn;
+http {
+    log_format main '$remote_addr - $request';
+    access_log /var/log/nginx/access.log main buffer=32k;
+
+    server {
+        listen 80;
+        server_name api.local;
+        access_log logs/api.access.log;
+
+        location /health {
+            access_log off;
+        }
+    }
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+
+        let sources = discover_log_sources(&config, Path::new("/opt/nginx"));
+
+        let global_access = sources
+            .iter()
+            .find(|source| source.kind == NginxLogKind::Access && source.server_id.is_none())
+            .expect("global access log");
+        assert_eq!(global_access.path, "/var/log/nginx/access.log");
+        assert_eq!(global_access.format.as_deref(), Some("main"));
+
+        let error_log = sources
+            .iter()
+            .find(|source| source.kind == NginxLogKind::Error)
+            .expect("error log");
+        assert_eq!(error_log.level.as_deref(), Some("warn"));
+        assert!(!error_log.is_default);
+
+        let server_access = sources
+            .iter()
+            .find(|source| source.server_id.is_some())
+            .expect("server access log");
+        assert_eq!(
+            PathBuf::from(&server_access.path),
+            Path::new("/opt/nginx").join("logs/api.access.log")
+        );
+        assert_eq!(server_access.format.as_deref(), Some("combined"));
+        assert_eq!(server_access.server_name, vec!["api.local"]);
+        assert_eq!(sources.len(), 3);
+    }
+
+    #[test]
+    fn extract_log_timestamp_ms_should_support_access_error_and_iso_formats() {
+        let access = r#"127.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET / HTTP/1.1" 200 612"#;
+        let iso = r#"{"time":"2000-10-10T13:55:36+00:00","status":"200"}"#;
+        let error = "2000/10/10 13:55:36 [error] 1234#0: *1 open() failed";
+
+        assert_eq!(extract_log_timestamp_ms(access), Some(971_186_136_000));
+        assert_eq!(extract_log_timestamp_ms(iso), Some(971_186_136_000));
+        assert!(extract_log_timestamp_ms(error).is_some());
+        assert_eq!(extract_log_timestamp_ms("no timestamp here"), None);
+    }
+
+    #[test]
+    fn log_tailer_should_follow_appends_and_truncation() {
+        let dir = create_temp_dir("nginx_log_tail");
+        let path = dir.join("access.log");
+        fs::write(&path, "old line\n").expect("write log");
+
+        let mut tailer = LogTailer::open_at_end(&path);
+        let mut file = fs::OpenOptions::new().append(true).open(&path).expect("open log");
+        write!(file, "first\nsecond\npart").expect("append log");
+
+        let chunk = tailer.poll().expect("poll appended lines");
+        assert_eq!(chunk.lines, vec!["first", "second"]);
+
+        writeln!(file, "ial").expect("complete partial line");
+        let chunk = tailer.poll().expect("poll partial line");
+        assert_eq!(chunk.lines, vec!["partial"]);
+
+        fs::write(&path, "fresh\n").expect("truncate log");
+        let chunk = tailer.poll().expect("poll truncated log");
+        assert!(chunk.truncated);
+        assert_eq!(chunk.lines, vec!["fresh"]);
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn search_log_files_should_scan_gzip_rotations_with_filters() {
+        let dir = create_temp_dir("nginx_log_search");
+        let path = dir.join("access.log");
+        let rotated = dir.join("access.log.2.gz");
+
+        let mut encoder = GzEncoder::new(File::create(&rotated).expect("create gz"), Compression::default());
+        writeln!(encoder, r#"10.0.0.1 - - [10/Oct/2000:13:55:36 +0000] "GET /api/old HTTP/1.1" 502 0"#)
+            .expect("write gz");
+        writeln!(encoder, r#"10.0.0.1 - - [10/Oct/2000:13:55:37 +0000] "GET /static HTTP/1.1" 200 10"#)
+            .expect("write gz");
+        encoder.finish().expect("finish gz");
+
+        fs::write(
+            &path,
+            "10.0.0.2 - - [11/Oct/2000:08:00:00 +0000] \"GET /api/new HTTP/1.1\" 502 0\n",
+        )
+        .expect("write log");
+
+        let request = NginxLogSearchRequest {
+            path: path.to_string_lossy().to_string(),
+            pattern: Some(r"/API/\w+.* 502".to_string()),
+            case_insensitive: true,
+            ..NginxLogSearchRequest::default()
+        };
+        let result = search_log_files(&request).expect("search logs");
+        assert_eq!(result.files.len(), 2);
+        assert_eq!(result.total_matches, 2);
+        assert_eq!(result.matches[0].line_number, 1);
+        assert!(result.matches[1].text.contains("/api/new"));
+
+        let request = NginxLogSearchRequest {
+            start_time_ms: Some(971_186_137_000),
+            end_time_ms: Some(971_186_137_000),
+            ..request
+        };
+        let result = search_log_files(&NginxLogSearchRequest {
+            pattern: None,
+            ..request
+        })
+        .expect("search by time");
+        assert_eq!(result.total_matches, 1);
+        assert!(result.matches[0].text.contains("/static"));
+
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,521 @@
+use crate::audit::audited;
+use crate::config::{add_server_block_text_internal, load_nginx_config, EditResult, LocationBlock, NginxConfig, ServerBlock};
+use crate::port_preflight::{parse_listen_address, ListenProtocol};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+use tokio::time::sleep;
+
+/// 每次采样后推送给前端的事件名
+pub const NGINX_METRICS_EVENT: &str = "nginx-metrics-sample";
+
+const DEFAULT_STUB_STATUS_PORT: u16 = 18080;
+const DEFAULT_STUB_STATUS_PATH: &str = "/nginx_status";
+const DEFAULT_POLL_INTERVAL_MS: u64 = 2000;
+const MIN_POLL_INTERVAL_MS: u64 = 500;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+/// 内存中保留的采样点数量
+const MAX_HISTORY_SAMPLES: usize = 600;
+
+/// 配置中可用的 stub_status 端点
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StubStatusEndpoint {
+    pub server_id: String,
+    pub location_id: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+    pub url: String,
+    /// 请求使用的 Host，取 server 的第一个非通配 server_name，没有时使用监听地址
+    pub host_header: String,
+    /// 仅监听回环地址，或 location 中限制了只允许本机访问
+    pub localhost_only: bool,
+}
+
+/// stub_status 输出的一次采样
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxMetricsSample {
+    pub timestamp_ms: i64,
+    pub active: u64,
+    pub reading: u64,
+    pub writing: u64,
+    pub waiting: u64,
+    pub accepts: u64,
+    pub handled: u64,
+    pub requests: u64,
+    /// 与上一采样点之间的每秒请求数，首个采样或计数器重置（重启）时为空
+    pub requests_per_second: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxMetricsEvent {
+    pub endpoint: StubStatusEndpoint,
+    pub sample: Option<NginxMetricsSample>,
+    pub error: Option<String>,
+}
+
+struct MetricsPoller {
+    endpoint: StubStatusEndpoint,
+    stop_flag: Arc<AtomicBool>,
+}
+
+/// 指标轮询任务与最近的采样历史
+#[derive(Default)]
+pub struct NginxMetricsState {
+    poller: Mutex<Option<MetricsPoller>>,
+    history: Arc<Mutex<VecDeque<NginxMetricsSample>>>,
+}
+
+// ==================== 端点识别 ====================
+
+/// 由 listen 指令得到访问地址与端口，通配地址统一使用 127.0.0.1 访问
+fn listen_target(listen: &str) -> Option<(String, u16, bool)> {
+    let address = parse_listen_address(listen).filter(|address| address.protocol == ListenProtocol::Tcp)?;
+    let loopback = matches!(address.host.as_deref(), Some("127.0.0.1" | "localhost" | "::1"));
+    let host = address.host.unwrap_or_else(|| "127.0.0.1".to_string());
+
+    Some((host, address.port, loopback))
+}
+
+fn is_location_restricted_to_localhost(location: &LocationBlock) -> bool {
+    let allows_only_loopback = location
+        .directives
+        .iter()
+        .filter(|directive| directive.name == "allow")
+        .all(|directive| matches!(directive.value.as_str(), "127.0.0.1" | "::1"));
+    let denies_all = location
+        .directives
+        .iter()
+        .any(|directive| directive.name == "deny" && directive.value == "all");
+
+    allows_only_loopback && denies_all
+}
+
+/// 能直接作为 Host 请求的 server_name，排除 `_`、通配符与正则
+fn literal_server_name(server: &ServerBlock) -> Option<&str> {
+    server.server_name.iter().map(|name| name.trim()).find(|name| {
+        !name.is_empty()
+            && *name != "_"
+            && !name.starts_with('~')
+            && !name.starts_with('.')
+            && !name.contains('*')
+            && !name.contains('$')
+    })
+}
+
+fn build_endpoint(server: &ServerBlock, location: &LocationBlock) -> Option<StubStatusEndpoint> {
+    let (host, port, loopback) = server
+        .listen
+        .iter()
+        .filter(|listen| !listen.contains(" ssl"))
+        .find_map(|listen| listen_target(listen))?;
+
+    let url_host = if host.contains(':') { format!("[{}]", host) } else { host.clone() };
+
+    Some(StubStatusEndpoint {
+        server_id: server.id.clone(),
+        location_id: location.id.clone(),
+        url: format!("http://{}:{}{}", url_host, port, location.path),
+        host_header: literal_server_name(server).map(str::to_string).unwrap_or(url_host),
+        host,
+        port,
+        path: location.path.clone(),
+        localhost_only: loopback || is_location_restricted_to_localhost(location),
+    })
+}
+
+/// 查找配置中已启用的 stub_status location，优先选择仅本机可访问的
+pub(crate) fn detect_stub_status_endpoint(config: &NginxConfig) -> Option<StubStatusEndpoint> {
+    let mut endpoints: Vec<StubStatusEndpoint> = config
+        .servers
+        .iter()
+        .filter(|server| server.enabled)
+        .flat_map(|server| server.locations.iter().map(move |location| (server, location)))
+        .filter(|(_, location)| {
+            !matches!(location.modifier.as_deref(), Some("~") | Some("~*"))
+                && location.directives.iter().any(|directive| directive.name == "stub_status")
+        })
+        .filter_map(|(server, location)| build_endpoint(server, location))
+        .collect();
+
+    endpoints.sort_by_key(|endpoint| !endpoint.localhost_only);
+    endpoints.into_iter().next()
+}
+
+/// 生成仅监听本机的 stub_status server 块
+fn render_stub_status_server(port: u16) -> String {
+    format!(
+        r#"server {{
+    # 运行指标
+    listen 127.0.0.1:{port};
+    server_name localhost;
+
+    location = {path} {{
+        stub_status;
+        allow 127.0.0.1;
+        deny all;
+        access_log off;
+    }}
+}}"#,
+        port = port,
+        path = DEFAULT_STUB_STATUS_PATH,
+    )
+}
+
+// ==================== 采样 ====================
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn labelled_value(text: &str, label: &str) -> Option<u64> {
+    let start = text.find(label)? + label.len();
+    text[start..].split_whitespace().next()?.parse().ok()
+}
+
+/// 解析 stub_status 输出：
+/// ```text
+/// Active connections: 291
+/// server accepts handled requests
+///  16630948 16630948 31070465
+/// Reading: 6 Writing: 179 Waiting: 106
+/// ```
+pub(crate) fn parse_stub_status(body: &str, timestamp_ms: i64) -> Result<NginxMetricsSample, String> {
+    let invalid = || "stub_status 输出格式无法识别".to_string();
+
+    let counters: Vec<u64> = body
+        .lines()
+        .skip_while(|line| !line.trim_start().starts_with("server accepts"))
+        .nth(1)
+        .ok_or_else(invalid)?
+        .split_whitespace()
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    if counters.len() < 3 {
+        return Err(invalid());
+    }
+
+    Ok(NginxMetricsSample {
+        timestamp_ms,
+        active: labelled_value(body, "Active connections:").ok_or_else(invalid)?,
+        reading: labelled_value(body, "Reading:").unwrap_or(0),
+        writing: labelled_value(body, "Writing:").unwrap_or(0),
+        waiting: labelled_value(body, "Waiting:").unwrap_or(0),
+        accepts: counters[0],
+        handled: counters[1],
+        requests: counters[2],
+        requests_per_second: None,
+    })
+}
+
+/// 根据上一采样计算请求速率；nginx 重启后计数器归零，此时不计算
+fn apply_request_rate(sample: &mut NginxMetricsSample, previous: Option<&NginxMetricsSample>) {
+    let Some(previous) = previous else {
+        return;
+    };
+
+    let elapsed_ms = sample.timestamp_ms - previous.timestamp_ms;
+    if elapsed_ms <= 0 || sample.requests < previous.requests {
+        return;
+    }
+
+    sample.requests_per_second = Some((sample.requests - previous.requests) as f64 * 1000.0 / elapsed_ms as f64);
+}
+
+/// 直接以 HTTP/1.0 请求 stub_status，无需额外的 HTTP 客户端依赖
+fn fetch_stub_status(endpoint: &StubStatusEndpoint) -> Result<String, String> {
+    let address = (endpoint.host.as_str(), endpoint.port)
+        .to_socket_addrs()
+        .map_err(|e| format!("解析地址失败: {}", e))?
+        .next()
+        .ok_or_else(|| format!("无法解析地址: {}", endpoint.host))?;
+
+    let started_at = Instant::now();
+    let mut stream = TcpStream::connect_timeout(&address, FETCH_TIMEOUT)
+        .map_err(|e| format!("连接 {} 失败: {}", endpoint.url, e))?;
+    let remaining = FETCH_TIMEOUT.saturating_sub(started_at.elapsed()).max(Duration::from_millis(100));
+    let _ = stream.set_read_timeout(Some(remaining));
+    let _ = stream.set_write_timeout(Some(remaining));
+
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: {}\r\nUser-Agent: nginx-config-manager\r\nConnection: close\r\n\r\n",
+        endpoint.path, endpoint.host_header
+    );
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("请求 stub_status 失败: {}", e))?;
+
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| format!("读取 stub_status 响应失败: {}", e))?;
+
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or_else(|| "stub_status 响应不完整".to_string())?;
+    let status_line = head.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("stub_status 返回异常状态: {}", status_line));
+    }
+
+    Ok(body.to_string())
+}
+
+fn push_history(history: &Mutex<VecDeque<NginxMetricsSample>>, sample: &NginxMetricsSample) {
+    if let Ok(mut history) = history.lock() {
+        if history.len() == MAX_HISTORY_SAMPLES {
+            history.pop_front();
+        }
+        history.push_back(sample.clone());
+    }
+}
+
+fn stop_current_poller(state: &NginxMetricsState) -> Result<bool, String> {
+    let poller = state
+        .poller
+        .lock()
+        .map_err(|_| "指标采集状态异常".to_string())?
+        .take();
+
+    Ok(match poller {
+        Some(poller) => {
+            poller.stop_flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    })
+}
+
+// ==================== 命令 ====================
+
+/// 检测配置中是否已有 stub_status 端点
+#[tauri::command]
+pub async fn detect_stub_status(config_path: String) -> Result<Option<StubStatusEndpoint>, String> {
+    let config = load_nginx_config(&config_path)?;
+    Ok(detect_stub_status_endpoint(&config))
+}
+
+/// 配置中没有 stub_status 时，新增一个仅监听本机的监控 server 块
+#[tauri::command]
+pub async fn enable_stub_status(
+    app: AppHandle,
+    config_path: String,
+    port: Option<u16>,
+) -> Result<EditResult, String> {
+    audited(
+        &app,
+        "enable_stub_status",
+        "stub_status 监控端点启用",
+        enable_stub_status_internal(config_path, port),
+    )
+    .await
+}
+
+async fn enable_stub_status_internal(config_path: String, port: Option<u16>) -> Result<EditResult, String> {
+    let config = load_nginx_config(&config_path)?;
+    if let Some(endpoint) = detect_stub_status_endpoint(&config) {
+        return Ok(EditResult {
+            success: true,
+            message: format!("已存在 stub_status 端点: {}", endpoint.url),
+        });
+    }
+
+    let port = port.unwrap_or(DEFAULT_STUB_STATUS_PORT);
+    let result = add_server_block_text_internal(config_path, render_stub_status_server(port)).await?;
+
+    Ok(EditResult {
+        success: result.success,
+        message: if result.success {
+            format!(
+                "已添加 stub_status 端点 http://127.0.0.1:{}{}，重载配置后生效",
+                port, DEFAULT_STUB_STATUS_PATH
+            )
+        } else {
+            result.message
+        },
+    })
+}
+
+/// 开始轮询 stub_status，采样通过 `nginx-metrics-sample` 事件推送
+#[tauri::command]
+pub async fn start_nginx_metrics(
+    app: AppHandle,
+    state: State<'_, NginxMetricsState>,
+    config_path: String,
+    interval_ms: Option<u64>,
+) -> Result<StubStatusEndpoint, String> {
+    let config = load_nginx_config(&config_path)?;
+    let endpoint = detect_stub_status_endpoint(&config)
+        .ok_or_else(|| "配置中未找到 stub_status 端点，请先启用".to_string())?;
+
+    stop_current_poller(&state)?;
+    if let Ok(mut history) = state.history.lock() {
+        history.clear();
+    }
+
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    *state
+        .poller
+        .lock()
+        .map_err(|_| "指标采集状态异常".to_string())? = Some(MetricsPoller {
+        endpoint: endpoint.clone(),
+        stop_flag: stop_flag.clone(),
+    });
+
+    let interval = Duration::from_millis(interval_ms.unwrap_or(DEFAULT_POLL_INTERVAL_MS).max(MIN_POLL_INTERVAL_MS));
+    let history = state.history.clone();
+    let task_endpoint = endpoint.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut previous: Option<NginxMetricsSample> = None;
+
+        while !stop_flag.load(Ordering::Relaxed) {
+            // 同步的连接与读取可能阻塞数秒，放到阻塞线程池中执行，避免占用异步运行时
+            let fetch_endpoint = task_endpoint.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || fetch_stub_status(&fetch_endpoint))
+                .await
+                .map_err(|e| format!("采集任务异常: {}", e))
+                .and_then(|fetched| fetched)
+                .and_then(|body| parse_stub_status(&body, now_timestamp_ms()));
+
+            let event = match result {
+                Ok(mut sample) => {
+                    apply_request_rate(&mut sample, previous.as_ref());
+                    push_history(&history, &sample);
+                    previous = Some(sample.clone());
+                    NginxMetricsEvent {
+                        endpoint: task_endpoint.clone(),
+                        sample: Some(sample),
+                        error: None,
+                    }
+                }
+                Err(error) => {
+                    previous = None;
+                    NginxMetricsEvent {
+                        endpoint: task_endpoint.clone(),
+                        sample: None,
+                        error: Some(error),
+                    }
+                }
+            };
+
+            let _ = app.emit(NGINX_METRICS_EVENT, event);
+            sleep(interval).await;
+        }
+    });
+
+    Ok(endpoint)
+}
+
+/// 停止指标轮询
+#[tauri::command]
+pub async fn stop_nginx_metrics(state: State<'_, NginxMetricsState>) -> Result<bool, String> {
+    stop_current_poller(&state)
+}
+
+/// 获取最近的采样历史，便于页面重新打开时恢复曲线
+#[tauri::command]
+pub async fn get_nginx_metrics_history(
+    state: State<'_, NginxMetricsState>,
+) -> Result<Vec<NginxMetricsSample>, String> {
+    let history = state
+        .history
+        .lock()
+        .map_err(|_| "指标采集状态异常".to_string())?;
+
+    Ok(history.iter().cloned().collect())
+}
+
+/// 当前正在轮询的端点
+#[tauri::command]
+pub async fn get_nginx_metrics_endpoint(
+    state: State<'_, NginxMetricsState>,
+) -> Result<Option<StubStatusEndpoint>, String> {
+    let poller = state
+        .poller
+        .lock()
+        .map_err(|_| "指标采集状态异常".to_string())?;
+
+    Ok(poller.as_ref().map(|poller| poller.endpoint.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    #[test]
+    fn parse_stub_status_should_read_all_counters() {
+        let body = "Active connections: 291 \nserver accepts handled requests\n 16630948 16630948 31070465 \nReading: 6 Writing: 179 Waiting: 106 \n";
+
+        let sample = parse_stub_status(body, 1000).expect("parse stub_status");
+
+        assert_eq!(sample.active, 291);
+        assert_eq!(sample.accepts, 16_630_948);
+        assert_eq!(sample.requests, 31_070_465);
+        assert_eq!((sample.reading, sample.writing, sample.waiting), (6, 179, 106));
+        assert!(parse_stub_status("<html>404</html>", 1000).is_err());
+    }
+
+    #[test]
+    fn apply_request_rate_should_skip_counter_reset() {
+        let previous = NginxMetricsSample {
+            timestamp_ms: 0,
+            requests: 100,
+            ..NginxMetricsSample::default()
+        };
+        let mut current = NginxMetricsSample {
+            timestamp_ms: 2000,
+            requests: 300,
+            ..NginxMetricsSample::default()
+        };
+        apply_request_rate(&mut current, Some(&previous));
+        assert_eq!(current.requests_per_second, Some(100.0));
+
+        let mut restarted = NginxMetricsSample {
+            timestamp_ms: 4000,
+            requests: 5,
+            ..NginxMetricsSample::default()
+        };
+        apply_request_rate(&mut restarted, Some(&current));
+        assert!(restarted.requests_per_second.is_none());
+    }
+
+    #[test]
+    fn detect_stub_status_endpoint_should_prefer_rendered_localhost_server() {
+        let content = format!(
+            "http {{\n    server {{\n        listen 80;\n        location /status {{\n            stub_status;\n        }}\n    }}\n{}\n}}\n",
+            render_stub_status_server(18080)
+        );
+        let config = parse_nginx_config(&content, "test.conf").expect("parse config");
+
+        let endpoint = detect_stub_status_endpoint(&config).expect("endpoint");
+
+        assert!(endpoint.localhost_only);
+        assert_eq!(endpoint.url, "http://127.0.0.1:18080/nginx_status");
+        assert_eq!(endpoint.host_header, "localhost");
+
+        let named = parse_nginx_config(
+            "http {\n    server {\n        listen 8080;\n        server_name *.example.com status.example.com;\n        location /status {\n            stub_status;\n        }\n    }\n}\n",
+            "test.conf",
+        )
+        .expect("parse config");
+        let endpoint = detect_stub_status_endpoint(&named).expect("endpoint");
+        assert_eq!(endpoint.host_header, "status.example.com");
+        assert_eq!(listen_target("[::1]:8080 default_server"), Some(("::1".to_string(), 8080, true)));
+        assert_eq!(listen_target("*:80"), Some(("127.0.0.1".to_string(), 80, false)));
+        assert_eq!(listen_target("443 quic"), None);
+    }
+}
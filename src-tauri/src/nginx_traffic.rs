@@ -0,0 +1,517 @@
+use crate::config::{load_nginx_config, ServerBlock};
+use crate::nginx_log_format::{AccessLogParser, AccessLogRecord, CompiledLogFormat};
+use crate::nginx_logs::{discover_log_sources, for_each_log_line, list_log_files, resolve_log_prefix, NginxLogKind};
+use crate::server_match::{is_default_server, listens_on_port, select_server};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+const DEFAULT_WINDOW_MINUTES: u32 = 60;
+const DEFAULT_TOP_N: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TrafficAggregateRequest {
+    pub config_path: String,
+    pub nginx_path: String,
+    /// 统计最近多少分钟，0 表示不限
+    pub window_minutes: u32,
+    pub top_n: usize,
+    pub include_rotated: bool,
+}
+
+impl Default for TrafficAggregateRequest {
+    fn default() -> Self {
+        Self {
+            config_path: String::new(),
+            nginx_path: String::new(),
+            window_minutes: DEFAULT_WINDOW_MINUTES,
+            top_n: DEFAULT_TOP_N,
+            include_rotated: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficMinuteBucket {
+    pub minute_ms: i64,
+    pub requests: usize,
+    pub server_errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficTopItem {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTimePercentiles {
+    pub p50: Option<f64>,
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+}
+
+/// 单个 server 块的流量统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTrafficSummary {
+    /// 无法归属到具体 server 的请求为 None
+    pub server_id: Option<String>,
+    pub server_name: Vec<String>,
+    pub listen: Vec<String>,
+    pub total_requests: usize,
+    pub average_rpm: f64,
+    pub requests_per_minute: Vec<TrafficMinuteBucket>,
+    /// 按 1xx ~ 5xx 分类的数量
+    pub status_classes: BTreeMap<String, usize>,
+    pub status_codes: BTreeMap<u16, usize>,
+    pub request_time: RequestTimePercentiles,
+    pub top_uris: Vec<TrafficTopItem>,
+    pub top_client_ips: Vec<TrafficTopItem>,
+    pub upstream_requests: usize,
+    pub upstream_errors: usize,
+    pub upstream_error_rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficAggregateResult {
+    pub generated_at: i64,
+    pub window_minutes: u32,
+    pub servers: Vec<ServerTrafficSummary>,
+    pub scanned_files: Vec<String>,
+    pub unmatched_lines: usize,
+    pub warnings: Vec<String>,
+}
+
+// ==================== server 归属 ====================
+
+/// 按 nginx 的虚拟主机选择规则在候选 server 中找到处理该请求的块，返回其下标；
+/// 日志记录了 $server_port 时只在监听该端口的 server 中选择
+fn match_server(servers: &[ServerBlock], candidates: &[usize], host: Option<&str>, port: Option<u16>) -> Option<usize> {
+    if candidates.len() == 1 {
+        return candidates.first().copied();
+    }
+
+    let host = host?;
+    let on_port: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|index| port.is_none_or(|port| listens_on_port(&servers[*index], port)))
+        .collect();
+    let indexes = if on_port.is_empty() { candidates } else { &on_port };
+    let pool: Vec<(&ServerBlock, bool)> = indexes
+        .iter()
+        .map(|index| (&servers[*index], is_default_server(&servers[*index], port)))
+        .collect();
+    select_server(&pool, host).map(|(position, _)| indexes[position])
+}
+
+// ==================== 统计 ====================
+
+#[derive(Default)]
+struct TrafficAccumulator {
+    total_requests: usize,
+    minute_buckets: BTreeMap<i64, (usize, usize)>,
+    status_classes: BTreeMap<String, usize>,
+    status_codes: BTreeMap<u16, usize>,
+    request_times: Vec<f64>,
+    uris: HashMap<String, usize>,
+    client_ips: HashMap<String, usize>,
+    upstream_requests: usize,
+    upstream_errors: usize,
+}
+
+fn is_server_error(status: u16) -> bool {
+    (500..600).contains(&status)
+}
+
+/// 上游返回 5xx，或未记录 $upstream_status 时网关类错误（502/503/504）视为上游错误
+fn is_upstream_error(record: &AccessLogRecord) -> bool {
+    match &record.upstream_status {
+        Some(statuses) => statuses
+            .split([',', ':'])
+            .filter_map(|status| status.trim().parse::<u16>().ok())
+            .any(is_server_error),
+        None => matches!(record.status, Some(502..=504)),
+    }
+}
+
+fn uses_upstream(record: &AccessLogRecord) -> bool {
+    record.upstream_addr.is_some() || record.upstream_status.is_some() || record.upstream_response_time.is_some()
+}
+
+impl TrafficAccumulator {
+    fn add(&mut self, record: &AccessLogRecord) {
+        self.total_requests += 1;
+
+        let server_error = record.status.is_some_and(is_server_error);
+        if let Some(timestamp) = record.timestamp_ms {
+            let bucket = self
+                .minute_buckets
+                .entry(timestamp.div_euclid(MILLIS_PER_MINUTE) * MILLIS_PER_MINUTE)
+                .or_default();
+            bucket.0 += 1;
+            bucket.1 += usize::from(server_error);
+        }
+
+        if let Some(status) = record.status {
+            *self.status_classes.entry(format!("{}xx", status / 100)).or_default() += 1;
+            *self.status_codes.entry(status).or_default() += 1;
+        }
+
+        if let Some(request_time) = record.request_time {
+            self.request_times.push(request_time);
+        }
+
+        if let Some(uri) = &record.uri {
+            let path = uri.split('?').next().unwrap_or(uri);
+            *self.uris.entry(path.to_string()).or_default() += 1;
+        }
+
+        if let Some(ip) = &record.remote_addr {
+            *self.client_ips.entry(ip.clone()).or_default() += 1;
+        }
+
+        if uses_upstream(record) {
+            self.upstream_requests += 1;
+            self.upstream_errors += usize::from(is_upstream_error(record));
+        }
+    }
+
+    fn finish(mut self, server: Option<&ServerBlock>, window_minutes: u32, top_n: usize) -> ServerTrafficSummary {
+        let span_minutes = if window_minutes > 0 {
+            window_minutes as usize
+        } else {
+            match (self.minute_buckets.keys().next(), self.minute_buckets.keys().next_back()) {
+                (Some(first), Some(last)) => ((last - first) / MILLIS_PER_MINUTE) as usize + 1,
+                _ => 1,
+            }
+        };
+
+        self.request_times.sort_by(|left, right| left.total_cmp(right));
+
+        ServerTrafficSummary {
+            server_id: server.map(|server| server.id.clone()),
+            server_name: server.map(|server| server.server_name.clone()).unwrap_or_default(),
+            listen: server.map(|server| server.listen.clone()).unwrap_or_default(),
+            total_requests: self.total_requests,
+            average_rpm: self.total_requests as f64 / span_minutes as f64,
+            requests_per_minute: self
+                .minute_buckets
+                .into_iter()
+                .map(|(minute_ms, (requests, server_errors))| TrafficMinuteBucket {
+                    minute_ms,
+                    requests,
+                    server_errors,
+                })
+                .collect(),
+            status_classes: self.status_classes,
+            status_codes: self.status_codes,
+            request_time: RequestTimePercentiles {
+                p50: percentile(&self.request_times, 0.50),
+                p95: percentile(&self.request_times, 0.95),
+                p99: percentile(&self.request_times, 0.99),
+            },
+            top_uris: top_items(self.uris, top_n),
+            top_client_ips: top_items(self.client_ips, top_n),
+            upstream_requests: self.upstream_requests,
+            upstream_errors: self.upstream_errors,
+            upstream_error_rate: if self.upstream_requests == 0 {
+                0.0
+            } else {
+                self.upstream_errors as f64 / self.upstream_requests as f64
+            },
+        }
+    }
+}
+
+/// 最近秩法计算百分位，输入需已排序
+fn percentile(sorted: &[f64], ratio: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+
+    let rank = (ratio * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.clamp(1, sorted.len()) - 1).copied()
+}
+
+fn top_items(counts: HashMap<String, usize>, top_n: usize) -> Vec<TrafficTopItem> {
+    let mut items: Vec<TrafficTopItem> = counts
+        .into_iter()
+        .map(|(value, count)| TrafficTopItem { value, count })
+        .collect();
+
+    items.sort_by(|left, right| right.count.cmp(&left.count).then_with(|| left.value.cmp(&right.value)));
+    items.truncate(top_n);
+    items
+}
+
+/// 按 server 块累计访问记录
+struct TrafficAggregator<'a> {
+    servers: &'a [ServerBlock],
+    /// (候选 server, 端口, Host) 到选中 server 的缓存，避免逐行重复编译 server_name 正则
+    selections: HashMap<(Vec<usize>, Option<u16>, String), Option<usize>>,
+    groups: HashMap<Option<usize>, TrafficAccumulator>,
+    window_start_ms: Option<i64>,
+}
+
+impl<'a> TrafficAggregator<'a> {
+    fn new(servers: &'a [ServerBlock], window_start_ms: Option<i64>) -> Self {
+        Self {
+            servers,
+            selections: HashMap::new(),
+            groups: HashMap::new(),
+            window_start_ms,
+        }
+    }
+
+    /// candidates 为写入该日志文件的 server 下标，全局日志为所有 server
+    fn add(&mut self, candidates: &[usize], record: &AccessLogRecord) {
+        if let Some(window_start) = self.window_start_ms {
+            if record.timestamp_ms.is_none_or(|timestamp| timestamp < window_start) {
+                return;
+            }
+        }
+
+        let host = record.host.as_deref();
+        let port = record.fields.get("server_port").and_then(|port| port.parse().ok());
+        let servers = self.servers;
+        let server = *self
+            .selections
+            .entry((candidates.to_vec(), port, host.unwrap_or_default().to_string()))
+            .or_insert_with(|| match_server(servers, candidates, host, port));
+        self.groups.entry(server).or_default().add(record);
+    }
+
+    fn finish(self, window_minutes: u32, top_n: usize) -> Vec<ServerTrafficSummary> {
+        let servers = self.servers;
+        let mut summaries: Vec<ServerTrafficSummary> = self
+            .groups
+            .into_iter()
+            .map(|(index, accumulator)| {
+                accumulator.finish(index.and_then(|index| servers.get(index)), window_minutes, top_n)
+            })
+            .collect();
+
+        // 5xx 最多的站点排在前面，便于定位正在出错的站点
+        summaries.sort_by(|left, right| {
+            let left_errors = left.status_classes.get("5xx").copied().unwrap_or(0);
+            let right_errors = right.status_classes.get("5xx").copied().unwrap_or(0);
+            right_errors
+                .cmp(&left_errors)
+                .then_with(|| right.total_requests.cmp(&left.total_requests))
+        });
+        summaries
+    }
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// 按 server 块汇总访问日志：每分钟请求数、状态码分布、request_time 百分位、
+/// Top URI / 客户端 IP 与上游错误率
+#[tauri::command]
+pub async fn aggregate_nginx_traffic(request: TrafficAggregateRequest) -> Result<TrafficAggregateResult, String> {
+    // 逐行扫描全部访问日志（含 .gz），不能占用异步运行时
+    tauri::async_runtime::spawn_blocking(move || aggregate_traffic(request))
+        .await
+        .map_err(|e| format!("流量汇总任务异常: {}", e))?
+}
+
+fn aggregate_traffic(request: TrafficAggregateRequest) -> Result<TrafficAggregateResult, String> {
+    let config = load_nginx_config(&request.config_path)?;
+    let parser = AccessLogParser::from_config_path(&request.config_path)?;
+    let prefix = resolve_log_prefix(&request.config_path, &request.nginx_path);
+    let servers: Vec<ServerBlock> = config.servers.iter().filter(|server| server.enabled).cloned().collect();
+
+    // 同一日志文件可能被多个 server 共用，按 (路径, 格式) 合并后只读一遍
+    let mut log_files: BTreeMap<(String, String), Vec<usize>> = BTreeMap::new();
+    for source in discover_log_sources(&config, &prefix) {
+        if source.kind != NginxLogKind::Access {
+            continue;
+        }
+
+        let format = source.format.unwrap_or_default();
+        let candidates = log_files.entry((source.path, format)).or_default();
+        match source.server_id {
+            Some(server_id) => {
+                if let Some(index) = servers.iter().position(|server| server.id == server_id) {
+                    candidates.push(index);
+                }
+            }
+            None => candidates.extend(0..servers.len()),
+        }
+    }
+
+    let generated_at = now_timestamp_ms();
+    let window_start_ms = (request.window_minutes > 0)
+        .then(|| generated_at - i64::from(request.window_minutes) * MILLIS_PER_MINUTE);
+    let top_n = if request.top_n == 0 { DEFAULT_TOP_N } else { request.top_n };
+
+    let mut aggregator = TrafficAggregator::new(&servers, window_start_ms);
+    let mut scanned_files = Vec::new();
+    let mut warnings = Vec::new();
+    let mut unmatched_lines = 0;
+
+    for ((path, format_name), mut candidates) in log_files {
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let format: &CompiledLogFormat = match parser.format(&format_name) {
+            Ok(format) => format,
+            Err(error) => {
+                warnings.push(format!("{}: {}", path, error));
+                continue;
+            }
+        };
+
+        for file in list_log_files(Path::new(&path), request.include_rotated) {
+            let result = for_each_log_line(&file, |_, text| {
+                if text.trim().is_empty() {
+                    return;
+                }
+                match format.parse_line(&text) {
+                    Some(record) => aggregator.add(&candidates, &record),
+                    None => unmatched_lines += 1,
+                }
+            });
+
+            match result {
+                Ok(()) => scanned_files.push(file.to_string_lossy().to_string()),
+                Err(error) => warnings.push(format!("读取日志文件失败 {}: {}", file.display(), error)),
+            }
+        }
+    }
+
+    Ok(TrafficAggregateResult {
+        generated_at,
+        window_minutes: request.window_minutes,
+        servers: aggregator.finish(request.window_minutes, top_n),
+        scanned_files,
+        unmatched_lines,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_server(id: &str, names: &[&str]) -> ServerBlock {
+        ServerBlock {
+            id: id.to_string(),
+            enabled: true,
+            category: None,
+            listen: vec!["80".to_string()],
+            server_name: names.iter().map(|name| name.to_string()).collect(),
+            locations: Vec::new(),
+            directives: Vec::new(),
+            raw_content: String::new(),
+            start_line: 0,
+            end_line: 0,
+        }
+    }
+
+    fn create_record(host: &str, status: u16, request_time: f64, uri: &str) -> AccessLogRecord {
+        AccessLogRecord {
+            remote_addr: Some("10.0.0.1".to_string()),
+            timestamp_ms: Some(120_000),
+            host: Some(host.to_string()),
+            uri: Some(uri.to_string()),
+            status: Some(status),
+            request_time: Some(request_time),
+            ..AccessLogRecord::default()
+        }
+    }
+
+    #[test]
+    fn aggregator_should_attribute_shared_log_by_nginx_server_selection() {
+        let mut fallback = create_server("server-3", &["fallback.local"]);
+        fallback.listen = vec!["80 default_server".to_string()];
+        let mut admin = create_server("server-4", &["admin.example.com"]);
+        admin.listen = vec!["8080".to_string()];
+        let servers = vec![
+            create_server("server-1", &["*.example.com"]),
+            create_server("server-2", &["api.example.com"]),
+            fallback,
+            admin,
+        ];
+        let mut aggregator = TrafficAggregator::new(&servers, None);
+
+        aggregator.add(&[0, 1, 2, 3], &create_record("api.example.com:443", 200, 0.1, "/a"));
+        aggregator.add(&[0, 1, 2, 3], &create_record("www.example.com", 200, 0.1, "/b"));
+        aggregator.add(&[0, 1, 2, 3], &create_record("other.local", 200, 0.1, "/c"));
+        let mut on_admin_port = create_record("other.local", 200, 0.1, "/d");
+        on_admin_port.fields.insert("server_port".to_string(), "8080".to_string());
+        aggregator.add(&[0, 1, 2, 3], &on_admin_port);
+        aggregator.add(&[0], &create_record("other.local", 200, 0.1, "/e"));
+        let mut without_host = create_record("", 200, 0.1, "/f");
+        without_host.host = None;
+        aggregator.add(&[0, 1, 2, 3], &without_host);
+
+        let summaries = aggregator.finish(0, 10);
+        let find = |id: Option<&str>| {
+            summaries
+                .iter()
+                .find(|summary| summary.server_id.as_deref() == id)
+                .map(|summary| summary.total_requests)
+        };
+
+        assert_eq!(find(Some("server-1")), Some(2));
+        assert_eq!(find(Some("server-2")), Some(1));
+        assert_eq!(find(Some("server-3")), Some(1));
+        assert_eq!(find(Some("server-4")), Some(1));
+        assert_eq!(find(None), Some(1));
+    }
+
+    #[test]
+    fn accumulator_should_compute_percentiles_status_and_upstream_errors() {
+        let mut accumulator = TrafficAccumulator::default();
+        for index in 1..=100 {
+            let mut record = create_record("api.local", 200, index as f64 / 100.0, "/api?id=1");
+            record.upstream_addr = Some("127.0.0.1:9000".to_string());
+            accumulator.add(&record);
+        }
+        let mut failed = create_record("api.local", 502, 2.0, "/api");
+        failed.upstream_status = Some("502".to_string());
+        accumulator.add(&failed);
+
+        let summary = accumulator.finish(None, 0, 1);
+
+        assert_eq!(summary.total_requests, 101);
+        assert_eq!(summary.status_classes.get("2xx"), Some(&100));
+        assert_eq!(summary.status_codes.get(&502), Some(&1));
+        assert_eq!(summary.request_time.p50, Some(0.51));
+        assert_eq!(summary.request_time.p99, Some(1.0));
+        assert_eq!(summary.top_uris, vec![TrafficTopItem { value: "/api".to_string(), count: 101 }]);
+        assert_eq!(summary.upstream_requests, 101);
+        assert_eq!(summary.upstream_errors, 1);
+        assert_eq!(summary.requests_per_minute[0].server_errors, 1);
+        assert_eq!(summary.average_rpm, 101.0);
+    }
+
+    #[test]
+    fn aggregator_should_skip_records_outside_window() {
+        let servers = vec![create_server("server-1", &["api.local"])];
+        let mut aggregator = TrafficAggregator::new(&servers, Some(60_000));
+
+        aggregator.add(&[0], &create_record("api.local", 200, 0.1, "/"));
+        let mut stale = create_record("api.local", 200, 0.1, "/");
+        stale.timestamp_ms = Some(0);
+        aggregator.add(&[0], &stale);
+
+        let summaries = aggregator.finish(1, 10);
+        assert_eq!(summaries[0].total_requests, 1);
+    }
+}
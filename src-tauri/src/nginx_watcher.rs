@@ -0,0 +1,247 @@
+use crate::audit::{build_system_log_entry, publish_log_entry};
+use crate::nginx::{build_nginx_status, query_nginx_processes, NginxProcessSnapshot, NginxStatus};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::time::sleep;
+
+/// 进程状态变化时推送给前端的事件名
+pub const NGINX_STATUS_CHANGED_EVENT: &str = "nginx-status-changed";
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+/// Windows 下每次采集都要启动 PowerShell，状态稳定时逐步放慢到该间隔
+#[cfg(target_os = "windows")]
+const MAX_WATCH_INTERVAL: Duration = Duration::from_secs(16);
+#[cfg(not(target_os = "windows"))]
+const MAX_WATCH_INTERVAL: Duration = WATCH_INTERVAL;
+/// 应用自身的启停操作结束后，仍视为预期变化的宽限时间
+const EXPECTED_CHANGE_GRACE_MS: i64 = 5000;
+const WATCHER_SOURCE: &str = "nginx_status_watcher";
+
+/// 正在执行中的启停类操作数量
+static MANAGED_OPERATIONS_IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static LAST_MANAGED_OPERATION_END_MS: AtomicI64 = AtomicI64::new(0);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NginxStatusChangeKind {
+    Started,
+    Stopped,
+    /// 未经本应用操作而退出
+    Crashed,
+    /// master 进程 PID 变化
+    Restarted,
+    WorkersChanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NginxStatusChangedEvent {
+    pub kind: NginxStatusChangeKind,
+    /// 是否由本应用的启停操作引起
+    pub expected: bool,
+    pub status: NginxStatus,
+    pub snapshot: NginxProcessSnapshot,
+    pub previous: NginxProcessSnapshot,
+    pub timestamp_ms: i64,
+}
+
+fn now_timestamp_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// 包裹启停、重载、结束进程等操作，期间及结束后短时间内的进程变化不视为意外
+pub async fn managed_operation<F: Future>(operation: F) -> F::Output {
+    MANAGED_OPERATIONS_IN_FLIGHT.fetch_add(1, Ordering::SeqCst);
+    let output = operation.await;
+    LAST_MANAGED_OPERATION_END_MS.store(now_timestamp_ms(), Ordering::SeqCst);
+    MANAGED_OPERATIONS_IN_FLIGHT.fetch_sub(1, Ordering::SeqCst);
+    output
+}
+
+fn is_change_expected(now_ms: i64) -> bool {
+    MANAGED_OPERATIONS_IN_FLIGHT.load(Ordering::SeqCst) > 0
+        || now_ms - LAST_MANAGED_OPERATION_END_MS.load(Ordering::SeqCst) <= EXPECTED_CHANGE_GRACE_MS
+}
+
+/// 比较前后两次快照，得出变化类型
+fn detect_status_change(
+    previous: &NginxProcessSnapshot,
+    current: &NginxProcessSnapshot,
+    expected: bool,
+) -> Option<NginxStatusChangeKind> {
+    match (previous.is_running(), current.is_running()) {
+        (false, true) => Some(NginxStatusChangeKind::Started),
+        (true, false) if expected => Some(NginxStatusChangeKind::Stopped),
+        (true, false) => Some(NginxStatusChangeKind::Crashed),
+        (true, true) => {
+            if previous.master_pid.is_some() && previous.master_pid != current.master_pid {
+                Some(NginxStatusChangeKind::Restarted)
+            } else if previous.worker_pids.len() != current.worker_pids.len() {
+                Some(NginxStatusChangeKind::WorkersChanged)
+            } else {
+                None
+            }
+        }
+        (false, false) => None,
+    }
+}
+
+fn describe_pid(pid: Option<u32>) -> String {
+    pid.map(|pid| pid.to_string()).unwrap_or_else(|| "未知".to_string())
+}
+
+/// 意外的退出与 master 变化需要写入操作日志，返回 (级别, 摘要)
+fn unexpected_change_log(
+    kind: NginxStatusChangeKind,
+    expected: bool,
+    previous: &NginxProcessSnapshot,
+    current: &NginxProcessSnapshot,
+) -> Option<(&'static str, String)> {
+    if expected {
+        return None;
+    }
+
+    match kind {
+        NginxStatusChangeKind::Crashed => Some((
+            "error",
+            format!(
+                "检测到 Nginx 意外退出（master PID {}），请检查错误日志",
+                describe_pid(previous.master_pid)
+            ),
+        )),
+        NginxStatusChangeKind::Restarted => Some((
+            "warning",
+            format!(
+                "检测到 Nginx master 进程意外变化（PID {} -> {}）",
+                describe_pid(previous.master_pid),
+                describe_pid(current.master_pid)
+            ),
+        )),
+        _ => None,
+    }
+}
+
+/// 状态有变化或处于应用自身操作期间时恢复基础间隔，否则加倍直到上限
+fn next_watch_interval(current: Duration, active: bool) -> Duration {
+    if active {
+        WATCH_INTERVAL
+    } else {
+        (current * 2).min(MAX_WATCH_INTERVAL)
+    }
+}
+
+/// 启动后台状态监听：定期采集进程快照，变化时推送 `nginx-status-changed` 事件
+pub fn spawn_status_watcher(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        // 首次成功采集前没有基准，不推送变化
+        let mut previous: Option<NginxProcessSnapshot> = query_nginx_processes().ok();
+        let mut interval = WATCH_INTERVAL;
+
+        loop {
+            sleep(interval).await;
+
+            let timestamp_ms = now_timestamp_ms();
+            let expected = is_change_expected(timestamp_ms);
+            // 采集失败（如 ps / PowerShell 临时出错）时跳过本轮，不能当作进程已退出
+            let Ok(current) = query_nginx_processes() else {
+                interval = next_watch_interval(interval, expected);
+                continue;
+            };
+            let Some(last) = previous.replace(current.clone()) else {
+                continue;
+            };
+
+            let change = detect_status_change(&last, &current, expected);
+            interval = next_watch_interval(interval, expected || change.is_some());
+
+            if let Some(kind) = change {
+                if let Some((level, summary)) = unexpected_change_log(kind, expected, &last, &current) {
+                    publish_log_entry(&app, build_system_log_entry(WATCHER_SOURCE, level, summary));
+                }
+
+                let _ = app.emit(
+                    NGINX_STATUS_CHANGED_EVENT,
+                    NginxStatusChangedEvent {
+                        kind,
+                        expected,
+                        status: build_nginx_status(&current),
+                        snapshot: current,
+                        previous: last,
+                        timestamp_ms,
+                    },
+                );
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(master_pid: Option<u32>, worker_pids: &[u32]) -> NginxProcessSnapshot {
+        NginxProcessSnapshot {
+            master_pid,
+            worker_pids: worker_pids.to_vec(),
+            process_count: master_pid.map(|_| 1).unwrap_or(0) + worker_pids.len() as u32,
+        }
+    }
+
+    #[test]
+    fn detect_status_change_should_distinguish_stop_and_crash() {
+        let running = snapshot(Some(100), &[101, 102]);
+        let stopped = snapshot(None, &[]);
+
+        assert_eq!(
+            detect_status_change(&stopped, &running, false),
+            Some(NginxStatusChangeKind::Started)
+        );
+        assert_eq!(
+            detect_status_change(&running, &stopped, true),
+            Some(NginxStatusChangeKind::Stopped)
+        );
+        assert_eq!(
+            detect_status_change(&running, &stopped, false),
+            Some(NginxStatusChangeKind::Crashed)
+        );
+        assert_eq!(
+            detect_status_change(&running, &snapshot(Some(200), &[201, 202]), false),
+            Some(NginxStatusChangeKind::Restarted)
+        );
+        assert_eq!(
+            detect_status_change(&running, &snapshot(Some(100), &[103]), true),
+            Some(NginxStatusChangeKind::WorkersChanged)
+        );
+        assert_eq!(detect_status_change(&running, &running, false), None);
+    }
+
+    #[test]
+    fn next_watch_interval_should_back_off_until_activity() {
+        let mut interval = WATCH_INTERVAL;
+        for _ in 0..10 {
+            interval = next_watch_interval(interval, false);
+        }
+
+        assert_eq!(interval, MAX_WATCH_INTERVAL);
+        assert_eq!(next_watch_interval(interval, true), WATCH_INTERVAL);
+    }
+
+    #[test]
+    fn unexpected_change_log_should_only_record_unexpected_exits() {
+        let running = snapshot(Some(100), &[101]);
+        let stopped = snapshot(None, &[]);
+
+        let crashed = unexpected_change_log(NginxStatusChangeKind::Crashed, false, &running, &stopped)
+            .expect("crash log");
+        assert_eq!(crashed.0, "error");
+        assert!(crashed.1.contains("100"));
+        assert!(unexpected_change_log(NginxStatusChangeKind::Stopped, true, &running, &stopped).is_none());
+        assert!(unexpected_change_log(NginxStatusChangeKind::Started, false, &stopped, &running).is_none());
+    }
+}
@@ -0,0 +1,435 @@
+use crate::config::parse_nginx_config;
+use crate::config_drift::{applied_config_file_set, read_config_file_set};
+use crate::nginx::{inspect_ports, query_nginx_processes, PortProcessInfo};
+use crate::settings::read_app_settings;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::ErrorKind;
+use std::net::{TcpListener, ToSocketAddrs, UdpSocket};
+
+const DEFAULT_LISTEN_PORT: u16 = 80;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ListenProtocol {
+    Tcp,
+    Udp,
+}
+
+/// 解析后的 listen 地址，host 为 None 表示监听全部地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ListenAddress {
+    pub host: Option<String>,
+    pub port: u16,
+    pub protocol: ListenProtocol,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PortPreflightStatus {
+    Free,
+    /// 由运行中的 Nginx 自身占用，重载时可继续使用
+    OwnedByNginx,
+    Conflict,
+    /// 无法判断，例如非特权用户无法探测 1024 以下端口
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortPreflightEntry {
+    pub port: u16,
+    pub protocol: ListenProtocol,
+    /// 配置中的原始 listen 值
+    pub listen: Vec<String>,
+    /// 使用该端口的 server，形如 `example.com (conf.d/site.conf:12)`
+    pub servers: Vec<String>,
+    pub status: PortPreflightStatus,
+    /// 占用端口的外部进程，仅在平台支持端口查询时提供
+    pub owners: Vec<PortProcessInfo>,
+    pub can_release: bool,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortPreflightReport {
+    pub ok: bool,
+    pub config_path: String,
+    pub nginx_running: bool,
+    pub entries: Vec<PortPreflightEntry>,
+    pub conflicts: Vec<u16>,
+    pub parse_errors: Vec<String>,
+    pub message: String,
+}
+
+/// 解析 listen 指令的值，例如 `80`、`127.0.0.1:8080`、`[::]:443 ssl`、`53 udp`；unix socket 返回 None
+pub(crate) fn parse_listen_address(value: &str) -> Option<ListenAddress> {
+    let mut parts = value.split_whitespace();
+    let address = parts.next()?;
+    let params: Vec<&str> = parts.collect();
+    if address.starts_with("unix:") {
+        return None;
+    }
+
+    let protocol = if params.iter().any(|param| *param == "udp" || *param == "quic") {
+        ListenProtocol::Udp
+    } else {
+        ListenProtocol::Tcp
+    };
+
+    let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']')?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => DEFAULT_LISTEN_PORT,
+        };
+        (Some(host.to_string()), port)
+    } else if let Some((host, port)) = address.rsplit_once(':') {
+        (Some(host.to_string()), port.parse().ok()?)
+    } else if address.chars().all(|c| c.is_ascii_digit()) {
+        (None, address.parse().ok()?)
+    } else {
+        (Some(address.to_string()), DEFAULT_LISTEN_PORT)
+    };
+
+    let host = host.filter(|host| !matches!(host.as_str(), "*" | "0.0.0.0" | "::"));
+    Some(ListenAddress { host, port, protocol })
+}
+
+struct ListenUsage {
+    listen: BTreeSet<String>,
+    hosts: BTreeSet<Option<String>>,
+    servers: Vec<String>,
+}
+
+fn server_label(file_path: &str, server_name: &[String], start_line: usize) -> String {
+    let name = server_name.first().map(String::as_str).unwrap_or("_");
+    format!("{} ({}:{})", name, file_path, start_line)
+}
+
+/// 汇总所有已启用 server（含 stream 中的 server）的 listen 端口
+fn collect_listen_usage(
+    files: &BTreeMap<String, String>,
+) -> (BTreeMap<(u16, ListenProtocol), ListenUsage>, Vec<String>) {
+    let mut usage: BTreeMap<(u16, ListenProtocol), ListenUsage> = BTreeMap::new();
+    let mut errors = Vec::new();
+
+    for (file_path, content) in files {
+        let config = match parse_nginx_config(content, file_path) {
+            Ok(config) => config,
+            Err(e) => {
+                errors.push(format!("{}: {}", file_path, e));
+                continue;
+            }
+        };
+
+        for server in config.servers.iter().filter(|server| server.enabled) {
+            let label = server_label(file_path, &server.server_name, server.start_line);
+            for value in &server.listen {
+                let Some(address) = parse_listen_address(value) else {
+                    continue;
+                };
+                let entry = usage
+                    .entry((address.port, address.protocol))
+                    .or_insert_with(|| ListenUsage {
+                        listen: BTreeSet::new(),
+                        hosts: BTreeSet::new(),
+                        servers: Vec::new(),
+                    });
+                entry.listen.insert(value.trim().to_string());
+                entry.hosts.insert(address.host);
+                if !entry.servers.contains(&label) {
+                    entry.servers.push(label.clone());
+                }
+            }
+        }
+    }
+
+    (usage, errors)
+}
+
+/// 通过尝试绑定判断端口是否被占用，返回 None 表示无法判断
+fn probe_port_in_use(host: Option<&str>, port: u16, protocol: ListenProtocol) -> Option<bool> {
+    let host = host.unwrap_or("0.0.0.0");
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let address = (host, port).to_socket_addrs().ok()?.next()?;
+
+    let result = match protocol {
+        ListenProtocol::Tcp => TcpListener::bind(address).map(drop),
+        ListenProtocol::Udp => UdpSocket::bind(address).map(drop),
+    };
+
+    match result {
+        Ok(()) => Some(false),
+        Err(e) if e.kind() == ErrorKind::AddrInUse => Some(true),
+        Err(_) => None,
+    }
+}
+
+fn classify_entry(
+    port: u16,
+    protocol: ListenProtocol,
+    usage: ListenUsage,
+    applied_ports: &HashSet<(u16, ListenProtocol)>,
+    inspected_owners: Option<&Vec<PortProcessInfo>>,
+    nginx_pids: &HashSet<u32>,
+    nginx_running: bool,
+) -> PortPreflightEntry {
+    let owned_by_nginx = applied_ports.contains(&(port, protocol));
+    let foreign_owners: Vec<PortProcessInfo> = inspected_owners
+        .map(|owners| {
+            owners
+                .iter()
+                .filter(|owner| !nginx_pids.contains(&owner.pid))
+                .filter(|owner| {
+                    let is_udp = owner.protocol.eq_ignore_ascii_case("udp");
+                    is_udp == (protocol == ListenProtocol::Udp)
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let (status, message) = if let Some(owners) = inspected_owners {
+        if !foreign_owners.is_empty() {
+            let names: Vec<String> = foreign_owners
+                .iter()
+                .map(|owner| format!("{} (PID {})", owner.process_name, owner.pid))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            (
+                PortPreflightStatus::Conflict,
+                format!("端口 {} 已被 {} 占用", port, names.join("、")),
+            )
+        } else if !owners.is_empty() {
+            (PortPreflightStatus::OwnedByNginx, format!("端口 {} 由运行中的 Nginx 监听", port))
+        } else {
+            (PortPreflightStatus::Free, format!("端口 {} 可用", port))
+        }
+    } else {
+        let probes: Vec<Option<bool>> = usage
+            .hosts
+            .iter()
+            .map(|host| probe_port_in_use(host.as_deref(), port, protocol))
+            .collect();
+
+        if probes.iter().all(|probe| *probe == Some(false)) {
+            (PortPreflightStatus::Free, format!("端口 {} 可用", port))
+        } else if probes.contains(&Some(true)) && owned_by_nginx {
+            (PortPreflightStatus::OwnedByNginx, format!("端口 {} 由运行中的 Nginx 监听", port))
+        } else if probes.contains(&Some(true)) && nginx_running {
+            // 无法获取占用进程时，Nginx 运行中（可能由系统服务或命令行启动）不能断定为冲突
+            (
+                PortPreflightStatus::Unknown,
+                format!("端口 {} 已被占用，Nginx 正在运行但当前平台无法确认占用进程", port),
+            )
+        } else if probes.contains(&Some(true)) {
+            (
+                PortPreflightStatus::Conflict,
+                format!("端口 {} 已被其他进程占用（当前平台无法获取占用进程）", port),
+            )
+        } else {
+            (
+                PortPreflightStatus::Unknown,
+                format!("无法探测端口 {}，可能需要更高权限", port),
+            )
+        }
+    };
+
+    PortPreflightEntry {
+        port,
+        protocol,
+        listen: usage.listen.into_iter().collect(),
+        servers: usage.servers,
+        can_release: status == PortPreflightStatus::Conflict && !foreign_owners.is_empty(),
+        status,
+        owners: foreign_owners,
+        message,
+    }
+}
+
+/// 启动或重载前检查配置中的全部监听端口
+pub(crate) async fn run_port_preflight(config_path: &str) -> PortPreflightReport {
+    let files = read_config_file_set(config_path);
+    let (usage, parse_errors) = collect_listen_usage(&files);
+
+    // 查询失败时按未运行处理，只影响占用者是否识别为 Nginx 自身
+    let snapshot = query_nginx_processes().unwrap_or_default();
+    let nginx_running = snapshot.is_running();
+    let nginx_pids: HashSet<u32> = snapshot
+        .master_pid
+        .into_iter()
+        .chain(snapshot.worker_pids.iter().copied())
+        .collect();
+
+    // 运行中配置已监听的端口视为 Nginx 自身占用
+    let applied_ports: HashSet<(u16, ListenProtocol)> = if nginx_running {
+        applied_config_file_set(config_path)
+            .map(|applied| collect_listen_usage(&applied).0.into_keys().collect())
+            .unwrap_or_default()
+    } else {
+        HashSet::new()
+    };
+
+    let ports: Vec<u16> = usage.keys().map(|(port, _)| *port).collect::<BTreeSet<_>>().into_iter().collect();
+    let inspections = if ports.is_empty() {
+        None
+    } else {
+        inspect_ports(ports).await.ok()
+    };
+
+    let entries: Vec<PortPreflightEntry> = usage
+        .into_iter()
+        .map(|((port, protocol), usage)| {
+            let owners = inspections
+                .as_ref()
+                .and_then(|results| results.iter().find(|result| result.port == port))
+                .map(|result| &result.entries);
+            classify_entry(port, protocol, usage, &applied_ports, owners, &nginx_pids, nginx_running)
+        })
+        .collect();
+
+    let conflicts: Vec<u16> = entries
+        .iter()
+        .filter(|entry| entry.status == PortPreflightStatus::Conflict)
+        .map(|entry| entry.port)
+        .collect();
+
+    let message = if conflicts.is_empty() {
+        format!("端口预检通过，共检查 {} 个监听端口", entries.len())
+    } else {
+        let details: Vec<String> = entries
+            .iter()
+            .filter(|entry| entry.status == PortPreflightStatus::Conflict)
+            .map(|entry| format!("- {}，使用方: {}", entry.message, entry.servers.join("、")))
+            .collect();
+        format!(
+            "✗ 端口预检发现 {} 个冲突:\n{}\n\n可在进程管理中释放端口后重试",
+            conflicts.len(),
+            details.join("\n")
+        )
+    };
+
+    PortPreflightReport {
+        ok: conflicts.is_empty(),
+        config_path: config_path.to_string(),
+        nginx_running,
+        entries,
+        conflicts,
+        parse_errors,
+        message,
+    }
+}
+
+/// 对即将加载的配置文件执行预检，存在冲突时返回预检报告
+pub(crate) async fn find_port_conflicts(config_path: &str) -> Option<PortPreflightReport> {
+    if config_path.trim().is_empty() {
+        return None;
+    }
+
+    let report = run_port_preflight(config_path).await;
+    (!report.ok).then_some(report)
+}
+
+/// 端口预检：列出配置中的监听端口及占用情况
+#[tauri::command]
+pub async fn preflight_nginx_ports(config_path: Option<String>) -> Result<PortPreflightReport, String> {
+    let config_path = match config_path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => path,
+        None => read_app_settings()?.config_path,
+    };
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+
+    Ok(run_port_preflight(&config_path).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_listen_address_should_handle_common_forms() {
+        let parse = |value: &str| parse_listen_address(value).map(|address| (address.host, address.port, address.protocol));
+
+        assert_eq!(parse("80"), Some((None, 80, ListenProtocol::Tcp)));
+        assert_eq!(parse("443 ssl http2"), Some((None, 443, ListenProtocol::Tcp)));
+        assert_eq!(
+            parse("127.0.0.1:8080 default_server"),
+            Some((Some("127.0.0.1".to_string()), 8080, ListenProtocol::Tcp))
+        );
+        assert_eq!(parse("[::]:443 ssl"), Some((None, 443, ListenProtocol::Tcp)));
+        assert_eq!(parse("[::1]:8443 quic"), Some((Some("::1".to_string()), 8443, ListenProtocol::Udp)));
+        assert_eq!(parse("*:80"), Some((None, 80, ListenProtocol::Tcp)));
+        assert_eq!(parse("localhost"), Some((Some("localhost".to_string()), 80, ListenProtocol::Tcp)));
+        assert_eq!(parse("53 udp reuseport"), Some((None, 53, ListenProtocol::Udp)));
+        assert_eq!(parse("unix:/var/run/nginx.sock"), None);
+    }
+
+    #[test]
+    fn preflight_should_report_foreign_owner_as_conflict() {
+        let files: BTreeMap<String, String> = [(
+            "nginx.conf".to_string(),
+            "http {\n    server {\n        listen 8080;\n        server_name a.test;\n    }\n}\nstream {\n    server {\n        listen 5353 udp;\n    }\n}\n"
+                .to_string(),
+        )]
+        .into_iter()
+        .collect();
+        let (mut usage, errors) = collect_listen_usage(&files);
+        assert!(errors.is_empty());
+        assert!(usage.contains_key(&(5353, ListenProtocol::Udp)));
+
+        let owner = PortProcessInfo {
+            protocol: "TCP".to_string(),
+            local_address: "0.0.0.0".to_string(),
+            local_port: 8080,
+            pid: 4321,
+            process_name: "java".to_string(),
+            executable_path: None,
+            command_line: None,
+            user: None,
+            status: "Listen".to_string(),
+            start_time: None,
+        };
+        let owners = vec![owner];
+        let usage_8080 = usage.remove(&(8080, ListenProtocol::Tcp)).unwrap();
+
+        let entry = classify_entry(8080, ListenProtocol::Tcp, usage_8080, &HashSet::new(), Some(&owners), &HashSet::new(), false);
+        assert_eq!(entry.status, PortPreflightStatus::Conflict);
+        assert!(entry.can_release);
+        assert!(entry.message.contains("java (PID 4321)"));
+        assert_eq!(entry.servers, vec!["a.test (nginx.conf:2)"]);
+
+        let (mut usage, _) = collect_listen_usage(&files);
+        let own = classify_entry(
+            8080,
+            ListenProtocol::Tcp,
+            usage.remove(&(8080, ListenProtocol::Tcp)).unwrap(),
+            &HashSet::new(),
+            Some(&owners),
+            &[4321].into_iter().collect(),
+            true,
+        );
+        assert_eq!(own.status, PortPreflightStatus::OwnedByNginx);
+    }
+
+    #[test]
+    fn preflight_should_not_flag_unresolved_owner_while_nginx_runs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let content = format!("http {{\n    server {{\n        listen 127.0.0.1:{};\n    }}\n}}\n", port);
+        let files: BTreeMap<String, String> = [("nginx.conf".to_string(), content)].into_iter().collect();
+        let classify = |nginx_running: bool| {
+            let (mut usage, _) = collect_listen_usage(&files);
+            let usage = usage.remove(&(port, ListenProtocol::Tcp)).unwrap();
+            classify_entry(port, ListenProtocol::Tcp, usage, &HashSet::new(), None, &HashSet::new(), nginx_running).status
+        };
+
+        assert_eq!(classify(true), PortPreflightStatus::Unknown);
+        assert_eq!(classify(false), PortPreflightStatus::Conflict);
+        drop(listener);
+    }
+}
@@ -0,0 +1,463 @@
+use crate::audit::audited;
+use crate::change_plan::{ensure_plan_valid, summarize_changes, validate_changes, ChangePlan};
+use crate::config::{
+    find_http_block, parse_nginx_config, write_config_file, DirectiveInput, EditResult, LocationBlockInput, ServerBlock,
+};
+use crate::config_text::{apply_edits, indent_of, render_location_block, TextEdit, INDENT_UNIT};
+use crate::config_watcher::{main_config_for, normalize_watch_path, resolve_config_files};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use tauri::AppHandle;
+
+/// WebSocket 未指定读取超时时使用的默认值，避免空闲连接在 60 秒后被断开
+const WEBSOCKET_READ_TIMEOUT: &str = "3600s";
+
+/// 反向代理向导的输入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyWizardRequest {
+    pub path: String,
+    #[serde(default)]
+    pub modifier: Option<String>,
+    /// 目标地址，例如 http://127.0.0.1:3000、https://api.example.com/v1/ 或 upstream 名称
+    pub target: String,
+    /// 转发时去掉 location 前缀（proxy_pass 带 URI）
+    #[serde(default)]
+    pub strip_prefix: bool,
+    #[serde(default)]
+    pub websocket: bool,
+    /// None 表示沿用 nginx 默认值
+    #[serde(default)]
+    pub buffering: Option<bool>,
+    #[serde(default)]
+    pub request_buffering: Option<bool>,
+    #[serde(default)]
+    pub client_max_body_size: Option<String>,
+    #[serde(default)]
+    pub connect_timeout: Option<String>,
+    #[serde(default)]
+    pub send_timeout: Option<String>,
+    #[serde(default)]
+    pub read_timeout: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyWizardResult {
+    pub location: LocationBlockInput,
+    /// 需要 http 级的 map $http_upgrade $connection_upgrade
+    pub requires_upgrade_map: bool,
+    /// 向导对输入所做的调整说明
+    pub notes: Vec<String>,
+}
+
+/// 添加反向代理 location 的变更计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProxyLocationPlan {
+    pub plan: ChangePlan,
+    /// server 位于 include 文件且主配置缺少 map $http_upgrade 时，对主配置的修改
+    pub upgrade_map_plan: Option<ChangePlan>,
+}
+
+fn size_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^\d+[kKmMgG]?$").expect("valid size regex"))
+}
+
+fn time_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^\d+(ms|s|m|h|d)?$").expect("valid time regex"))
+}
+
+fn upgrade_map_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^map\s+\$http_upgrade\s+\$connection_upgrade\s*\{").expect("valid map regex"))
+}
+
+fn directive(name: &str, value: &str) -> DirectiveInput {
+    DirectiveInput {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn optional_value<'a>(value: &'a Option<String>, label: &str, regex: &Regex) -> Result<Option<&'a str>, String> {
+    match value.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) if !regex.is_match(value) => Err(format!("{} 的值无效：{}", label, value)),
+        other => Ok(other),
+    }
+}
+
+fn on_off(flag: bool) -> &'static str {
+    if flag {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// 根据 location 类型与是否去除前缀计算 proxy_pass 的值，必要时调整 location 路径
+fn resolve_proxy_pass(request: &ProxyWizardRequest, notes: &mut Vec<String>) -> Result<(String, String), String> {
+    let target = request.target.trim();
+    if target.is_empty() {
+        return Err("目标地址不能为空".to_string());
+    }
+    if target.contains(|c: char| c.is_whitespace() || matches!(c, ';' | '{' | '}')) {
+        return Err(format!("目标地址无效：{}", target));
+    }
+
+    let mut path = request.path.trim().to_string();
+    if path.is_empty() {
+        return Err("location 路径不能为空".to_string());
+    }
+
+    let (scheme, rest) = target.split_once("://").unwrap_or(("http", target));
+    if !matches!(scheme, "http" | "https") {
+        return Err(format!("不支持的协议：{}", scheme));
+    }
+    // 含变量或 unix socket 的地址 nginx 不做 URI 替换，原样使用
+    if target.contains('$') || rest.starts_with("unix:") {
+        if target.contains('$') {
+            notes.push("目标地址包含变量，nginx 不会按 location 替换请求 URI".to_string());
+        }
+        return Ok((path, format!("{}://{}", scheme, rest)));
+    }
+
+    let (authority, base) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    if authority.is_empty() {
+        return Err(format!("目标地址缺少主机：{}", target));
+    }
+    let origin = format!("{}://{}", scheme, authority);
+
+    let prefix_location = matches!(request.modifier.as_deref(), None | Some("^~"));
+    let exact_location = request.modifier.as_deref() == Some("=");
+    if !prefix_location && !exact_location {
+        if request.strip_prefix {
+            return Err("正则 location 不支持去除前缀转发".to_string());
+        }
+        if base.len() > 1 {
+            return Err("正则 location 的 proxy_pass 不能包含路径".to_string());
+        }
+        return Ok((path, origin));
+    }
+
+    if !request.strip_prefix {
+        if base.len() > 1 {
+            return Err("保留原始路径转发时目标地址不能包含路径，如需改写路径请启用去除前缀".to_string());
+        }
+        return Ok((path, origin));
+    }
+
+    let mut uri = if base.is_empty() { "/".to_string() } else { base.to_string() };
+    if prefix_location {
+        // 前缀与 URI 的结尾斜杠必须一致，否则 /api/users 会被转发为 /users 以外的路径
+        if !path.ends_with('/') {
+            path.push('/');
+            notes.push(format!("location 路径已调整为 {}，以便与 proxy_pass 的 URI 对齐", path));
+        }
+        if !uri.ends_with('/') {
+            uri.push('/');
+            notes.push(format!("proxy_pass URI 已补齐结尾斜杠：{}", uri));
+        }
+    }
+    Ok((path, format!("{}{}", origin, uri)))
+}
+
+/// 根据向导输入生成 location
+pub(crate) fn build_proxy_location_input(request: &ProxyWizardRequest) -> Result<ProxyWizardResult, String> {
+    let mut notes = Vec::new();
+    let (path, proxy_pass) = resolve_proxy_pass(request, &mut notes)?;
+
+    let body_size = optional_value(&request.client_max_body_size, "client_max_body_size", size_regex())?;
+    let connect_timeout = optional_value(&request.connect_timeout, "proxy_connect_timeout", time_regex())?;
+    let send_timeout = optional_value(&request.send_timeout, "proxy_send_timeout", time_regex())?;
+    let read_timeout = optional_value(&request.read_timeout, "proxy_read_timeout", time_regex())?
+        .or(request.websocket.then_some(WEBSOCKET_READ_TIMEOUT));
+
+    let mut directives = vec![
+        directive("proxy_pass", &proxy_pass),
+        directive("proxy_http_version", "1.1"),
+        directive("proxy_set_header", "Host $host"),
+        directive("proxy_set_header", "X-Real-IP $remote_addr"),
+        directive("proxy_set_header", "X-Forwarded-For $proxy_add_x_forwarded_for"),
+        directive("proxy_set_header", "X-Forwarded-Proto $scheme"),
+    ];
+    if request.websocket {
+        directives.push(directive("proxy_set_header", "Upgrade $http_upgrade"));
+        directives.push(directive("proxy_set_header", "Connection $connection_upgrade"));
+    } else {
+        // 清空 Connection 以便复用上游长连接
+        directives.push(directive("proxy_set_header", "Connection \"\""));
+    }
+    if let Some(buffering) = request.buffering {
+        directives.push(directive("proxy_buffering", on_off(buffering)));
+    }
+    if let Some(buffering) = request.request_buffering {
+        directives.push(directive("proxy_request_buffering", on_off(buffering)));
+    }
+    if let Some(size) = body_size {
+        directives.push(directive("client_max_body_size", size));
+    }
+    if let Some(timeout) = connect_timeout {
+        directives.push(directive("proxy_connect_timeout", timeout));
+    }
+    if let Some(timeout) = send_timeout {
+        directives.push(directive("proxy_send_timeout", timeout));
+    }
+    if let Some(timeout) = read_timeout {
+        directives.push(directive("proxy_read_timeout", timeout));
+    }
+
+    Ok(ProxyWizardResult {
+        location: LocationBlockInput {
+            path,
+            modifier: request.modifier.clone().filter(|modifier| !modifier.trim().is_empty()),
+            directives,
+        },
+        requires_upgrade_map: request.websocket,
+        notes,
+    })
+}
+
+fn has_upgrade_map(content: &str) -> bool {
+    content.lines().any(|line| upgrade_map_regex().is_match(line.trim()))
+}
+
+/// 主配置及其 include 文件中是否已定义 map $http_upgrade，edited_path 使用尚未写入的 edited_content
+fn config_has_upgrade_map(main_config: &str, edited_path: &str, edited_content: &str) -> bool {
+    let edited = normalize_watch_path(Path::new(edited_path));
+    resolve_config_files(main_config).iter().any(|path| {
+        if normalize_watch_path(path) == edited {
+            has_upgrade_map(edited_content)
+        } else {
+            fs::read_to_string(path).is_ok_and(|content| has_upgrade_map(&content))
+        }
+    })
+}
+
+/// 将向导生成的 location 插入 server 末尾
+pub(crate) fn insert_proxy_location(content: &str, server: &ServerBlock, result: &ProxyWizardResult) -> Result<String, String> {
+    if !server.enabled {
+        return Err("Server 块已禁用，请先启用后再添加 location".to_string());
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    if server.end_line < 2 || server.end_line > lines.len() {
+        return Err("Server 块位置无效".to_string());
+    }
+
+    let indent = format!("{}{}", indent_of(lines[server.start_line - 1]), INDENT_UNIT);
+    let location = format!("\n{}", render_location_block(&indent, &result.location));
+    Ok(apply_edits(&lines, &[TextEdit::InsertAfter(server.end_line - 2, location)]))
+}
+
+/// 在主配置 http 块开头补充 WebSocket 所需的 map，已存在时原样返回
+pub(crate) fn insert_upgrade_map(content: &str) -> Result<String, String> {
+    if has_upgrade_map(content) {
+        return Ok(content.to_string());
+    }
+    let lines: Vec<&str> = content.lines().collect();
+    let (http_start, _) = find_http_block(&lines).map_err(|e| format!("无法添加 map $http_upgrade: {}", e))?;
+    let map_indent = format!("{}{}", indent_of(lines[http_start]), INDENT_UNIT);
+    let map_block = [
+        format!("{}map $http_upgrade $connection_upgrade {{", map_indent),
+        format!("{}{}default upgrade;", map_indent, INDENT_UNIT),
+        format!("{}{}''      close;", map_indent, INDENT_UNIT),
+        format!("{}}}", map_indent),
+        String::new(),
+    ];
+    Ok(apply_edits(&lines, &[TextEdit::InsertAfter(http_start, map_block.join("\n"))]))
+}
+
+/// 生成添加 location 的变更计划；缺少 map 时写入主配置的 http 块，server 位于 include 文件时另附主配置的计划，
+/// 两处修改一并校验
+async fn plan_proxy_location(
+    config_path: &str,
+    server_id: &str,
+    request: &ProxyWizardRequest,
+) -> Result<ProxyLocationPlan, String> {
+    let result = build_proxy_location_input(request)?;
+    let content = fs::read_to_string(config_path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let config = parse_nginx_config(&content, config_path).map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let server = config
+        .servers
+        .iter()
+        .find(|server| server.id == server_id)
+        .ok_or_else(|| format!("未找到 ID 为 {} 的 Server 块", server_id))?;
+
+    let mut new_content = insert_proxy_location(&content, server, &result)?;
+    let main_config = main_config_for(config_path);
+    let mut upgrade_map_plan = None;
+    if result.requires_upgrade_map && !config_has_upgrade_map(&main_config, config_path, &new_content) {
+        if normalize_watch_path(Path::new(&main_config)) == normalize_watch_path(Path::new(config_path)) {
+            new_content = insert_upgrade_map(&new_content)?;
+        } else {
+            let before = fs::read_to_string(&main_config).map_err(|e| format!("读取主配置文件失败: {}", e))?;
+            let after = insert_upgrade_map(&before)?;
+            upgrade_map_plan = Some(summarize_changes(&main_config, &before, after)?);
+        }
+    }
+
+    let mut plan = summarize_changes(config_path, &content, new_content)?;
+    let validation = {
+        let mut changes = vec![(config_path, plan.new_content.as_str())];
+        if let Some(map_plan) = &upgrade_map_plan {
+            changes.push((map_plan.config_path.as_str(), map_plan.new_content.as_str()));
+        }
+        validate_changes(&changes).await
+    };
+    if let Some(map_plan) = upgrade_map_plan.as_mut() {
+        map_plan.validation = validation.clone();
+    }
+    plan.validation = validation;
+
+    Ok(ProxyLocationPlan { plan, upgrade_map_plan })
+}
+
+/// 根据向导输入生成 location，不修改配置文件
+#[tauri::command]
+pub async fn build_proxy_location(request: ProxyWizardRequest) -> Result<ProxyWizardResult, String> {
+    build_proxy_location_input(&request)
+}
+
+/// 生成添加反向代理 location 的变更计划（含校验结果），不修改配置文件
+#[tauri::command]
+pub async fn generate_proxy_location_content(
+    config_path: String,
+    server_id: String,
+    request: ProxyWizardRequest,
+) -> Result<ProxyLocationPlan, String> {
+    plan_proxy_location(&config_path, &server_id, &request).await
+}
+
+/// 添加反向代理 location 并写入配置文件
+#[tauri::command]
+pub async fn add_proxy_location(
+    app: AppHandle,
+    config_path: String,
+    server_id: String,
+    request: ProxyWizardRequest,
+) -> Result<EditResult, String> {
+    let label = format!("Server 块 {} 的反向代理 Location 添加", server_id);
+    audited(&app, "add_proxy_location", &label, add_proxy_location_internal(config_path, server_id, request)).await
+}
+
+async fn add_proxy_location_internal(
+    config_path: String,
+    server_id: String,
+    request: ProxyWizardRequest,
+) -> Result<EditResult, String> {
+    let plans = plan_proxy_location(&config_path, &server_id, &request).await?;
+    ensure_plan_valid(&plans.plan.validation)?;
+    if let Some(map_plan) = &plans.upgrade_map_plan {
+        write_config_file(&map_plan.config_path, &map_plan.new_content)?;
+    }
+    write_config_file(&config_path, &plans.plan.new_content)?;
+
+    let message = match &plans.upgrade_map_plan {
+        Some(map_plan) => format!("反向代理 Location 添加成功，已在 {} 中补充 map $http_upgrade", map_plan.config_path),
+        None => "反向代理 Location 添加成功".to_string(),
+    };
+    Ok(EditResult { success: true, message })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str, modifier: Option<&str>, target: &str, strip_prefix: bool) -> ProxyWizardRequest {
+        ProxyWizardRequest {
+            path: path.to_string(),
+            modifier: modifier.map(str::to_string),
+            target: target.to_string(),
+            strip_prefix,
+            websocket: false,
+            buffering: None,
+            request_buffering: None,
+            client_max_body_size: None,
+            connect_timeout: None,
+            send_timeout: None,
+            read_timeout: None,
+        }
+    }
+
+    fn proxy_pass(result: &ProxyWizardResult) -> &str {
+        &result.location.directives[0].value
+    }
+
+    #[test]
+    fn build_should_follow_proxy_pass_slash_semantics() {
+        let kept = build_proxy_location_input(&request("/api/", None, "http://127.0.0.1:3000/", false)).unwrap();
+        assert_eq!(proxy_pass(&kept), "http://127.0.0.1:3000");
+
+        let stripped = build_proxy_location_input(&request("/api", None, "backend", true)).unwrap();
+        assert_eq!(stripped.location.path, "/api/");
+        assert_eq!(proxy_pass(&stripped), "http://backend/");
+        assert_eq!(stripped.notes.len(), 1);
+
+        let rewritten = build_proxy_location_input(&request("/v1/", None, "https://api.test/internal", true)).unwrap();
+        assert_eq!(proxy_pass(&rewritten), "https://api.test/internal/");
+
+        assert!(build_proxy_location_input(&request("/api/", None, "http://b/internal/", false)).is_err());
+        assert!(build_proxy_location_input(&request("\\.php$", Some("~"), "http://b", true)).is_err());
+        let regex = build_proxy_location_input(&request("\\.php$", Some("~"), "http://b/", false)).unwrap();
+        assert_eq!(proxy_pass(&regex), "http://b");
+
+        let mut invalid = request("/", None, "http://b", false);
+        invalid.read_timeout = Some("1 minute".to_string());
+        assert!(build_proxy_location_input(&invalid).is_err());
+    }
+
+    #[test]
+    fn insert_should_add_location_and_upgrade_map_once() {
+        let content = "events {}\nhttp {\n    server {\n        listen 80;\n        server_name app.local;\n    }\n}\n";
+        let config = parse_nginx_config(content, "").unwrap();
+        let mut ws = request("/ws/", None, "http://127.0.0.1:3000", false);
+        ws.websocket = true;
+        ws.client_max_body_size = Some("20m".to_string());
+        let result = build_proxy_location_input(&ws).unwrap();
+
+        let updated = insert_upgrade_map(&insert_proxy_location(content, &config.servers[0], &result).unwrap()).unwrap();
+        assert!(updated.contains(
+            "http {\n    map $http_upgrade $connection_upgrade {\n        default upgrade;\n        ''      close;\n    }\n\n    server {"
+        ));
+        assert!(updated.contains(
+            "        server_name app.local;\n\n        location /ws/ {\n            proxy_pass http://127.0.0.1:3000;\n            proxy_http_version 1.1;\n"
+        ));
+        assert!(updated.contains("            proxy_set_header Connection $connection_upgrade;\n"));
+        assert!(updated.contains("            proxy_read_timeout 3600s;\n        }\n    }\n}"));
+
+        let reparsed = parse_nginx_config(&updated, "").unwrap();
+        let again = insert_upgrade_map(&insert_proxy_location(&updated, &reparsed.servers[0], &result).unwrap()).unwrap();
+        assert_eq!(again.matches("map $http_upgrade").count(), 1);
+        assert_eq!(parse_nginx_config(&again, "").unwrap().servers[0].locations.len(), 2);
+    }
+
+    #[test]
+    fn upgrade_map_should_be_found_in_included_files() {
+        let dir = std::env::temp_dir().join(format!("ncm_proxy_wizard_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf.d")).unwrap();
+        let main = dir.join("nginx.conf");
+        let site = dir.join("conf.d/site.conf");
+        fs::write(&main, "events {}\nhttp {\n    include conf.d/*.conf;\n}\n").unwrap();
+        fs::write(&site, "server {\n    listen 80;\n}\n").unwrap();
+        let (main, site) = (main.to_str().unwrap(), site.to_str().unwrap());
+
+        assert!(!config_has_upgrade_map(main, site, "server {\n    listen 80;\n}\n"));
+        fs::write(
+            dir.join("conf.d/maps.conf"),
+            "map $http_upgrade $connection_upgrade {\n    default upgrade;\n    '' close;\n}\n",
+        )
+        .unwrap();
+        assert!(config_has_upgrade_map(main, site, "server {\n    listen 80;\n}\n"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
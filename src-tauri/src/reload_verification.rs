@@ -0,0 +1,261 @@
+use crate::config::load_nginx_config;
+use crate::nginx::{query_nginx_processes, NginxProcessSnapshot, OperationResult};
+use crate::nginx_error_analysis::parse_error_log_line;
+use crate::nginx_logs::{discover_log_sources, resolve_log_prefix, NginxLogKind};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+
+/// 等待新 worker 出现的最长时间
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+/// 进程查询开销较大（Windows 下需启动 PowerShell），轮询间隔逐次加倍
+const VERIFY_INITIAL_INTERVAL: Duration = Duration::from_millis(500);
+const VERIFY_MAX_INTERVAL: Duration = Duration::from_secs(2);
+/// 结果中最多展示的错误日志行数
+const MAX_REPORTED_ERRORS: usize = 5;
+const FATAL_LEVELS: &[&str] = &["emerg", "alert"];
+
+/// 发送重载信号前记录的进程与错误日志位置
+pub(crate) struct ReloadBaseline {
+    snapshot: NginxProcessSnapshot,
+    error_logs: Vec<(PathBuf, u64)>,
+}
+
+/// 本次重载所用配置中的全局 error_log 文件，配置无法解析时使用 nginx 默认位置
+fn resolve_global_error_logs(nginx_path: &str, config_path: &str) -> Vec<PathBuf> {
+    let prefix = resolve_log_prefix(config_path, nginx_path);
+
+    let mut paths: Vec<PathBuf> = load_nginx_config(config_path)
+        .map(|config| discover_log_sources(&config, &prefix))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|source| source.kind == NginxLogKind::Error && source.server_id.is_none())
+        .map(|source| PathBuf::from(source.path))
+        .collect();
+
+    if paths.is_empty() {
+        paths.push(prefix.join("logs").join("error.log"));
+    }
+    paths.dedup();
+    paths
+}
+
+impl ReloadBaseline {
+    pub(crate) fn capture(nginx_path: &str, config_path: &str) -> Self {
+        let error_logs = resolve_global_error_logs(nginx_path, config_path)
+            .into_iter()
+            .map(|path| {
+                let offset = fs::metadata(&path).map(|metadata| metadata.len()).unwrap_or(0);
+                (path, offset)
+            })
+            .collect();
+
+        Self {
+            // 查询失败时没有 worker 信息，校验只依据错误日志
+            snapshot: query_nginx_processes().unwrap_or_default(),
+            error_logs,
+        }
+    }
+}
+
+/// 不在重载前快照中的 worker PID；旧 worker 优雅退出期间会与新 worker 并存
+fn new_worker_pids(previous: &NginxProcessSnapshot, current: &NginxProcessSnapshot) -> Vec<u32> {
+    let previous: HashSet<u32> = previous.worker_pids.iter().copied().collect();
+    current
+        .worker_pids
+        .iter()
+        .copied()
+        .filter(|pid| !previous.contains(pid))
+        .collect()
+}
+
+fn is_fatal_error_line(line: &str) -> bool {
+    parse_error_log_line(line).is_some_and(|parsed| FATAL_LEVELS.contains(&parsed.level.as_str()))
+}
+
+/// 读取重载后新写入 error_log 的 [emerg] / [alert] 行，日志被轮转时从头读取
+fn collect_fatal_errors(error_logs: &[(PathBuf, u64)]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (path, offset) in error_logs {
+        let Ok(mut file) = File::open(path) else {
+            continue;
+        };
+        let len = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        let start = if len < *offset { 0 } else { *offset };
+        if file.seek(SeekFrom::Start(start)).is_err() {
+            continue;
+        }
+
+        errors.extend(
+            BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter(|line| is_fatal_error_line(line))
+                .map(|line| line.trim().to_string()),
+        );
+    }
+
+    errors
+}
+
+fn describe_pids(pids: &[u32]) -> String {
+    pids.iter().map(u32::to_string).collect::<Vec<_>>().join(", ")
+}
+
+fn with_signal_output(message: String, signal_output: &str) -> String {
+    let signal_output = signal_output.trim();
+    if signal_output.is_empty() {
+        message
+    } else {
+        format!("{}\n{}", message, signal_output)
+    }
+}
+
+/// 根据 worker 替换情况与错误日志得出重载结果，signal_output 为 `nginx -s reload` 的输出
+fn build_verification_result(
+    signal_output: &str,
+    new_workers: &[u32],
+    master_alive: bool,
+    fatal_errors: &[String],
+) -> OperationResult {
+    if !fatal_errors.is_empty() {
+        let shown: Vec<&str> = fatal_errors
+            .iter()
+            .rev()
+            .take(MAX_REPORTED_ERRORS)
+            .rev()
+            .map(String::as_str)
+            .collect();
+        return OperationResult {
+            success: false,
+            message: format!(
+                "✗ Nginx 已接收重载信号，但新配置未生效，error.log 记录了以下错误:\n{}\n\n运行中的仍是旧配置。",
+                shown.join("\n")
+            ),
+        };
+    }
+
+    if !master_alive {
+        return OperationResult {
+            success: false,
+            message: with_signal_output(
+                "✗ 重载后 Nginx master 进程已退出，请检查错误日志".to_string(),
+                signal_output,
+            ),
+        };
+    }
+
+    if new_workers.is_empty() {
+        return OperationResult {
+            success: false,
+            message: with_signal_output(
+                format!(
+                    "✗ Nginx 已接收重载信号，但 {} 秒内未检测到新的 worker 进程，新配置可能未生效",
+                    VERIFY_TIMEOUT.as_secs()
+                ),
+                signal_output,
+            ),
+        };
+    }
+
+    OperationResult {
+        success: true,
+        message: with_signal_output(
+            format!(
+                "✓ 配置重新加载成功，新配置已生效（新 worker PID: {}）",
+                describe_pids(new_workers)
+            ),
+            signal_output,
+        ),
+    }
+}
+
+/// `nginx -s reload` 返回成功后，确认新 worker 已替换旧 worker 且 error.log 无致命错误
+pub(crate) async fn verify_reload(baseline: ReloadBaseline, signal_output: &str) -> OperationResult {
+    // 进程信息不可用时（例如权限不足）只能依据错误日志判断
+    let can_track_workers = !baseline.snapshot.worker_pids.is_empty();
+    let started = Instant::now();
+    let mut current = query_nginx_processes().unwrap_or_else(|_| baseline.snapshot.clone());
+    let mut interval = VERIFY_INITIAL_INTERVAL;
+
+    loop {
+        let fatal_errors = collect_fatal_errors(&baseline.error_logs);
+        let new_workers = new_worker_pids(&baseline.snapshot, &current);
+        let settled = !fatal_errors.is_empty() || !new_workers.is_empty() || !current.is_running();
+
+        if settled || started.elapsed() >= VERIFY_TIMEOUT {
+            if !can_track_workers && fatal_errors.is_empty() && current.is_running() {
+                return OperationResult {
+                    success: true,
+                    message: with_signal_output(
+                        "✓ 配置重新加载成功（未能获取 worker 进程信息，error.log 中未发现致命错误）".to_string(),
+                        signal_output,
+                    ),
+                };
+            }
+            return build_verification_result(signal_output, &new_workers, current.is_running(), &fatal_errors);
+        }
+
+        sleep(interval).await;
+        interval = (interval * 2).min(VERIFY_MAX_INTERVAL);
+        // 单次查询失败时沿用上一次快照，避免误判为 Nginx 已退出
+        if let Ok(snapshot) = query_nginx_processes() {
+            current = snapshot;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(master_pid: u32, worker_pids: &[u32]) -> NginxProcessSnapshot {
+        NginxProcessSnapshot {
+            master_pid: Some(master_pid),
+            worker_pids: worker_pids.to_vec(),
+            process_count: 1 + worker_pids.len() as u32,
+        }
+    }
+
+    #[test]
+    fn new_worker_pids_should_ignore_workers_shutting_down() {
+        let before = snapshot(100, &[101, 102]);
+        let during = snapshot(100, &[101, 102, 201, 202]);
+
+        assert_eq!(new_worker_pids(&before, &during), vec![201, 202]);
+        assert!(new_worker_pids(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn reload_verification_should_fail_on_fatal_errors_since_reload() {
+        let path = std::env::temp_dir().join(format!("ncm_reload_verify_{}.log", std::process::id()));
+        fs::write(&path, "2024/01/01 10:00:00 [emerg] 1#1: old failure\n").unwrap();
+        let offset = fs::metadata(&path).unwrap().len();
+        fs::write(
+            &path,
+            "2024/01/01 10:00:00 [emerg] 1#1: old failure\n\
+             2024/01/01 10:05:00 [notice] 1#1: signal process started\n\
+             2024/01/01 10:05:00 [emerg] 100#100: bind() to 0.0.0.0:80 failed (98: Address already in use)\n",
+        )
+        .unwrap();
+
+        let errors = collect_fatal_errors(&[(path.clone(), offset)]);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("bind()"));
+
+        let failed = build_verification_result("", &[], true, &errors);
+        assert!(!failed.success);
+        assert!(failed.message.contains("Address already in use"));
+
+        let live = build_verification_result("", &[201], true, &[]);
+        assert!(live.success);
+        assert!(live.message.contains("201"));
+        assert!(!build_verification_result("", &[], true, &[]).success);
+
+        let _ = fs::remove_file(&path);
+    }
+}
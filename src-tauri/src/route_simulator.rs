@@ -0,0 +1,472 @@
+use crate::config::{LocationBlock, ServerBlock};
+use crate::config_watcher::{parse_config_servers, resolve_config_files, server_ref, ServerBlockRef};
+use crate::port_preflight::{parse_listen_address, ListenProtocol};
+use crate::server_match::{
+    select_location, select_server, LocationMatchKind, ServerNameMatchKind, ServerSelectionReason,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteSimulationRequest {
+    pub config_path: String,
+    /// 例如 `https://www.example.com:8443/api/users?id=1`，省略协议时按 http 处理
+    pub url: String,
+    /// 覆盖 URL 中的主机名作为 Host 请求头，用于通过 IP 访问时模拟
+    #[serde(default)]
+    pub host_header: Option<String>,
+    /// 请求到达的本机地址，用于区分监听在不同 IP 上的 server
+    #[serde(default)]
+    pub local_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerMatchReason {
+    ExactName,
+    LeadingWildcard,
+    TrailingWildcard,
+    Regex,
+    DefaultServer,
+    FirstServer,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocationMatchReason {
+    Exact,
+    PrefixStopRegex,
+    Regex,
+    LongestPrefix,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteSimulationResult {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+    pub query: Option<String>,
+    /// 参与选择的监听地址，例如 `*:80`
+    pub listen_address: Option<String>,
+    pub candidates: Vec<ServerBlockRef>,
+    pub server: Option<ServerBlock>,
+    pub server_file_path: Option<String>,
+    pub server_reason: Option<ServerMatchReason>,
+    pub matched_server_name: Option<String>,
+    pub location: Option<LocationBlock>,
+    pub location_reason: Option<LocationMatchReason>,
+    /// 按选择顺序给出的说明
+    pub explanation: Vec<String>,
+    pub warnings: Vec<String>,
+    pub parse_errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SimulatedUrl {
+    pub scheme: String,
+    pub host: String,
+    pub port: u16,
+    pub path: String,
+    pub query: Option<String>,
+}
+
+pub(crate) fn parse_simulated_url(url: &str) -> Result<SimulatedUrl, String> {
+    let url = url.trim();
+    if url.is_empty() {
+        return Err("URL 不能为空".to_string());
+    }
+
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (scheme.to_ascii_lowercase(), rest),
+        None => ("http".to_string(), url),
+    };
+    let default_port = match scheme.as_str() {
+        "http" => 80,
+        "https" => 443,
+        _ => return Err(format!("不支持的协议: {}", scheme)),
+    };
+
+    let (authority, target) = match rest.find(['/', '?']) {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, Some(query.to_string())),
+        None => (target, None),
+    };
+    let path = if path.is_empty() { "/" } else { path };
+
+    let authority = authority.rsplit('@').next().unwrap_or(authority);
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, rest) = rest.split_once(']').ok_or_else(|| format!("无效的主机: {}", authority))?;
+        let port = match rest.strip_prefix(':') {
+            Some(port) => port.parse().map_err(|_| format!("无效的端口: {}", port))?,
+            None => default_port,
+        };
+        (host.to_string(), port)
+    } else if let Some((host, port)) = authority.rsplit_once(':') {
+        (host.to_string(), port.parse().map_err(|_| format!("无效的端口: {}", port))?)
+    } else {
+        (authority.to_string(), default_port)
+    };
+
+    if host.is_empty() {
+        return Err("URL 中缺少主机名".to_string());
+    }
+
+    Ok(SimulatedUrl {
+        scheme,
+        host: host.to_ascii_lowercase(),
+        port,
+        path: path.to_string(),
+        query,
+    })
+}
+
+struct ListenCandidate {
+    index: usize,
+    host: Option<String>,
+    is_default: bool,
+    ssl: bool,
+}
+
+/// 收集监听请求端口的 server，未声明 listen 时按 *:80 处理
+fn listen_candidates(servers: &[(String, ServerBlock)], port: u16) -> Vec<ListenCandidate> {
+    let mut candidates = Vec::new();
+
+    for (index, (_, server)) in servers.iter().enumerate() {
+        if server.listen.is_empty() && port == 80 {
+            candidates.push(ListenCandidate { index, host: None, is_default: false, ssl: false });
+            continue;
+        }
+
+        for value in &server.listen {
+            let Some(address) = parse_listen_address(value) else {
+                continue;
+            };
+            if address.port != port || address.protocol != ListenProtocol::Tcp {
+                continue;
+            }
+            let params: Vec<&str> = value.split_whitespace().skip(1).collect();
+            candidates.push(ListenCandidate {
+                index,
+                host: address.host,
+                is_default: params.iter().any(|param| *param == "default_server" || *param == "default"),
+                ssl: params.contains(&"ssl"),
+            });
+        }
+    }
+
+    candidates
+}
+
+fn describe_location(location: &LocationBlock) -> String {
+    match location.modifier.as_deref() {
+        Some(modifier) => format!("location {} {}", modifier, location.path),
+        None => format!("location {}", location.path),
+    }
+}
+
+fn explain_location(server: &ServerBlock, path: &str) -> (Option<LocationBlock>, Option<LocationMatchReason>, String) {
+    let Some((location, kind)) = select_location(server, path) else {
+        return (
+            None,
+            None,
+            "没有 location 匹配该路径，请求由 server 级别配置处理（通常返回 404）".to_string(),
+        );
+    };
+
+    let longest_prefix = server
+        .locations
+        .iter()
+        .filter(|candidate| matches!(candidate.modifier.as_deref(), None | Some("^~")))
+        .filter(|candidate| path.starts_with(candidate.path.as_str()))
+        .max_by_key(|candidate| candidate.path.len());
+
+    let (reason, text) = match kind {
+        LocationMatchKind::Exact => (
+            LocationMatchReason::Exact,
+            format!("精确匹配 {}，立即结束查找", describe_location(location)),
+        ),
+        LocationMatchKind::PrefixStopRegex => (
+            LocationMatchReason::PrefixStopRegex,
+            format!("最长前缀为 {}，^~ 修饰符使 nginx 不再检查正则 location", describe_location(location)),
+        ),
+        LocationMatchKind::Regex => (
+            LocationMatchReason::Regex,
+            match longest_prefix {
+                Some(prefix) => format!(
+                    "最长前缀为 {}，但正则 {} 按出现顺序最先匹配，正则优先于普通前缀",
+                    describe_location(prefix),
+                    describe_location(location)
+                ),
+                None => format!("正则 {} 按出现顺序最先匹配", describe_location(location)),
+            },
+        ),
+        LocationMatchKind::LongestPrefix => (
+            LocationMatchReason::LongestPrefix,
+            format!("没有正则 location 匹配，使用最长前缀 {}", describe_location(location)),
+        ),
+    };
+
+    (Some(location.clone()), Some(reason), text)
+}
+
+/// 模拟 nginx 对请求的 server 与 location 选择
+pub(crate) fn simulate_route(
+    servers: &[(String, ServerBlock)],
+    url: &SimulatedUrl,
+    host_header: Option<&str>,
+    local_address: Option<&str>,
+) -> RouteSimulationResult {
+    let host = host_header
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_else(|| url.host.clone());
+    let mut explanation = Vec::new();
+    let mut warnings = Vec::new();
+
+    let enabled: Vec<(String, ServerBlock)> = servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .cloned()
+        .collect();
+    let all = listen_candidates(&enabled, url.port);
+
+    // 先按监听地址分组：请求到达的地址上有专门监听的 server 时，只在这些 server 中选择
+    let local_address = local_address.map(str::trim).filter(|address| !address.is_empty());
+    let specific_host = local_address
+        .filter(|address| all.iter().any(|candidate| candidate.host.as_deref() == Some(*address)))
+        .map(str::to_string)
+        .or_else(|| {
+            if all.iter().any(|candidate| candidate.host.is_none()) {
+                None
+            } else {
+                all.first().and_then(|candidate| candidate.host.clone())
+            }
+        });
+    if local_address.is_none() && specific_host.is_some() {
+        warnings.push(format!(
+            "端口 {} 仅在特定地址上监听，假设请求到达 {}",
+            url.port,
+            specific_host.as_deref().unwrap_or_default()
+        ));
+    }
+
+    let group: Vec<&ListenCandidate> = all
+        .iter()
+        .filter(|candidate| candidate.host == specific_host)
+        .collect();
+    let listen_address = (!group.is_empty()).then(|| format!("{}:{}", specific_host.as_deref().unwrap_or("*"), url.port));
+
+    let mut candidates: Vec<(&ServerBlock, bool)> = Vec::new();
+    let mut candidate_indexes = Vec::new();
+    for candidate in &group {
+        if let Some(position) = candidate_indexes.iter().position(|index| *index == candidate.index) {
+            candidates[position].1 |= candidate.is_default;
+        } else {
+            candidate_indexes.push(candidate.index);
+            candidates.push((&enabled[candidate.index].1, candidate.is_default));
+        }
+    }
+
+    let mut result = RouteSimulationResult {
+        scheme: url.scheme.clone(),
+        host: host.clone(),
+        port: url.port,
+        path: url.path.clone(),
+        query: url.query.clone(),
+        listen_address: listen_address.clone(),
+        candidates: candidate_indexes
+            .iter()
+            .map(|index| server_ref(&enabled[*index].0, &enabled[*index].1))
+            .collect(),
+        server: None,
+        server_file_path: None,
+        server_reason: None,
+        matched_server_name: None,
+        location: None,
+        location_reason: None,
+        explanation: Vec::new(),
+        warnings: Vec::new(),
+        parse_errors: Vec::new(),
+    };
+
+    let Some(listen_address) = listen_address else {
+        explanation.push(format!("没有启用的 server 监听端口 {}，连接将被拒绝", url.port));
+        result.explanation = explanation;
+        result.warnings = warnings;
+        return result;
+    };
+    explanation.push(format!(
+        "按端口筛选：{} 上有 {} 个 server 参与选择",
+        listen_address,
+        candidates.len()
+    ));
+
+    let Some((position, reason)) = select_server(&candidates, &host) else {
+        result.explanation = explanation;
+        result.warnings = warnings;
+        return result;
+    };
+    let index = candidate_indexes[position];
+    let (file_path, server) = &enabled[index];
+    let server_label = server.server_name.first().cloned().unwrap_or_else(|| "_".to_string());
+
+    let (server_reason, matched_name, text) = match reason {
+        ServerSelectionReason::Name(kind, name) => {
+            let (reason, label) = match kind {
+                ServerNameMatchKind::Exact => (ServerMatchReason::ExactName, "精确名称"),
+                ServerNameMatchKind::LeadingWildcard => (ServerMatchReason::LeadingWildcard, "最长的前导通配符"),
+                ServerNameMatchKind::TrailingWildcard => (ServerMatchReason::TrailingWildcard, "最长的后缀通配符"),
+                ServerNameMatchKind::Regex => (ServerMatchReason::Regex, "按出现顺序第一个匹配的正则"),
+            };
+            let text = format!("Host {} 命中{} {}（{}:{}）", host, label, name, file_path, server.start_line);
+            (reason, Some(name), text)
+        }
+        ServerSelectionReason::DefaultServer => (
+            ServerMatchReason::DefaultServer,
+            None,
+            format!(
+                "没有 server_name 匹配 Host {}，使用 default_server {}（{}:{}）",
+                host, server_label, file_path, server.start_line
+            ),
+        ),
+        ServerSelectionReason::FirstServer => (
+            ServerMatchReason::FirstServer,
+            None,
+            format!(
+                "没有 server_name 匹配 Host {} 且未声明 default_server，使用该地址上的第一个 server {}（{}:{}）",
+                host, server_label, file_path, server.start_line
+            ),
+        ),
+    };
+    explanation.push(text);
+
+    let ssl = group
+        .iter()
+        .filter(|candidate| candidate.index == index)
+        .any(|candidate| candidate.ssl);
+    if url.scheme == "https" && !ssl {
+        warnings.push(format!("以 HTTPS 访问，但 {} 的 listen 未启用 ssl，TLS 握手将失败", listen_address));
+    } else if url.scheme == "http" && ssl {
+        warnings.push(format!("以 HTTP 访问启用 ssl 的 {}，nginx 将返回 400", listen_address));
+    }
+
+    let (location, location_reason, location_text) = explain_location(server, &url.path);
+    explanation.push(location_text);
+
+    result.server = Some(server.clone());
+    result.server_file_path = Some(file_path.clone());
+    result.server_reason = Some(server_reason);
+    result.matched_server_name = matched_name;
+    result.location = location;
+    result.location_reason = location_reason;
+    result.explanation = explanation;
+    result.warnings = warnings;
+    result
+}
+
+/// 请求路由模拟：给定 URL，返回 nginx 会选择的 server 与 location 及原因
+#[tauri::command]
+pub async fn simulate_nginx_route(request: RouteSimulationRequest) -> Result<RouteSimulationResult, String> {
+    if request.config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+
+    let url = parse_simulated_url(&request.url)?;
+    let (servers, parse_errors) = parse_config_servers(&resolve_config_files(&request.config_path));
+
+    let mut result = simulate_route(
+        &servers,
+        &url,
+        request.host_header.as_deref(),
+        request.local_address.as_deref(),
+    );
+    result.parse_errors = parse_errors;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    #[test]
+    fn parse_simulated_url_should_apply_scheme_defaults() {
+        let url = parse_simulated_url("https://WWW.Example.com/api/users?id=1").unwrap();
+        assert_eq!(
+            (url.scheme.as_str(), url.host.as_str(), url.port, url.path.as_str(), url.query.as_deref()),
+            ("https", "www.example.com", 443, "/api/users", Some("id=1"))
+        );
+
+        let url = parse_simulated_url("[::1]:8080").unwrap();
+        assert_eq!((url.host.as_str(), url.port, url.path.as_str()), ("::1", 8080, "/"));
+        assert!(parse_simulated_url("ftp://example.com").is_err());
+    }
+
+    #[test]
+    fn simulate_route_should_explain_server_and_location_choice() {
+        let servers: Vec<(String, ServerBlock)> = parse_nginx_config(
+            r#"http {
+    server {
+        listen 80 default_server;
+        server_name _;
+        return 444;
+    }
+    server {
+        listen 80;
+        server_name *.example.com;
+        location / {
+            root html;
+        }
+        location /api/ {
+            proxy_pass http://backend;
+        }
+        location ~* \.json$ {
+            default_type application/json;
+        }
+    }
+    server {
+        listen 127.0.0.1:80;
+        server_name internal.example.com;
+    }
+}"#,
+            "nginx.conf",
+        )
+        .unwrap()
+        .servers
+        .into_iter()
+        .map(|server| ("nginx.conf".to_string(), server))
+        .collect();
+
+        let url = parse_simulated_url("http://shop.example.com/api/items.json").unwrap();
+        let result = simulate_route(&servers, &url, None, None);
+        assert_eq!(result.listen_address.as_deref(), Some("*:80"));
+        assert_eq!(result.candidates.len(), 2);
+        assert_eq!(result.server_reason, Some(ServerMatchReason::LeadingWildcard));
+        assert_eq!(result.matched_server_name.as_deref(), Some("*.example.com"));
+        assert_eq!(result.location_reason, Some(LocationMatchReason::Regex));
+        assert!(result.explanation[2].contains("location /api/"));
+
+        let fallback = simulate_route(&servers, &parse_simulated_url("http://10.0.0.1/").unwrap(), None, None);
+        assert_eq!(fallback.server_reason, Some(ServerMatchReason::DefaultServer));
+        assert_eq!(fallback.location_reason, None);
+
+        let internal = simulate_route(
+            &servers,
+            &parse_simulated_url("http://127.0.0.1/").unwrap(),
+            Some("internal.example.com"),
+            Some("127.0.0.1"),
+        );
+        assert_eq!(internal.listen_address.as_deref(), Some("127.0.0.1:80"));
+        assert_eq!(internal.server_reason, Some(ServerMatchReason::ExactName));
+
+        let https = simulate_route(&servers, &parse_simulated_url("https://shop.example.com").unwrap(), None, None);
+        assert!(https.server.is_none());
+        assert!(https.explanation[0].contains("443"));
+    }
+}
@@ -0,0 +1,765 @@
+use crate::config::{load_nginx_config, Directive, ServerBlock};
+use crate::config_watcher::{parse_config_servers, resolve_config_files, server_ref, ServerBlockRef};
+use crate::port_preflight::parse_listen_address;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// HSTS 最短有效期：180 天
+const HSTS_MIN_MAX_AGE: u64 = 15_552_000;
+/// client_max_body_size 超过该值视为过大：100 MiB
+const LARGE_BODY_SIZE: u64 = 100 * 1024 * 1024;
+
+const WEAK_PROTOCOLS: &[&str] = &["SSLv2", "SSLv3", "TLSv1", "TLSv1.1"];
+const WEAK_CIPHER_PARTS: &[&str] = &["RC4", "DES", "3DES", "MD5", "NULL", "ANULL", "ENULL", "EXPORT", "EXP"];
+const SECURITY_HEADERS: &[&str] = &["Content-Security-Policy", "X-Frame-Options", "X-Content-Type-Options"];
+/// 来自客户端请求、不应决定代理目标主机的变量
+const REQUEST_HOST_VARIABLES: &[&str] = &["$host", "$http_", "$arg_", "$request_uri", "$uri", "$server_name"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecurityCheckStatus {
+    Pass,
+    Warn,
+    Fail,
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityCheck {
+    pub id: String,
+    pub title: String,
+    pub status: SecurityCheckStatus,
+    /// 权重，用于计算得分
+    pub weight: u32,
+    pub detail: String,
+    pub recommendation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSecurityAudit {
+    pub server: ServerBlockRef,
+    /// 监听非回环地址
+    pub internet_facing: bool,
+    pub tls_enabled: bool,
+    /// 0 - 100
+    pub score: u32,
+    /// A / B / C / D / F
+    pub grade: String,
+    pub checks: Vec<SecurityCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityAuditReport {
+    pub config_path: String,
+    pub generated_at: String,
+    pub servers: Vec<ServerSecurityAudit>,
+    pub average_score: u32,
+    pub fail_count: usize,
+    pub warn_count: usize,
+    pub parse_errors: Vec<String>,
+}
+
+/// 审计单个 server 所需的上下文，global 为 http 等上层作用域的指令
+struct AuditContext<'a> {
+    server: &'a ServerBlock,
+    global: &'a [Directive],
+}
+
+impl AuditContext<'_> {
+    /// server 级别优先，否则继承上层作用域
+    fn inherited(&self, name: &str) -> Option<&Directive> {
+        self.server
+            .directives
+            .iter()
+            .rev()
+            .find(|directive| directive.name == name)
+            .or_else(|| self.global.iter().rev().find(|directive| directive.name == name))
+    }
+
+    /// add_header 只有在当前级别完全未声明时才继承上层
+    fn effective_headers(&self) -> Vec<&Directive> {
+        let own: Vec<&Directive> = self.server.directives.iter().filter(|directive| directive.name == "add_header").collect();
+        if !own.is_empty() {
+            return own;
+        }
+        self.global.iter().filter(|directive| directive.name == "add_header").collect()
+    }
+
+    fn header(&self, name: &str) -> Option<&Directive> {
+        self.effective_headers().into_iter().find(|directive| {
+            directive
+                .value
+                .split_whitespace()
+                .next()
+                .is_some_and(|header| header.eq_ignore_ascii_case(name))
+        })
+    }
+
+    fn all_directives(&self) -> impl Iterator<Item = &Directive> {
+        self.server
+            .directives
+            .iter()
+            .chain(self.server.locations.iter().flat_map(|location| location.directives.iter()))
+    }
+
+    fn tls_enabled(&self) -> bool {
+        self.server.listen.iter().any(|listen| {
+            listen
+                .split_whitespace()
+                .skip(1)
+                .any(|param| param == "ssl" || param == "quic")
+        }) || self
+            .server
+            .directives
+            .iter()
+            .any(|directive| directive.name == "ssl" && directive.value == "on")
+    }
+
+    /// server 级别直接 return，不提供任何内容（例如 HTTP 跳转 HTTPS）
+    fn redirect_target(&self) -> Option<&str> {
+        self.server
+            .directives
+            .iter()
+            .find(|directive| directive.name == "return")
+            .map(|directive| directive.value.as_str())
+    }
+
+    fn internet_facing(&self) -> bool {
+        if self.server.listen.is_empty() {
+            return true;
+        }
+        self.server
+            .listen
+            .iter()
+            .filter_map(|listen| parse_listen_address(listen))
+            .any(|address| match address.host.as_deref() {
+                None => true,
+                Some(host) => !(host == "localhost" || host == "::1" || host.starts_with("127.")),
+            })
+    }
+}
+
+fn check(
+    id: &str,
+    title: &str,
+    weight: u32,
+    status: SecurityCheckStatus,
+    detail: impl Into<String>,
+    recommendation: Option<&str>,
+) -> SecurityCheck {
+    SecurityCheck {
+        id: id.to_string(),
+        title: title.to_string(),
+        status,
+        weight,
+        detail: detail.into(),
+        recommendation: recommendation.map(str::to_string),
+    }
+}
+
+/// 解析 nginx 大小值（1024、10k、8m、1g）
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim().to_ascii_lowercase();
+    let (number, unit) = match value.chars().last()? {
+        'k' => (&value[..value.len() - 1], 1024),
+        'm' => (&value[..value.len() - 1], 1024 * 1024),
+        'g' => (&value[..value.len() - 1], 1024 * 1024 * 1024),
+        _ => (value.as_str(), 1),
+    };
+    number.parse::<u64>().ok().map(|number| number * unit)
+}
+
+fn is_weak_cipher(cipher: &str) -> bool {
+    if cipher.starts_with('!') || cipher.starts_with('-') {
+        return false;
+    }
+    cipher
+        .trim_start_matches('+')
+        .split('-')
+        .any(|part| WEAK_CIPHER_PARTS.contains(&part.to_ascii_uppercase().as_str()))
+}
+
+fn check_https(context: &AuditContext, internet_facing: bool) -> SecurityCheck {
+    const ID: &str = "https";
+    const TITLE: &str = "HTTPS";
+    if context.tls_enabled() {
+        return check(ID, TITLE, 3, SecurityCheckStatus::Pass, "已启用 TLS", None);
+    }
+    if let Some(target) = context.redirect_target().filter(|target| target.contains("https://")) {
+        return check(ID, TITLE, 3, SecurityCheckStatus::Pass, format!("HTTP 请求全部跳转：return {}", target), None);
+    }
+    if !internet_facing {
+        return check(ID, TITLE, 3, SecurityCheckStatus::NotApplicable, "仅监听本机回环地址", None);
+    }
+    check(
+        ID,
+        TITLE,
+        3,
+        SecurityCheckStatus::Fail,
+        "对外监听但未启用 TLS，流量以明文传输",
+        Some("为 listen 添加 ssl 并配置证书，或将 HTTP 请求 301 跳转到 HTTPS"),
+    )
+}
+
+fn check_tls_protocols(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "tls-protocols";
+    const TITLE: &str = "TLS 协议版本";
+    let Some(protocols) = context.inherited("ssl_protocols") else {
+        return check(
+            ID,
+            TITLE,
+            2,
+            SecurityCheckStatus::Warn,
+            "未显式设置 ssl_protocols，nginx 1.23 之前的默认值包含 TLSv1 与 TLSv1.1",
+            Some("设置 ssl_protocols TLSv1.2 TLSv1.3;"),
+        );
+    };
+
+    let weak: Vec<&str> = protocols
+        .value
+        .split_whitespace()
+        .filter(|protocol| WEAK_PROTOCOLS.contains(protocol))
+        .collect();
+    if weak.is_empty() {
+        check(ID, TITLE, 2, SecurityCheckStatus::Pass, format!("ssl_protocols {}", protocols.value), None)
+    } else {
+        check(
+            ID,
+            TITLE,
+            2,
+            SecurityCheckStatus::Fail,
+            format!("启用了不安全的协议：{}", weak.join("、")),
+            Some("设置 ssl_protocols TLSv1.2 TLSv1.3;"),
+        )
+    }
+}
+
+fn check_tls_ciphers(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "tls-ciphers";
+    const TITLE: &str = "TLS 加密套件";
+    let Some(ciphers) = context.inherited("ssl_ciphers") else {
+        return check(ID, TITLE, 2, SecurityCheckStatus::Pass, "使用 nginx 默认套件 HIGH:!aNULL:!MD5", None);
+    };
+
+    let weak: Vec<&str> = ciphers.value.trim_matches('"').split(':').filter(|cipher| is_weak_cipher(cipher)).collect();
+    if weak.is_empty() {
+        check(ID, TITLE, 2, SecurityCheckStatus::Pass, "未发现弱加密套件", None)
+    } else {
+        check(
+            ID,
+            TITLE,
+            2,
+            SecurityCheckStatus::Fail,
+            format!("包含弱加密套件：{}", weak.join("、")),
+            Some("移除 RC4、DES、MD5、NULL、EXPORT 相关套件，或使用 Mozilla Intermediate 推荐配置"),
+        )
+    }
+}
+
+fn check_hsts(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "hsts";
+    const TITLE: &str = "HSTS";
+    let recommendation = Some("add_header Strict-Transport-Security \"max-age=31536000; includeSubDomains\" always;");
+    let Some(header) = context.header("Strict-Transport-Security") else {
+        return check(ID, TITLE, 2, SecurityCheckStatus::Fail, "未设置 Strict-Transport-Security", recommendation);
+    };
+
+    let max_age = header
+        .value
+        .to_ascii_lowercase()
+        .split("max-age=")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next().and_then(|number| number.parse::<u64>().ok()));
+    match max_age {
+        Some(max_age) if max_age >= HSTS_MIN_MAX_AGE => {
+            check(ID, TITLE, 2, SecurityCheckStatus::Pass, format!("max-age={}", max_age), None)
+        }
+        _ => check(
+            ID,
+            TITLE,
+            2,
+            SecurityCheckStatus::Warn,
+            "HSTS 的 max-age 缺失或少于 180 天",
+            recommendation,
+        ),
+    }
+}
+
+fn check_security_headers(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "security-headers";
+    const TITLE: &str = "安全响应头";
+    let missing: Vec<&str> = SECURITY_HEADERS
+        .iter()
+        .copied()
+        .filter(|name| context.header(name).is_none())
+        .collect();
+
+    let status = match missing.len() {
+        0 => SecurityCheckStatus::Pass,
+        len if len == SECURITY_HEADERS.len() => SecurityCheckStatus::Fail,
+        _ => SecurityCheckStatus::Warn,
+    };
+    if missing.is_empty() {
+        check(ID, TITLE, 2, status, "已设置 CSP、X-Frame-Options、X-Content-Type-Options", None)
+    } else {
+        check(
+            ID,
+            TITLE,
+            2,
+            status,
+            format!("缺少 {}", missing.join("、")),
+            Some("在 server 级别添加缺少的 add_header，并注意 location 中的 add_header 会覆盖它们"),
+        )
+    }
+}
+
+fn check_dotfiles(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "dotfiles";
+    const TITLE: &str = "隐藏文件访问";
+    let protected = context.server.locations.iter().any(|location| {
+        matches!(location.modifier.as_deref(), Some("~") | Some("~*"))
+            && location.path.contains("/\\.")
+            && location.directives.iter().any(|directive| {
+                (directive.name == "deny" && directive.value == "all")
+                    || (directive.name == "return" && matches!(directive.value.split_whitespace().next(), Some("403") | Some("404")))
+            })
+    });
+    if protected {
+        return check(ID, TITLE, 2, SecurityCheckStatus::Pass, "已拒绝访问以 . 开头的文件", None);
+    }
+
+    let serves_files = context.all_directives().any(|directive| directive.name == "root" || directive.name == "alias")
+        || !context.all_directives().any(|directive| directive.name == "proxy_pass");
+    check(
+        ID,
+        TITLE,
+        2,
+        if serves_files { SecurityCheckStatus::Fail } else { SecurityCheckStatus::Warn },
+        if serves_files {
+            "提供静态文件但未禁止访问 .git、.env 等隐藏文件"
+        } else {
+            "未禁止访问隐藏文件，上游应用可能暴露它们"
+        },
+        Some("添加 location ~ /\\.(?!well-known) { deny all; }"),
+    )
+}
+
+fn check_client_max_body_size(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "client-max-body-size";
+    const TITLE: &str = "请求体大小限制";
+    let values: Vec<&Directive> = context
+        .all_directives()
+        .chain(context.global.iter())
+        .filter(|directive| directive.name == "client_max_body_size")
+        .collect();
+
+    if values.iter().any(|directive| parse_size(&directive.value) == Some(0)) {
+        return check(
+            ID,
+            TITLE,
+            1,
+            SecurityCheckStatus::Fail,
+            "client_max_body_size 0 不限制请求体大小",
+            Some("按业务需要设置上限，仅在上传接口的 location 中放宽"),
+        );
+    }
+    let large: Vec<&str> = values
+        .iter()
+        .filter(|directive| parse_size(&directive.value).is_some_and(|size| size > LARGE_BODY_SIZE))
+        .map(|directive| directive.value.as_str())
+        .collect();
+    if !large.is_empty() {
+        return check(
+            ID,
+            TITLE,
+            1,
+            SecurityCheckStatus::Warn,
+            format!("client_max_body_size 过大：{}", large.join("、")),
+            Some("仅在需要上传大文件的 location 中放宽限制"),
+        );
+    }
+
+    let detail = context
+        .inherited("client_max_body_size")
+        .map(|directive| format!("client_max_body_size {}", directive.value))
+        .unwrap_or_else(|| "使用默认值 1m".to_string());
+    check(ID, TITLE, 1, SecurityCheckStatus::Pass, detail, None)
+}
+
+fn check_open_proxy(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "open-proxy";
+    const TITLE: &str = "开放代理";
+    let mut open = Vec::new();
+    let mut dynamic = Vec::new();
+
+    for directive in context.all_directives().filter(|directive| directive.name == "proxy_pass") {
+        let target = directive.value.split_once("://").map(|(_, rest)| rest).unwrap_or(&directive.value);
+        let authority = target.split('/').next().unwrap_or_default();
+        if !authority.contains('$') {
+            continue;
+        }
+        if REQUEST_HOST_VARIABLES.iter().any(|variable| authority.contains(variable))
+            || authority.chars().zip(authority.chars().skip(1)).any(|(a, b)| a == '$' && b.is_ascii_digit())
+        {
+            open.push(directive.value.as_str());
+        } else {
+            dynamic.push(directive.value.as_str());
+        }
+    }
+
+    if !open.is_empty() {
+        return check(
+            ID,
+            TITLE,
+            3,
+            SecurityCheckStatus::Fail,
+            format!("代理目标主机来自客户端请求：{}", open.join("、")),
+            Some("使用固定的 upstream，或用 map 将允许的主机列入白名单"),
+        );
+    }
+    if !dynamic.is_empty() {
+        return check(
+            ID,
+            TITLE,
+            3,
+            SecurityCheckStatus::Warn,
+            format!("代理目标主机由变量决定：{}", dynamic.join("、")),
+            Some("确认变量来源不受客户端控制"),
+        );
+    }
+    check(ID, TITLE, 3, SecurityCheckStatus::Pass, "代理目标均为固定地址", None)
+}
+
+fn check_server_tokens(context: &AuditContext) -> SecurityCheck {
+    const ID: &str = "server-tokens";
+    const TITLE: &str = "版本号暴露";
+    let recommendation = Some("在 http 级别设置 server_tokens off;");
+    match context.inherited("server_tokens").map(|directive| directive.value.as_str()) {
+        Some("off") => check(ID, TITLE, 1, SecurityCheckStatus::Pass, "server_tokens off", None),
+        Some(value @ ("on" | "build")) => check(
+            ID,
+            TITLE,
+            1,
+            SecurityCheckStatus::Fail,
+            format!("server_tokens {} 会暴露版本信息", value),
+            recommendation,
+        ),
+        _ => check(
+            ID,
+            TITLE,
+            1,
+            SecurityCheckStatus::Warn,
+            "未设置 server_tokens，默认会在响应头中暴露版本号",
+            recommendation,
+        ),
+    }
+}
+
+fn not_applicable(id: &str, title: &str, weight: u32, reason: &str) -> SecurityCheck {
+    check(id, title, weight, SecurityCheckStatus::NotApplicable, reason, None)
+}
+
+fn score_checks(checks: &[SecurityCheck]) -> u32 {
+    let (earned, total) = checks.iter().fold((0, 0), |(earned, total), check| match check.status {
+        SecurityCheckStatus::Pass => (earned + check.weight * 2, total + check.weight * 2),
+        SecurityCheckStatus::Warn => (earned + check.weight, total + check.weight * 2),
+        SecurityCheckStatus::Fail => (earned, total + check.weight * 2),
+        SecurityCheckStatus::NotApplicable => (earned, total),
+    });
+    (earned * 100 + total / 2).checked_div(total).unwrap_or(100)
+}
+
+fn grade(score: u32) -> &'static str {
+    match score {
+        90.. => "A",
+        75..=89 => "B",
+        60..=74 => "C",
+        40..=59 => "D",
+        _ => "F",
+    }
+}
+
+pub(crate) fn audit_server(file_path: &str, server: &ServerBlock, global: &[Directive]) -> ServerSecurityAudit {
+    let context = AuditContext { server, global };
+    let internet_facing = context.internet_facing();
+    let tls_enabled = context.tls_enabled();
+    let redirect_only = context.redirect_target().is_some();
+
+    let mut checks = vec![check_https(&context, internet_facing)];
+    if tls_enabled {
+        checks.push(check_tls_protocols(&context));
+        checks.push(check_tls_ciphers(&context));
+        checks.push(check_hsts(&context));
+    } else {
+        checks.push(not_applicable("tls-protocols", "TLS 协议版本", 2, "未启用 TLS"));
+        checks.push(not_applicable("tls-ciphers", "TLS 加密套件", 2, "未启用 TLS"));
+        checks.push(not_applicable("hsts", "HSTS", 2, "未启用 TLS"));
+    }
+    if redirect_only {
+        let reason = "server 直接 return，不提供内容";
+        checks.push(not_applicable("security-headers", "安全响应头", 2, reason));
+        checks.push(not_applicable("dotfiles", "隐藏文件访问", 2, reason));
+        checks.push(not_applicable("client-max-body-size", "请求体大小限制", 1, reason));
+    } else {
+        checks.push(check_security_headers(&context));
+        checks.push(check_dotfiles(&context));
+        checks.push(check_client_max_body_size(&context));
+    }
+    checks.push(check_open_proxy(&context));
+    checks.push(check_server_tokens(&context));
+
+    let score = score_checks(&checks);
+    ServerSecurityAudit {
+        server: server_ref(file_path, server),
+        internet_facing,
+        tls_enabled,
+        score,
+        grade: grade(score).to_string(),
+        checks,
+    }
+}
+
+fn build_security_audit(config_path: &str) -> SecurityAuditReport {
+    let global = load_nginx_config(config_path)
+        .map(|config| config.global_directives)
+        .unwrap_or_default();
+    let (servers, parse_errors) = parse_config_servers(&resolve_config_files(config_path));
+
+    let audits: Vec<ServerSecurityAudit> = servers
+        .iter()
+        .filter(|(_, server)| server.enabled)
+        .map(|(file_path, server)| audit_server(file_path, server, &global))
+        .collect();
+    let count_status = |status: SecurityCheckStatus| {
+        audits
+            .iter()
+            .flat_map(|audit| audit.checks.iter())
+            .filter(|check| check.status == status)
+            .count()
+    };
+
+    SecurityAuditReport {
+        config_path: config_path.to_string(),
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        average_score: if audits.is_empty() {
+            0
+        } else {
+            audits.iter().map(|audit| audit.score).sum::<u32>() / audits.len() as u32
+        },
+        fail_count: count_status(SecurityCheckStatus::Fail),
+        warn_count: count_status(SecurityCheckStatus::Warn),
+        servers: audits,
+        parse_errors,
+    }
+}
+
+fn status_label(status: SecurityCheckStatus) -> &'static str {
+    match status {
+        SecurityCheckStatus::Pass => "✅ 通过",
+        SecurityCheckStatus::Warn => "⚠️ 警告",
+        SecurityCheckStatus::Fail => "❌ 未通过",
+        SecurityCheckStatus::NotApplicable => "— 不适用",
+    }
+}
+
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
+
+/// 渲染为 Markdown，便于提交安全评审
+pub(crate) fn render_security_audit_markdown(report: &SecurityAuditReport) -> String {
+    let mut lines = vec![
+        "# Nginx 安全审计报告".to_string(),
+        String::new(),
+        format!("- 配置文件：`{}`", report.config_path),
+        format!("- 生成时间：{}", report.generated_at),
+        format!("- 平均得分：{}", report.average_score),
+        format!("- 未通过项：{}，警告项：{}", report.fail_count, report.warn_count),
+        String::new(),
+        "| Server | 监听 | 对外 | TLS | 得分 | 等级 |".to_string(),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    let server_title = |audit: &ServerSecurityAudit| {
+        if audit.server.server_name.is_empty() {
+            audit.server.id.clone()
+        } else {
+            audit.server.server_name.join(" ")
+        }
+    };
+
+    for audit in &report.servers {
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} | {} |",
+            escape_cell(&server_title(audit)),
+            escape_cell(&audit.server.listen.join(", ")),
+            if audit.internet_facing { "是" } else { "否" },
+            if audit.tls_enabled { "是" } else { "否" },
+            audit.score,
+            audit.grade
+        ));
+    }
+
+    for audit in &report.servers {
+        lines.push(String::new());
+        lines.push(format!("## {}（{} / {}）", server_title(audit), audit.score, audit.grade));
+        lines.push(String::new());
+        lines.push(format!(
+            "`{}` 第 {} 行，监听 {}",
+            audit.server.file_path,
+            audit.server.start_line,
+            audit.server.listen.join(", ")
+        ));
+        lines.push(String::new());
+        lines.push("| 检查项 | 结果 | 说明 | 建议 |".to_string());
+        lines.push("| --- | --- | --- | --- |".to_string());
+        for check in &audit.checks {
+            lines.push(format!(
+                "| {} | {} | {} | {} |",
+                check.title,
+                status_label(check.status),
+                escape_cell(&check.detail),
+                escape_cell(check.recommendation.as_deref().unwrap_or(""))
+            ));
+        }
+    }
+
+    if !report.parse_errors.is_empty() {
+        lines.push(String::new());
+        lines.push("## 解析错误".to_string());
+        lines.push(String::new());
+        lines.extend(report.parse_errors.iter().map(|error| format!("- {}", error)));
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// 对已启用的 server 执行安全审计
+#[tauri::command]
+pub async fn audit_nginx_security(config_path: String) -> Result<SecurityAuditReport, String> {
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+    Ok(build_security_audit(&config_path))
+}
+
+/// 导出安全审计报告，format 为 json 或 markdown
+#[tauri::command]
+pub async fn export_security_audit(config_path: String, output_path: String, format: String) -> Result<(), String> {
+    if config_path.trim().is_empty() || output_path.trim().is_empty() {
+        return Err("配置文件路径与导出路径不能为空".to_string());
+    }
+
+    let report = build_security_audit(&config_path);
+    let content = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&report).map_err(|e| format!("序列化审计报告失败: {}", e))?,
+        "markdown" | "md" => render_security_audit_markdown(&report),
+        other => return Err(format!("不支持的导出格式: {}", other)),
+    };
+    fs::write(&output_path, content).map_err(|e| format!("写入审计报告失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    fn audit_all(content: &str) -> Vec<ServerSecurityAudit> {
+        let config = parse_nginx_config(content, "nginx.conf").expect("parse config");
+        config
+            .servers
+            .iter()
+            .map(|server| audit_server("nginx.conf", server, &config.global_directives))
+            .collect()
+    }
+
+    fn status_of(audit: &ServerSecurityAudit, id: &str) -> SecurityCheckStatus {
+        audit.checks.iter().find(|check| check.id == id).expect("check exists").status
+    }
+
+    #[test]
+    fn audit_should_score_hardened_and_weak_servers() {
+        let audits = audit_all(
+            r#"http {
+    server_tokens off;
+    server {
+        listen 443 ssl;
+        server_name secure.example.com;
+        ssl_protocols TLSv1.2 TLSv1.3;
+        ssl_ciphers HIGH:!aNULL:!MD5;
+        add_header Strict-Transport-Security "max-age=31536000" always;
+        add_header Content-Security-Policy "default-src 'self'";
+        add_header X-Frame-Options DENY;
+        add_header X-Content-Type-Options nosniff;
+        root /srv/www;
+        location ~ /\.(?!well-known) {
+            deny all;
+        }
+    }
+    server {
+        listen 443 ssl;
+        server_name weak.example.com;
+        ssl_protocols TLSv1 TLSv1.2;
+        ssl_ciphers RC4-SHA:HIGH;
+        server_tokens on;
+        client_max_body_size 0;
+        root /srv/weak;
+        location / {
+            proxy_pass http://$http_host$request_uri;
+        }
+    }
+}"#,
+        );
+
+        assert_eq!(audits[0].score, 100);
+        assert_eq!(audits[0].grade, "A");
+        assert!(audits[0].tls_enabled && audits[0].internet_facing);
+
+        let weak = &audits[1];
+        for id in ["tls-protocols", "tls-ciphers", "hsts", "security-headers", "dotfiles", "client-max-body-size", "open-proxy", "server-tokens"] {
+            assert_eq!(status_of(weak, id), SecurityCheckStatus::Fail, "{}", id);
+        }
+        assert_eq!(weak.grade, "F");
+    }
+
+    #[test]
+    fn audit_should_skip_checks_for_redirect_and_local_servers() {
+        let audits = audit_all(
+            r#"server {
+    listen 80;
+    server_name example.com;
+    return 301 https://$host$request_uri;
+}
+server {
+    listen 127.0.0.1:8080;
+    server_name internal;
+    proxy_pass http://backend;
+}"#,
+        );
+
+        let redirect = &audits[0];
+        assert_eq!(status_of(redirect, "https"), SecurityCheckStatus::Pass);
+        assert_eq!(status_of(redirect, "hsts"), SecurityCheckStatus::NotApplicable);
+        assert_eq!(status_of(redirect, "security-headers"), SecurityCheckStatus::NotApplicable);
+
+        let internal = &audits[1];
+        assert!(!internal.internet_facing);
+        assert_eq!(status_of(internal, "https"), SecurityCheckStatus::NotApplicable);
+
+        let report = SecurityAuditReport {
+            config_path: "nginx.conf".to_string(),
+            generated_at: "2026-01-01 00:00:00".to_string(),
+            average_score: 0,
+            fail_count: 0,
+            warn_count: 0,
+            servers: audits,
+            parse_errors: Vec::new(),
+        };
+        let markdown = render_security_audit_markdown(&report);
+        assert!(markdown.contains("## example.com"));
+        assert!(markdown.contains("| HTTPS | ✅ 通过 |"));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::config::ServerBlock;
+use crate::config_watcher::{parse_config_servers, resolve_config_files, server_ref, ServerBlockRef};
+use crate::nginx_lint::LintSeverity;
+use crate::port_preflight::{parse_listen_address, ListenProtocol};
+use crate::server_match::{normalize_server_name, server_name_matches};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// 未声明 listen 时 nginx 默认监听的地址
+const DEFAULT_LISTEN_ADDRESS: &str = "*:80";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ServerConflictKind {
+    /// 同一地址上重复的 server_name，nginx 会忽略后出现的定义
+    DuplicateServerName,
+    /// 同一地址上有多个 default_server，nginx 无法启动
+    DuplicateDefaultServer,
+    /// 精确名称同时被其他 server 的通配符或正则匹配
+    NameOverlap,
+    /// 停用的 server 重新启用后会产生冲突
+    DisabledServerConflict,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConflictDiagnostic {
+    pub kind: ServerConflictKind,
+    pub severity: LintSeverity,
+    pub address: String,
+    pub server_name: Option<String>,
+    pub message: String,
+    pub servers: Vec<ServerBlockRef>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerConflictReport {
+    pub config_path: String,
+    pub diagnostics: Vec<ServerConflictDiagnostic>,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub parse_errors: Vec<String>,
+}
+
+/// server 监听的 地址:端口 及是否为 default_server
+fn listen_addresses(server: &ServerBlock) -> Vec<(String, bool)> {
+    if server.listen.is_empty() {
+        return vec![(DEFAULT_LISTEN_ADDRESS.to_string(), false)];
+    }
+
+    server
+        .listen
+        .iter()
+        .filter_map(|value| {
+            let address = parse_listen_address(value)?;
+            let is_default = value
+                .split_whitespace()
+                .skip(1)
+                .any(|param| param == "default_server" || param == "default");
+            let host = match address.host {
+                Some(host) if host.contains(':') => format!("[{}]", host),
+                Some(host) => host,
+                None => "*".to_string(),
+            };
+            let suffix = if address.protocol == ListenProtocol::Udp { " udp" } else { "" };
+            Some((format!("{}:{}{}", host, address.port, suffix), is_default))
+        })
+        .collect()
+}
+
+fn meaningful_names(server: &ServerBlock) -> impl Iterator<Item = String> + '_ {
+    server
+        .server_name
+        .iter()
+        .map(|name| normalize_server_name(name))
+        .filter(|name| !name.is_empty() && name != "_")
+}
+
+fn is_pattern_name(name: &str) -> bool {
+    name.starts_with('~') || name.starts_with('*') || name.starts_with('.') || name.ends_with('*')
+}
+
+/// 用于检测通配符是否落入正则范围的示例主机名
+fn wildcard_sample(name: &str) -> String {
+    name.trim_start_matches('.').replace('*', "sample")
+}
+
+fn diagnostic(
+    kind: ServerConflictKind,
+    severity: LintSeverity,
+    address: &str,
+    server_name: Option<String>,
+    message: String,
+    members: &[usize],
+    servers: &[(String, ServerBlock)],
+) -> ServerConflictDiagnostic {
+    ServerConflictDiagnostic {
+        kind,
+        severity,
+        address: address.to_string(),
+        server_name,
+        message,
+        servers: members
+            .iter()
+            .map(|index| server_ref(&servers[*index].0, &servers[*index].1))
+            .collect(),
+    }
+}
+
+/// 对 active 中的 server 按监听地址分组检查冲突
+fn analyze_active_servers(servers: &[(String, ServerBlock)], active: &[usize]) -> Vec<ServerConflictDiagnostic> {
+    let mut groups: BTreeMap<String, Vec<(usize, bool)>> = BTreeMap::new();
+    for index in active {
+        for (address, is_default) in listen_addresses(&servers[*index].1) {
+            let members = groups.entry(address).or_default();
+            if !members.iter().any(|(member, _)| member == index) {
+                members.push((*index, is_default));
+            }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+    for (address, members) in &groups {
+        let defaults: Vec<usize> = members
+            .iter()
+            .filter(|(_, is_default)| *is_default)
+            .map(|(index, _)| *index)
+            .collect();
+        if defaults.len() > 1 {
+            diagnostics.push(diagnostic(
+                ServerConflictKind::DuplicateDefaultServer,
+                LintSeverity::Error,
+                address,
+                None,
+                format!("{} 上声明了 {} 个 default_server，nginx 将拒绝启动", address, defaults.len()),
+                &defaults,
+                servers,
+            ));
+        }
+
+        let mut names: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, _) in members {
+            for name in meaningful_names(&servers[*index].1).collect::<BTreeSet<_>>() {
+                names.entry(name).or_default().push(*index);
+            }
+        }
+        for (name, owners) in names.iter().filter(|(_, owners)| owners.len() > 1) {
+            diagnostics.push(diagnostic(
+                ServerConflictKind::DuplicateServerName,
+                LintSeverity::Warning,
+                address,
+                Some(name.clone()),
+                format!(
+                    "server_name {} 在 {} 上重复定义 {} 次，nginx 只使用第一个，其余被忽略",
+                    name,
+                    address,
+                    owners.len()
+                ),
+                owners,
+                servers,
+            ));
+        }
+
+        for (index, _) in members {
+            for name in meaningful_names(&servers[*index].1) {
+                let sample = if is_pattern_name(&name) {
+                    if name.starts_with('~') {
+                        continue;
+                    }
+                    wildcard_sample(&name)
+                } else {
+                    name.clone()
+                };
+
+                for (other, _) in members.iter().filter(|(other, _)| other != index) {
+                    for pattern in meaningful_names(&servers[*other].1).filter(|pattern| is_pattern_name(pattern)) {
+                        // 通配符只与正则比较，通配符之间按最长匹配各司其职
+                        if is_pattern_name(&name) && !pattern.starts_with('~') {
+                            continue;
+                        }
+                        if pattern == name || !server_name_matches(&pattern, &sample) {
+                            continue;
+                        }
+                        diagnostics.push(diagnostic(
+                            ServerConflictKind::NameOverlap,
+                            LintSeverity::Info,
+                            address,
+                            Some(name.clone()),
+                            format!(
+                                "{} 同时匹配 {}，按 nginx 优先级由声明 {} 的 server 处理",
+                                sample, pattern, name
+                            ),
+                            &[*index, *other],
+                            servers,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// 分析全部 server：启用的 server 之间的冲突，以及停用的 server 重新启用后的冲突
+pub(crate) fn analyze_server_conflicts_for(servers: &[(String, ServerBlock)]) -> Vec<ServerConflictDiagnostic> {
+    let enabled: Vec<usize> = (0..servers.len()).filter(|index| servers[*index].1.enabled).collect();
+    let mut diagnostics = analyze_active_servers(servers, &enabled);
+
+    for disabled in (0..servers.len()).filter(|index| !servers[*index].1.enabled) {
+        let mut active = enabled.clone();
+        active.push(disabled);
+        let disabled_id = &servers[disabled].1.id;
+        let disabled_file = &servers[disabled].0;
+
+        diagnostics.extend(
+            analyze_active_servers(servers, &active)
+                .into_iter()
+                .filter(|item| item.kind != ServerConflictKind::NameOverlap)
+                .filter(|item| {
+                    item.servers
+                        .iter()
+                        .any(|server| server.id == *disabled_id && server.file_path == *disabled_file)
+                })
+                .map(|item| ServerConflictDiagnostic {
+                    kind: ServerConflictKind::DisabledServerConflict,
+                    severity: LintSeverity::Warning,
+                    message: format!("停用的 server 重新启用后将产生冲突：{}", item.message),
+                    ..item
+                }),
+        );
+    }
+
+    diagnostics
+}
+
+/// 静态分析 listen / server_name 冲突
+#[tauri::command]
+pub async fn analyze_server_conflicts(config_path: String) -> Result<ServerConflictReport, String> {
+    if config_path.trim().is_empty() {
+        return Err("配置文件路径不能为空".to_string());
+    }
+
+    let (servers, parse_errors) = parse_config_servers(&resolve_config_files(&config_path));
+    let diagnostics = analyze_server_conflicts_for(&servers);
+
+    Ok(ServerConflictReport {
+        config_path,
+        error_count: diagnostics.iter().filter(|item| item.severity == LintSeverity::Error).count(),
+        warning_count: diagnostics.iter().filter(|item| item.severity == LintSeverity::Warning).count(),
+        diagnostics,
+        parse_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    fn servers_of(content: &str) -> Vec<(String, ServerBlock)> {
+        parse_nginx_config(content, "nginx.conf")
+            .expect("parse config")
+            .servers
+            .into_iter()
+            .map(|server| ("nginx.conf".to_string(), server))
+            .collect()
+    }
+
+    #[test]
+    fn analyze_should_find_duplicate_names_and_default_servers() {
+        let servers = servers_of(
+            r#"http {
+    server {
+        listen 80 default_server;
+        server_name example.com;
+    }
+    server {
+        listen 0.0.0.0:80 default_server;
+        server_name Example.com www.example.com;
+    }
+    server {
+        listen 127.0.0.1:80;
+        server_name example.com;
+    }
+    server {
+        listen 80;
+        server_name *.example.com;
+    }
+    server {
+        listen 80;
+        server_name ~^.+\.example\.com$;
+    }
+}"#,
+        );
+
+        let diagnostics = analyze_server_conflicts_for(&servers);
+        let kinds: Vec<(ServerConflictKind, Option<&str>)> = diagnostics
+            .iter()
+            .map(|item| (item.kind, item.server_name.as_deref()))
+            .collect();
+
+        assert!(kinds.contains(&(ServerConflictKind::DuplicateDefaultServer, None)));
+        let duplicate = diagnostics
+            .iter()
+            .find(|item| item.kind == ServerConflictKind::DuplicateServerName)
+            .expect("duplicate server_name");
+        assert_eq!(duplicate.address, "*:80");
+        assert_eq!(duplicate.servers.len(), 2);
+        // 127.0.0.1:80 是独立的地址，不与 *:80 冲突
+        assert_eq!(
+            kinds.iter().filter(|(kind, _)| *kind == ServerConflictKind::DuplicateServerName).count(),
+            1
+        );
+        assert!(kinds.contains(&(ServerConflictKind::NameOverlap, Some("www.example.com"))));
+        assert!(kinds.contains(&(ServerConflictKind::NameOverlap, Some("*.example.com"))));
+    }
+
+    #[test]
+    fn analyze_should_report_disabled_server_that_would_conflict() {
+        let mut servers = servers_of(
+            r#"http {
+    server {
+        listen 8080;
+        server_name app.local;
+    }
+    server {
+        listen 8080;
+        server_name app.local;
+    }
+}"#,
+        );
+        servers[1].1.enabled = false;
+
+        let diagnostics = analyze_server_conflicts_for(&servers);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, ServerConflictKind::DisabledServerConflict);
+        assert!(diagnostics[0].servers.iter().any(|server| !server.enabled));
+    }
+}
@@ -0,0 +1,349 @@
+use crate::config::{LocationBlock, ServerBlock};
+use crate::port_preflight::parse_listen_address;
+use regex::{Regex, RegexBuilder};
+
+/// server_name 的匹配形式
+enum ServerNamePattern {
+    Exact(String),
+    /// `*.example.com`
+    Suffix(String),
+    /// `.example.com` 同时匹配主域名
+    DomainAndSubdomains(String),
+    /// `www.example.*`
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl ServerNamePattern {
+    fn parse(name: &str) -> Option<Self> {
+        let name = normalize_server_name(name);
+        if name.is_empty() || name == "_" {
+            return None;
+        }
+
+        // 正则保留原文，转义如 \D、\S 大小写含义不同；主机名不区分大小写，因此按忽略大小写编译
+        if let Some(pattern) = name.strip_prefix('~') {
+            return RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .ok()
+                .map(Self::Regex);
+        }
+        if let Some(suffix) = name.strip_prefix('*') {
+            return Some(Self::Suffix(suffix.to_string()));
+        }
+        if let Some(domain) = name.strip_prefix('.') {
+            return Some(Self::DomainAndSubdomains(domain.to_string()));
+        }
+        if let Some(prefix) = name.strip_suffix('*') {
+            return Some(Self::Prefix(prefix.to_string()));
+        }
+
+        Some(Self::Exact(name))
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        match self {
+            Self::Exact(name) => host == name,
+            Self::Suffix(suffix) => host.ends_with(suffix.as_str()),
+            Self::DomainAndSubdomains(domain) => {
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }
+            Self::Prefix(prefix) => host.starts_with(prefix.as_str()),
+            Self::Regex(regex) => regex.is_match(host),
+        }
+    }
+}
+
+/// server_name 的命中类型，按 nginx 的优先级从高到低排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ServerNameMatchKind {
+    Exact,
+    /// `*.example.com` 或 `.example.com`
+    LeadingWildcard,
+    /// `www.example.*`
+    TrailingWildcard,
+    Regex,
+}
+
+impl ServerNamePattern {
+    fn match_kind(&self) -> ServerNameMatchKind {
+        match self {
+            Self::Exact(_) => ServerNameMatchKind::Exact,
+            Self::Suffix(_) | Self::DomainAndSubdomains(_) => ServerNameMatchKind::LeadingWildcard,
+            Self::Prefix(_) => ServerNameMatchKind::TrailingWildcard,
+            Self::Regex(_) => ServerNameMatchKind::Regex,
+        }
+    }
+}
+
+/// 按 nginx 的虚拟主机选择规则挑选 server 的依据
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ServerSelectionReason {
+    /// 命中的 server_name 及类型
+    Name(ServerNameMatchKind, String),
+    DefaultServer,
+    /// 没有声明 default_server 时，地址上的第一个 server 作为默认
+    FirstServer,
+}
+
+/// 在同一监听地址的候选 server 中按主机名选择：
+/// 精确名称 > 最长前导通配符 > 最长后缀通配符 > 按出现顺序的第一个正则 > default_server > 第一个 server
+pub(crate) fn select_server(
+    candidates: &[(&ServerBlock, bool)],
+    host: &str,
+) -> Option<(usize, ServerSelectionReason)> {
+    let host = normalize_host(host);
+    let mut best: Option<(ServerNameMatchKind, std::cmp::Reverse<usize>, usize, String)> = None;
+
+    for (index, (server, _)) in candidates.iter().enumerate() {
+        for name in &server.server_name {
+            let Some(pattern) = ServerNamePattern::parse(name) else {
+                continue;
+            };
+            if !pattern.matches(&host) {
+                continue;
+            }
+
+            let kind = pattern.match_kind();
+            // 通配符取最长，正则与精确名称取最先出现
+            let length = match kind {
+                ServerNameMatchKind::LeadingWildcard | ServerNameMatchKind::TrailingWildcard => name.trim().len(),
+                _ => 0,
+            };
+            let candidate = (kind, std::cmp::Reverse(length), index, name.trim().to_string());
+            if best.as_ref().is_none_or(|current| candidate < *current) {
+                best = Some(candidate);
+            }
+        }
+    }
+
+    if let Some((kind, _, index, name)) = best {
+        return Some((index, ServerSelectionReason::Name(kind, name)));
+    }
+    if let Some(index) = candidates.iter().position(|(_, is_default)| *is_default) {
+        return Some((index, ServerSelectionReason::DefaultServer));
+    }
+    (!candidates.is_empty()).then_some((0, ServerSelectionReason::FirstServer))
+}
+
+/// server 是否监听 port，未声明 listen 时按 *:80 处理
+pub(crate) fn listens_on_port(server: &ServerBlock, port: u16) -> bool {
+    if server.listen.is_empty() {
+        return port == 80;
+    }
+    server
+        .listen
+        .iter()
+        .filter_map(|value| parse_listen_address(value))
+        .any(|address| address.port == port)
+}
+
+/// server 是否声明为 default_server，指定 port 时只看该端口上的 listen
+pub(crate) fn is_default_server(server: &ServerBlock, port: Option<u16>) -> bool {
+    server.listen.iter().any(|value| {
+        let on_port = port.is_none_or(|port| parse_listen_address(value).is_some_and(|address| address.port == port));
+        on_port
+            && value
+                .split_whitespace()
+                .skip(1)
+                .any(|param| param == "default_server" || param == "default")
+    })
+}
+
+/// 单个 server_name 是否匹配主机名，`_` 与空名不匹配任何主机
+pub(crate) fn server_name_matches(name: &str, host: &str) -> bool {
+    ServerNamePattern::parse(name).is_some_and(|pattern| pattern.matches(&normalize_host(host)))
+}
+
+/// server_name 去除首尾空白，非正则形式统一为小写
+pub(crate) fn normalize_server_name(name: &str) -> String {
+    let name = name.trim();
+    if name.starts_with('~') {
+        name.to_string()
+    } else {
+        name.to_ascii_lowercase()
+    }
+}
+
+/// 统一为小写并去掉端口，IPv6 形如 [::1]:8080
+pub(crate) fn normalize_host(host: &str) -> String {
+    let host = host.trim().to_ascii_lowercase();
+    if host.starts_with('[') {
+        return host.split(']').next().unwrap_or("").trim_start_matches('[').to_string();
+    }
+    host.split(':').next().unwrap_or("").to_string()
+}
+
+fn location_regex(location: &LocationBlock) -> Option<Regex> {
+    let case_insensitive = match location.modifier.as_deref() {
+        Some("~") => false,
+        Some("~*") => true,
+        _ => return None,
+    };
+
+    RegexBuilder::new(&location.path)
+        .case_insensitive(case_insensitive)
+        .build()
+        .ok()
+}
+
+/// location 的命中类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LocationMatchKind {
+    Exact,
+    /// `^~` 前缀命中后不再检查正则
+    PrefixStopRegex,
+    Regex,
+    /// 没有正则命中时回退到最长前缀
+    LongestPrefix,
+}
+
+/// 按 nginx 的 location 选择顺序匹配请求路径，并返回命中依据
+pub(crate) fn select_location<'a>(
+    server: &'a ServerBlock,
+    uri: &str,
+) -> Option<(&'a LocationBlock, LocationMatchKind)> {
+    let path = uri.split('?').next().unwrap_or(uri);
+
+    if let Some(exact) = server
+        .locations
+        .iter()
+        .find(|location| location.modifier.as_deref() == Some("=") && location.path == path)
+    {
+        return Some((exact, LocationMatchKind::Exact));
+    }
+
+    let longest_prefix = server
+        .locations
+        .iter()
+        .filter(|location| matches!(location.modifier.as_deref(), None | Some("^~")))
+        .filter(|location| path.starts_with(location.path.as_str()))
+        .max_by_key(|location| location.path.len());
+
+    if let Some(prefix) = longest_prefix {
+        if prefix.modifier.as_deref() == Some("^~") {
+            return Some((prefix, LocationMatchKind::PrefixStopRegex));
+        }
+    }
+
+    server
+        .locations
+        .iter()
+        .find(|location| location_regex(location).is_some_and(|regex| regex.is_match(path)))
+        .map(|location| (location, LocationMatchKind::Regex))
+        .or(longest_prefix.map(|location| (location, LocationMatchKind::LongestPrefix)))
+}
+
+/// 按 nginx 的 location 选择顺序匹配请求路径：
+/// 精确匹配 > 最长前缀（^~ 时停止）> 按出现顺序的正则 > 最长前缀
+pub(crate) fn match_location<'a>(server: &'a ServerBlock, uri: &str) -> Option<&'a LocationBlock> {
+    select_location(server, uri).map(|(location, _)| location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_nginx_config;
+
+    #[test]
+    fn server_name_matcher_should_support_wildcards_and_ports() {
+        let content = r#"server {
+    listen 80;
+    server_name .example.com www.test.* ~^api\d+\.local$;
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let matches = |host: &str| config.servers[0].server_name.iter().any(|name| server_name_matches(name, host));
+
+        assert!(matches("example.com"));
+        assert!(matches("a.example.com:8443"));
+        assert!(matches("www.test.org"));
+        assert!(matches("API1.local"));
+        assert!(!matches("other.local"));
+    }
+
+    #[test]
+    fn server_name_regex_should_keep_escape_case_and_ignore_host_case() {
+        assert!(server_name_matches(r"~^\D+\.example\.com$", "shop.example.com"));
+        assert!(!server_name_matches(r"~^\D+\.example\.com$", "42.example.com"));
+        assert!(server_name_matches(r"~^API\.", "api.example.com"));
+        assert_eq!(normalize_server_name(" ~^\\S+$ "), "~^\\S+$");
+        assert_eq!(normalize_server_name("WWW.Example.com"), "www.example.com");
+    }
+
+    #[test]
+    fn match_location_should_follow_nginx_priority() {
+        let content = r#"server {
+    listen 80;
+    location / {
+        root html;
+    }
+    location ^~ /static/ {
+        root static;
+    }
+    location /api/ {
+        proxy_pass http://backend;
+    }
+    location ~* \.(png|jpg)$ {
+        expires 7d;
+    }
+    location = /health {
+        return 200;
+    }
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let server = &config.servers[0];
+        let path_of = |uri: &str| match_location(server, uri).map(|location| location.path.clone());
+
+        assert_eq!(path_of("/health").as_deref(), Some("/health"));
+        assert_eq!(path_of("/static/a.png").as_deref(), Some("/static/"));
+        assert_eq!(path_of("/api/logo.PNG").as_deref(), Some(r"\.(png|jpg)$"));
+        assert_eq!(path_of("/api/users?id=1").as_deref(), Some("/api/"));
+        assert_eq!(path_of("/index.html").as_deref(), Some("/"));
+        assert_eq!(
+            select_location(server, "/static/a.png").map(|(_, kind)| kind),
+            Some(LocationMatchKind::PrefixStopRegex)
+        );
+    }
+
+    #[test]
+    fn select_server_should_follow_server_name_priority() {
+        let content = r#"server {
+    listen 80;
+    server_name ~^(www\.)?example\.com$;
+}
+server {
+    listen 80;
+    server_name www.example.*;
+}
+server {
+    listen 80;
+    server_name *.example.com;
+}
+server {
+    listen 80;
+    server_name *.api.example.com;
+}
+server {
+    listen 80 default_server;
+    server_name www.example.com;
+}"#;
+        let config = parse_nginx_config(content, "test.conf").expect("parse config");
+        let candidates: Vec<(&ServerBlock, bool)> = config
+            .servers
+            .iter()
+            .enumerate()
+            .map(|(index, server)| (server, index == 4))
+            .collect();
+        let select = |host: &str| select_server(&candidates, host);
+
+        assert_eq!(
+            select("WWW.example.com:8080"),
+            Some((4, ServerSelectionReason::Name(ServerNameMatchKind::Exact, "www.example.com".to_string())))
+        );
+        assert_eq!(select("v1.api.example.com").map(|(index, _)| index), Some(3));
+        assert_eq!(select("www.example.org").map(|(index, _)| index), Some(1));
+        assert_eq!(select("example.com").map(|(index, _)| index), Some(0));
+        assert_eq!(select("unknown.test"), Some((4, ServerSelectionReason::DefaultServer)));
+    }
+}
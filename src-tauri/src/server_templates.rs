@@ -0,0 +1,639 @@
+use crate::config::parse_nginx_config;
+use crate::settings::get_app_config_dir;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const USER_TEMPLATES_FILE: &str = "server_templates.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TemplateParameterKind {
+    Text,
+    /// 空格分隔的 server_name 列表
+    HostList,
+    Port,
+    Number,
+    /// 作为 {{#name}} 区块条件，内联时渲染为 on / off
+    Boolean,
+    Path,
+    /// proxy_pass / fastcgi_pass 等上游地址
+    Upstream,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateParameter {
+    pub name: String,
+    pub label: String,
+    pub kind: TemplateParameterKind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 内置模板不可修改或删除
+    #[serde(default)]
+    pub builtin: bool,
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+    /// 模板正文：{{name}} 替换参数，独占一行的 {{#name}} / {{^name}} ... {{/name}} 按参数是否有值保留或移除
+    pub body: String,
+}
+
+fn placeholder_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").expect("valid placeholder regex"))
+}
+
+fn param(name: &str, label: &str, kind: TemplateParameterKind, required: bool, default: Option<&str>) -> TemplateParameter {
+    TemplateParameter {
+        name: name.to_string(),
+        label: label.to_string(),
+        kind,
+        required,
+        default: default.map(str::to_string),
+        description: None,
+    }
+}
+
+fn server_params(default_port: &str) -> Vec<TemplateParameter> {
+    vec![
+        param("server_names", "域名", TemplateParameterKind::HostList, true, None),
+        param("listen_port", "监听端口", TemplateParameterKind::Port, true, Some(default_port)),
+    ]
+}
+
+fn builtin(id: &str, name: &str, description: &str, extra: Vec<TemplateParameter>, default_port: &str, body: &str) -> ServerTemplate {
+    let mut parameters = server_params(default_port);
+    parameters.extend(extra);
+    ServerTemplate {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: description.to_string(),
+        builtin: true,
+        parameters,
+        body: body.to_string(),
+    }
+}
+
+const REVERSE_PROXY_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    client_max_body_size {{client_max_body_size}};
+
+    location / {
+        proxy_pass {{upstream}};
+        proxy_set_header Host $host;
+        proxy_set_header X-Real-IP $remote_addr;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+        proxy_set_header X-Forwarded-Proto $scheme;
+    }
+}"#;
+
+const SPA_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    root {{root}};
+    index index.html;
+
+    location / {
+        try_files $uri $uri/ /index.html;
+    }
+{{#cache_assets}}
+
+    location ~* \.(?:js|css|png|jpg|jpeg|gif|svg|ico|woff2?)$ {
+        expires 30d;
+        add_header Cache-Control "public";
+        try_files $uri =404;
+    }
+{{/cache_assets}}
+{{#api_upstream}}
+
+    location /api/ {
+        proxy_pass {{api_upstream}};
+        proxy_set_header Host $host;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+    }
+{{/api_upstream}}
+}"#;
+
+const STATIC_SITE_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    root {{root}};
+    index {{index}};
+{{#gzip}}
+    gzip on;
+    gzip_types text/plain text/css application/javascript application/json image/svg+xml;
+{{/gzip}}
+
+    location / {
+        try_files $uri $uri/ =404;
+    }
+
+    location ~ /\.(?!well-known) {
+        deny all;
+    }
+}"#;
+
+const PHP_FPM_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    root {{root}};
+    index index.php index.html;
+
+    location / {
+        try_files $uri $uri/ /index.php?$query_string;
+    }
+
+    location ~ \.php$ {
+        try_files $uri =404;
+        fastcgi_split_path_info ^(.+\.php)(/.+)$;
+        fastcgi_pass {{fastcgi_pass}};
+        fastcgi_index index.php;
+        include fastcgi_params;
+        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
+    }
+
+    location ~ /\.(?!well-known) {
+        deny all;
+    }
+}"#;
+
+const WEBSOCKET_PROXY_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+
+    location {{ws_path}} {
+        proxy_pass {{upstream}};
+        proxy_http_version 1.1;
+        proxy_set_header Upgrade $http_upgrade;
+        proxy_set_header Connection "upgrade";
+        proxy_set_header Host $host;
+        proxy_read_timeout {{read_timeout}};
+    }
+
+    location / {
+        proxy_pass {{upstream}};
+        proxy_set_header Host $host;
+        proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;
+    }
+}"#;
+
+const GRPC_PROXY_BODY: &str = r#"server {
+    listen {{listen_port}};
+    http2 on;
+    server_name {{server_names}};
+
+    location / {
+        grpc_pass grpc://{{upstream}};
+        grpc_set_header Host $host;
+        grpc_read_timeout {{read_timeout}};
+    }
+}"#;
+
+const FILE_DOWNLOAD_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    root {{root}};
+
+    location / {
+        sendfile on;
+        tcp_nopush on;
+        max_ranges {{max_ranges}};
+        default_type application/octet-stream;
+        add_header Content-Disposition "attachment";
+{{#limit_rate}}
+        limit_rate {{limit_rate}};
+{{/limit_rate}}
+    }
+}"#;
+
+const MAINTENANCE_BODY: &str = r#"server {
+    listen {{listen_port}};
+    server_name {{server_names}};
+    root {{root}};
+    add_header Retry-After {{retry_after}} always;
+
+    error_page 503 /{{page}};
+
+    location = /{{page}} {
+        internal;
+    }
+
+    location / {
+        return 503;
+    }
+}"#;
+
+pub(crate) fn builtin_templates() -> Vec<ServerTemplate> {
+    use TemplateParameterKind::*;
+
+    vec![
+        builtin(
+            "reverse-proxy",
+            "反向代理",
+            "将请求转发到后端服务",
+            vec![
+                param("upstream", "后端地址", Upstream, true, Some("http://127.0.0.1:3000")),
+                param("client_max_body_size", "请求体上限", Text, true, Some("10m")),
+            ],
+            "80",
+            REVERSE_PROXY_BODY,
+        ),
+        builtin(
+            "spa",
+            "单页应用",
+            "前端路由回退到 index.html，可选静态资源缓存与 API 代理",
+            vec![
+                param("root", "站点目录", Path, true, Some("html")),
+                param("cache_assets", "缓存静态资源", Boolean, false, Some("true")),
+                param("api_upstream", "API 后端地址", Upstream, false, None),
+            ],
+            "80",
+            SPA_BODY,
+        ),
+        builtin(
+            "static-site",
+            "静态站点",
+            "托管静态文件并禁止访问隐藏文件",
+            vec![
+                param("root", "站点目录", Path, true, Some("html")),
+                param("index", "首页文件", Text, true, Some("index.html index.htm")),
+                param("gzip", "启用 gzip", Boolean, false, Some("true")),
+            ],
+            "80",
+            STATIC_SITE_BODY,
+        ),
+        builtin(
+            "php-fpm",
+            "PHP-FPM",
+            "通过 FastCGI 运行 PHP 应用",
+            vec![
+                param("root", "站点目录", Path, true, Some("html")),
+                param("fastcgi_pass", "PHP-FPM 地址", Upstream, true, Some("127.0.0.1:9000")),
+            ],
+            "80",
+            PHP_FPM_BODY,
+        ),
+        builtin(
+            "websocket-proxy",
+            "WebSocket 代理",
+            "为指定路径升级 WebSocket 连接，其余请求普通代理",
+            vec![
+                param("upstream", "后端地址", Upstream, true, Some("http://127.0.0.1:3000")),
+                param("ws_path", "WebSocket 路径", Path, true, Some("/ws")),
+                param("read_timeout", "读取超时", Text, true, Some("3600s")),
+            ],
+            "80",
+            WEBSOCKET_PROXY_BODY,
+        ),
+        builtin(
+            "grpc-proxy",
+            "gRPC 代理",
+            "通过 HTTP/2 转发 gRPC 请求",
+            vec![
+                param("upstream", "gRPC 服务地址", Upstream, true, Some("127.0.0.1:50051")),
+                param("read_timeout", "读取超时", Text, true, Some("300s")),
+            ],
+            "8080",
+            GRPC_PROXY_BODY,
+        ),
+        builtin(
+            "file-download",
+            "文件下载",
+            "支持断点续传（Range）的文件下载站点，可选限速",
+            vec![
+                param("root", "文件目录", Path, true, Some("downloads")),
+                param("max_ranges", "最大 Range 数", Number, true, Some("16")),
+                param("limit_rate", "单连接限速", Text, false, None),
+            ],
+            "80",
+            FILE_DOWNLOAD_BODY,
+        ),
+        builtin(
+            "maintenance",
+            "维护页面",
+            "所有请求返回 503 与维护页面",
+            vec![
+                param("root", "维护页面目录", Path, true, Some("html")),
+                param("page", "维护页面文件", Text, true, Some("maintenance.html")),
+                param("retry_after", "Retry-After 秒数", Number, true, Some("3600")),
+            ],
+            "80",
+            MAINTENANCE_BODY,
+        ),
+    ]
+}
+
+// ==================== 用户模板 ====================
+
+fn user_templates_path() -> Result<PathBuf, String> {
+    Ok(get_app_config_dir()?.join(USER_TEMPLATES_FILE))
+}
+
+fn read_user_templates() -> Result<Vec<ServerTemplate>, String> {
+    let path = user_templates_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let json = fs::read_to_string(&path).map_err(|e| format!("读取模板文件失败: {}", e))?;
+    let templates: Vec<ServerTemplate> = serde_json::from_str(&json).map_err(|e| format!("解析模板文件失败: {}", e))?;
+    Ok(templates
+        .into_iter()
+        .map(|template| ServerTemplate { builtin: false, ..template })
+        .collect())
+}
+
+fn write_user_templates(templates: &[ServerTemplate]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(templates).map_err(|e| format!("序列化模板失败: {}", e))?;
+    fs::write(user_templates_path()?, json).map_err(|e| format!("写入模板文件失败: {}", e))
+}
+
+fn all_templates() -> Result<Vec<ServerTemplate>, String> {
+    let mut templates = builtin_templates();
+    templates.extend(read_user_templates()?);
+    Ok(templates)
+}
+
+// ==================== 渲染 ====================
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.trim().to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// 校验并规范化参数值；返回 None 表示参数未设置（区块条件为假）
+fn resolve_parameter(parameter: &TemplateParameter, value: Option<&Value>) -> Result<Option<String>, String> {
+    let raw = value
+        .and_then(value_to_string)
+        .filter(|value| !value.is_empty())
+        .or_else(|| parameter.default.clone().filter(|value| !value.is_empty()));
+    let Some(raw) = raw else {
+        if parameter.required {
+            return Err(format!("缺少参数：{}", parameter.label));
+        }
+        return Ok(None);
+    };
+
+    // 参数值会直接写入配置，不允许包含能结束指令或块的字符
+    if raw.contains([';', '{', '}', '\n', '\r', '#']) {
+        return Err(format!("参数 {} 不能包含 ; {{ }} # 或换行", parameter.label));
+    }
+
+    let invalid = || format!("参数 {} 的值无效：{}", parameter.label, raw);
+    match parameter.kind {
+        TemplateParameterKind::Port => match raw.parse::<u16>() {
+            Ok(port) if port > 0 => Ok(Some(port.to_string())),
+            _ => Err(invalid()),
+        },
+        TemplateParameterKind::Number => raw.parse::<u64>().map(|number| Some(number.to_string())).map_err(|_| invalid()),
+        TemplateParameterKind::Boolean => match raw.as_str() {
+            "true" | "on" | "1" => Ok(Some("on".to_string())),
+            "false" | "off" | "0" => Ok(None),
+            _ => Err(invalid()),
+        },
+        TemplateParameterKind::HostList => Ok(Some(raw.split_whitespace().collect::<Vec<_>>().join(" "))),
+        TemplateParameterKind::Upstream | TemplateParameterKind::Path if raw.chars().any(char::is_whitespace) => Err(invalid()),
+        _ => Ok(Some(raw)),
+    }
+}
+
+fn section_marker(line: &str) -> Option<(char, &str)> {
+    let inner = line.trim().strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    let mut chars = inner.chars();
+    let marker = chars.next().filter(|c| matches!(c, '#' | '^' | '/'))?;
+    Some((marker, chars.as_str().trim()))
+}
+
+/// 按参数渲染模板，结果需至少包含一个 server 块
+pub(crate) fn render_template(template: &ServerTemplate, values: &BTreeMap<String, Value>) -> Result<String, String> {
+    let mut resolved: BTreeMap<&str, Option<String>> = BTreeMap::new();
+    for parameter in &template.parameters {
+        resolved.insert(parameter.name.as_str(), resolve_parameter(parameter, values.get(&parameter.name))?);
+    }
+    let lookup = |name: &str| -> Result<Option<&String>, String> {
+        resolved
+            .get(name)
+            .map(Option::as_ref)
+            .ok_or_else(|| format!("模板引用了未声明的参数：{}", name))
+    };
+
+    let mut output = Vec::new();
+    // 每层区块是否保留
+    let mut sections: Vec<(String, bool)> = Vec::new();
+    for line in template.body.lines() {
+        if let Some((marker, name)) = section_marker(line) {
+            match marker {
+                '/' => {
+                    let (open, _) = sections.pop().ok_or_else(|| format!("多余的区块结束标记：{}", name))?;
+                    if open != name {
+                        return Err(format!("区块 {} 与结束标记 {} 不匹配", open, name));
+                    }
+                }
+                _ => {
+                    let present = lookup(name)?.is_some();
+                    sections.push((name.to_string(), if marker == '#' { present } else { !present }));
+                }
+            }
+            continue;
+        }
+        if sections.iter().any(|(_, keep)| !keep) {
+            continue;
+        }
+
+        let mut error = None;
+        let rendered = placeholder_regex().replace_all(line, |captures: &regex::Captures| match lookup(&captures[1]) {
+            Ok(value) => value.cloned().unwrap_or_default(),
+            Err(message) => {
+                error.get_or_insert(message);
+                String::new()
+            }
+        });
+        if let Some(message) = error {
+            return Err(message);
+        }
+        output.push(rendered.into_owned());
+    }
+    if let Some((open, _)) = sections.pop() {
+        return Err(format!("区块 {} 缺少结束标记", open));
+    }
+
+    let text = output.join("\n");
+    let parsed = parse_nginx_config(&text, "").map_err(|e| format!("模板渲染结果无法解析: {}", e))?;
+    if parsed.servers.is_empty() {
+        return Err("模板渲染结果中没有 server 块".to_string());
+    }
+    Ok(text)
+}
+
+/// 使用参数默认值或示例值试渲染，检查用户模板是否可用
+fn validate_template(template: &ServerTemplate) -> Result<(), String> {
+    if template.id.trim().is_empty() || template.name.trim().is_empty() {
+        return Err("模板 ID 与名称不能为空".to_string());
+    }
+    if !template.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err("模板 ID 只能包含字母、数字、- 与 _".to_string());
+    }
+    if builtin_templates().iter().any(|builtin| builtin.id == template.id) {
+        return Err(format!("模板 ID {} 与内置模板冲突", template.id));
+    }
+
+    let samples: BTreeMap<String, Value> = template
+        .parameters
+        .iter()
+        .filter(|parameter| parameter.default.is_none())
+        .map(|parameter| {
+            let sample = match parameter.kind {
+                TemplateParameterKind::HostList => Value::from("example.com"),
+                TemplateParameterKind::Port => Value::from(80),
+                TemplateParameterKind::Number => Value::from(1),
+                TemplateParameterKind::Boolean => Value::from(true),
+                TemplateParameterKind::Path => Value::from("/srv/www"),
+                TemplateParameterKind::Upstream => Value::from("http://127.0.0.1:3000"),
+                TemplateParameterKind::Text => Value::from("sample"),
+            };
+            (parameter.name.clone(), sample)
+        })
+        .collect();
+    render_template(template, &samples).map(|_| ())
+}
+
+/// 列出内置模板与用户模板
+#[tauri::command]
+pub async fn list_server_templates() -> Result<Vec<ServerTemplate>, String> {
+    all_templates()
+}
+
+/// 新增或更新用户模板
+#[tauri::command]
+pub async fn save_server_template(template: ServerTemplate) -> Result<ServerTemplate, String> {
+    let template = ServerTemplate { builtin: false, ..template };
+    validate_template(&template)?;
+
+    let mut templates = read_user_templates()?;
+    match templates.iter_mut().find(|existing| existing.id == template.id) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    write_user_templates(&templates)?;
+    Ok(template)
+}
+
+/// 删除用户模板
+#[tauri::command]
+pub async fn delete_server_template(template_id: String) -> Result<(), String> {
+    if builtin_templates().iter().any(|builtin| builtin.id == template_id) {
+        return Err("内置模板不能删除".to_string());
+    }
+
+    let mut templates = read_user_templates()?;
+    let before = templates.len();
+    templates.retain(|template| template.id != template_id);
+    if templates.len() == before {
+        return Err(format!("未找到模板 {}", template_id));
+    }
+    write_user_templates(&templates)
+}
+
+/// 渲染模板为 server 块文本，结果再交给 generate_add_server_content 校验与添加
+#[tauri::command]
+pub async fn render_server_template(template_id: String, values: BTreeMap<String, Value>) -> Result<String, String> {
+    let template = all_templates()?
+        .into_iter()
+        .find(|template| template.id == template_id)
+        .ok_or_else(|| format!("未找到模板 {}", template_id))?;
+    render_template(&template, &values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, Value)]) -> BTreeMap<String, Value> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.clone())).collect()
+    }
+
+    #[test]
+    fn builtin_templates_should_render_with_defaults() {
+        let templates = builtin_templates();
+        assert_eq!(templates.len(), 8);
+
+        for template in &templates {
+            let rendered = render_template(template, &values(&[("server_names", Value::from("app.local  www.app.local"))]))
+                .unwrap_or_else(|error| panic!("{}: {}", template.id, error));
+            assert!(rendered.contains("server_name app.local www.app.local;"), "{}", template.id);
+            assert!(!rendered.contains("{{"), "{}", template.id);
+            let parsed = parse_nginx_config(&rendered, "").unwrap();
+            assert_eq!(parsed.servers.len(), 1, "{}", template.id);
+        }
+
+        let spa = templates.iter().find(|template| template.id == "spa").unwrap();
+        let rendered = render_template(
+            spa,
+            &values(&[
+                ("server_names", Value::from("app.local")),
+                ("listen_port", Value::from(8080)),
+                ("cache_assets", Value::from(false)),
+                ("api_upstream", Value::from("http://127.0.0.1:4000/")),
+            ]),
+        )
+        .unwrap();
+        assert!(rendered.contains("listen 8080;"));
+        assert!(!rendered.contains("expires 30d"));
+        assert!(rendered.contains("proxy_pass http://127.0.0.1:4000/;"));
+
+        // listen 的 http2 参数自 1.25.1 起已弃用，使用独立的 http2 指令
+        let grpc = templates.iter().find(|template| template.id == "grpc-proxy").unwrap();
+        let rendered = render_template(grpc, &values(&[("server_names", Value::from("rpc.local"))])).unwrap();
+        assert!(rendered.contains("    http2 on;"));
+        assert!(!rendered.contains(" http2;"));
+    }
+
+    #[test]
+    fn render_and_validate_should_reject_invalid_input() {
+        let proxy = builtin_templates().into_iter().find(|template| template.id == "reverse-proxy").unwrap();
+        assert!(render_template(&proxy, &values(&[])).unwrap_err().contains("域名"));
+        assert!(render_template(&proxy, &values(&[("server_names", Value::from("a.com")), ("listen_port", Value::from(70000))])).is_err());
+        assert!(render_template(&proxy, &values(&[("server_names", Value::from("a.com; root /"))])).is_err());
+
+        let custom = ServerTemplate {
+            id: "custom".to_string(),
+            name: "自定义".to_string(),
+            description: String::new(),
+            builtin: true,
+            parameters: vec![param("server_names", "域名", TemplateParameterKind::HostList, true, None)],
+            body: "server {\n    server_name {{server_names}};\n{{#missing}}\n    return 200;\n{{/missing}}\n}".to_string(),
+        };
+        assert!(validate_template(&custom).unwrap_err().contains("missing"));
+
+        let duplicate = ServerTemplate { id: "spa".to_string(), ..custom.clone() };
+        assert!(validate_template(&duplicate).is_err());
+
+        let fixed = ServerTemplate {
+            body: "server {\n    server_name {{server_names}};\n}".to_string(),
+            ..custom
+        };
+        assert!(validate_template(&fixed).is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -11,6 +12,18 @@ pub struct AppSettings {
     pub language: String,
     pub log_retention_days: u32,
     pub auto_reload_after_save: bool,
+    /// 按规则 id 覆盖 lint 规则的启用状态与级别
+    pub lint_rules: BTreeMap<String, LintRuleSetting>,
+    /// 证书在该天数内过期时提醒
+    pub cert_expiry_warning_days: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LintRuleSetting {
+    pub disabled: bool,
+    /// error / warning / info，为空时使用规则默认级别
+    pub severity: Option<String>,
 }
 
 impl Default for AppSettings {
@@ -22,6 +35,8 @@ impl Default for AppSettings {
             language: "zh-CN".to_string(),
             log_retention_days: 30,
             auto_reload_after_save: true,
+            lint_rules: BTreeMap::new(),
+            cert_expiry_warning_days: 30,
         }
     }
 }
@@ -60,9 +75,8 @@ pub async fn save_app_settings(settings: AppSettings) -> Result<bool, String> {
     Ok(true)
 }
 
-/// 加载应用设置
-#[tauri::command]
-pub async fn load_app_settings() -> Result<AppSettings, String> {
+/// 从磁盘读取应用设置（同步版本，供后端内部模块复用）
+pub fn read_app_settings() -> Result<AppSettings, String> {
     let settings_path = get_settings_path()?;
 
     if !settings_path.exists() {
@@ -79,6 +93,12 @@ pub async fn load_app_settings() -> Result<AppSettings, String> {
     Ok(settings)
 }
 
+/// 加载应用设置
+#[tauri::command]
+pub async fn load_app_settings() -> Result<AppSettings, String> {
+    read_app_settings()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -28,6 +28,8 @@ export interface ConfigOperationResult {
   level: 'success' | 'warning' | 'error' | 'info';
   message: string;
   operation: 'toggle-server-state' | 'save-server' | 'save-server-reload';
+  /** 结果已由后端审计层写入操作日志，前端无需重复记录 */
+  audited: boolean;
 }
 
 // ---- 事件名常量 ----
@@ -1,9 +1,30 @@
 import { defineStore } from 'pinia';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import type {
+  CertificateInventory,
+  ChangePlan,
+  DevCertificateResult,
+  EnableHttpsRequest,
+  ConfigDriftReport,
+  ConfigModelExport,
+  ConfigModelPlan,
+  ConfigFileChangedEvent,
   ConfigSearchMode,
+  ConfigWatchStatus,
+  ImportReport,
+  ImportSourceFormat,
+  LintReport,
+  RouteSimulationResult,
+  SecurityAuditReport,
+  ServerConflictReport,
   NginxConfig,
+  ProxyLocationPlan,
+  ProxyWizardRequest,
+  ProxyWizardResult,
   ServerBlock,
+  ServerTemplate,
   LocationBlock,
 } from '@/types/config';
 import { useLogStore } from './log';
@@ -15,6 +36,10 @@ interface ParseResult {
   config?: NginxConfig;
 }
 
+const CONFIG_FILE_CHANGED_EVENT = 'config-file-changed';
+
+let unlistenConfigChanges: UnlistenFn | null = null;
+
 interface ConfigState {
   config: NginxConfig | null;
   loading: boolean;
@@ -25,6 +50,9 @@ interface ConfigState {
   categoryFilter: string | null;
   selectedServerId: string | null;
   selectedLocationId: string | null;
+  watchedFiles: string[];
+  diskDiffersFromRunning: boolean | null;
+  lastFileChange: ConfigFileChangedEvent | null;
 }
 
 const extractListenPort = (listen: string) => {
@@ -52,6 +80,9 @@ export const useConfigStore = defineStore('config', {
     categoryFilter: null,
     selectedServerId: null,
     selectedLocationId: null,
+    watchedFiles: [],
+    diskDiffersFromRunning: null,
+    lastFileChange: null,
   }),
 
   getters: {
@@ -231,6 +262,7 @@ export const useConfigStore = defineStore('config', {
 
           // 记录日志
           logStore.info(`配置文件加载成功: ${configPath}`);
+          void this.watchConfigFiles(configPath);
 
           eventBus.emit(EVENTS.CONFIG_LOADED, { success: true });
           return { success: true };
@@ -260,6 +292,408 @@ export const useConfigStore = defineStore('config', {
       }
     },
 
+    /**
+     * 监听配置文件及 include 文件，外部修改后自动重新加载
+     * @param configPath 配置文件路径
+     */
+    async watchConfigFiles(configPath: string) {
+      if (!unlistenConfigChanges) {
+        unlistenConfigChanges = await listen<ConfigFileChangedEvent>(CONFIG_FILE_CHANGED_EVENT, (event) => {
+          const change = event.payload;
+          if (change.configPath !== this.config?.filePath) {
+            return;
+          }
+
+          this.watchedFiles = change.watchedFiles;
+          this.diskDiffersFromRunning = change.diskDiffersFromRunning;
+          // 应用自身保存后已重新加载，无需再次提示
+          if (change.ownWrite) {
+            return;
+          }
+
+          this.lastFileChange = change;
+
+          const { added, removed, changed } = change.summary;
+          useLogStore().info(
+            `检测到配置文件变化: 新增 ${added.length} / 删除 ${removed.length} / 修改 ${changed.length} 个 server`
+          );
+          if (!this.loading) {
+            void this.loadConfig(change.configPath);
+          }
+        });
+
+        // 启动、重启、重载后运行中的配置可能已与磁盘一致
+        eventBus.on(EVENTS.NGINX_OPERATION_RESULT, () => {
+          void this.refreshWatchStatus();
+        });
+      }
+
+      try {
+        const status = await invoke<ConfigWatchStatus>('start_config_watch', { configPath });
+        this.watchedFiles = status.watchedFiles;
+        this.diskDiffersFromRunning = status.diskDiffersFromRunning;
+      } catch (error) {
+        console.error('监听配置文件失败:', error);
+      }
+    },
+
+    /**
+     * 刷新磁盘配置与运行配置是否一致的状态
+     */
+    async refreshWatchStatus() {
+      if (!this.config) {
+        return;
+      }
+
+      try {
+        const status = await invoke<ConfigWatchStatus>('get_config_watch_status', {
+          configPath: this.config.filePath,
+        });
+        this.watchedFiles = status.watchedFiles;
+        this.diskDiffersFromRunning = status.diskDiffersFromRunning;
+      } catch (error) {
+        console.error('获取配置监听状态失败:', error);
+      }
+    },
+
+    /**
+     * 获取尚未生效的配置差异
+     */
+    async fetchConfigDrift(): Promise<ConfigDriftReport | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const report = await invoke<ConfigDriftReport>('get_config_drift', {
+        configPath: this.config.filePath,
+      });
+      this.diskDiffersFromRunning =
+        report.status === 'unknown' ? null : report.status === 'drifted';
+      return report;
+    },
+
+    /**
+     * 分析 listen / server_name 冲突
+     */
+    async analyzeServerConflicts(): Promise<ServerConflictReport | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<ServerConflictReport>('analyze_server_conflicts', {
+        configPath: this.config.filePath,
+      });
+    },
+
+    /**
+     * 模拟请求会被哪个 server / location 处理
+     * @param url 请求地址
+     * @param hostHeader 可选的 Host 请求头
+     */
+    async simulateRoute(url: string, hostHeader?: string): Promise<RouteSimulationResult | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<RouteSimulationResult>('simulate_nginx_route', {
+        request: {
+          configPath: this.config.filePath,
+          url,
+          hostHeader: hostHeader || null,
+        },
+      });
+    },
+
+    /**
+     * 按 lint 规则检查当前配置及 include 文件
+     */
+    async lintConfig(): Promise<LintReport | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<LintReport>('lint_nginx_config', {
+        configPath: this.config.filePath,
+      });
+    },
+
+    /**
+     * 对当前配置中的 server 执行安全审计
+     */
+    async auditSecurity(): Promise<SecurityAuditReport | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<SecurityAuditReport>('audit_nginx_security', {
+        configPath: this.config.filePath,
+      });
+    },
+
+    /**
+     * 选择保存位置并导出安全审计报告
+     * @returns 导出的文件路径，取消时返回 null
+     */
+    async exportSecurityAudit(format: 'json' | 'markdown'): Promise<string | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const extension = format === 'json' ? 'json' : 'md';
+      const outputPath = await save({
+        defaultPath: `nginx-security-audit.${extension}`,
+        filters: [{ name: format === 'json' ? 'JSON' : 'Markdown', extensions: [extension] }],
+      });
+      if (!outputPath) {
+        return null;
+      }
+
+      await invoke('export_security_audit', {
+        configPath: this.config.filePath,
+        outputPath,
+        format,
+      });
+      return outputPath;
+    },
+
+    /**
+     * 获取配置中引用的 TLS 证书清单，过期提醒天数取自应用设置
+     */
+    async fetchCertificateInventory(): Promise<CertificateInventory | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<CertificateInventory>('get_certificate_inventory', {
+        configPath: this.config.filePath,
+        warningDays: null,
+      });
+    },
+
+    /**
+     * 为 server 生成开发证书并启用 HTTPS，成功后重新加载配置
+     * @param serverId Server 块 ID
+     * @param useDevCa 是否由本地开发 CA 签发
+     */
+    async generateDevCertificate(
+      serverId: string,
+      useDevCa: boolean,
+      validityDays?: number
+    ): Promise<DevCertificateResult | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const result = await invoke<DevCertificateResult>('generate_dev_certificate', {
+        request: {
+          configPath: this.config.filePath,
+          serverId,
+          useDevCa,
+          validityDays: validityDays ?? null,
+        },
+      });
+      if (result.success) {
+        await this.reloadConfig();
+      }
+      return result;
+    },
+
+    /**
+     * 生成启用 HTTPS 的变更计划（含 nginx -t 校验结果），用于审核后再应用
+     */
+    async generateEnableHttpsContent(request: EnableHttpsRequest): Promise<ChangePlan | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<ChangePlan>('generate_enable_https_content', {
+        configPath: this.config.filePath,
+        request,
+      });
+    },
+
+    /**
+     * 应用启用 HTTPS 的修改并重新加载配置
+     */
+    async enableServerHttps(request: EnableHttpsRequest): Promise<{ success: boolean; message: string } | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const result = await invoke<{ success: boolean; message: string }>('enable_server_https', {
+        configPath: this.config.filePath,
+        request,
+      });
+      if (result.success) {
+        await this.reloadConfig();
+      }
+      return result;
+    },
+
+    /**
+     * 获取内置与用户自定义的 server 模板
+     */
+    async listServerTemplates(): Promise<ServerTemplate[]> {
+      return invoke<ServerTemplate[]>('list_server_templates');
+    },
+
+    /**
+     * 保存用户模板（同 ID 覆盖）
+     */
+    async saveServerTemplate(template: ServerTemplate): Promise<ServerTemplate> {
+      return invoke<ServerTemplate>('save_server_template', { template });
+    },
+
+    /**
+     * 删除用户模板
+     */
+    async deleteServerTemplate(templateId: string): Promise<void> {
+      await invoke('delete_server_template', { templateId });
+    },
+
+    /**
+     * 按参数渲染模板为 server 块文本，之后走新增 server 的校验与保存流程
+     */
+    async renderServerTemplate(
+      templateId: string,
+      values: Record<string, string | number | boolean | null>
+    ): Promise<string> {
+      return invoke<string>('render_server_template', { templateId, values });
+    },
+
+    /**
+     * 预览反向代理向导生成的 location
+     */
+    async buildProxyLocation(request: ProxyWizardRequest): Promise<ProxyWizardResult> {
+      return invoke<ProxyWizardResult>('build_proxy_location', { request });
+    },
+
+    /**
+     * 生成添加反向代理 location 的变更计划（含 nginx -t 校验结果），用于审核后再应用
+     */
+    async generateProxyLocationContent(
+      serverId: string,
+      request: ProxyWizardRequest
+    ): Promise<ProxyLocationPlan | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<ProxyLocationPlan>('generate_proxy_location_content', {
+        configPath: this.config.filePath,
+        serverId,
+        request,
+      });
+    },
+
+    /**
+     * 添加反向代理 location 并重新加载配置
+     */
+    async addProxyLocation(
+      serverId: string,
+      request: ProxyWizardRequest
+    ): Promise<{ success: boolean; message: string } | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const result = await invoke<{ success: boolean; message: string }>('add_proxy_location', {
+        configPath: this.config.filePath,
+        serverId,
+        request,
+      });
+      if (result.success) {
+        await this.reloadConfig();
+      }
+      return result;
+    },
+
+    /**
+     * 选择 Apache / Caddy 配置文件并转换为待审核的 server 块
+     * @returns 用户取消选择时返回 null
+     */
+    async importForeignConfig(format: ImportSourceFormat): Promise<ImportReport | null> {
+      const sourcePath = await open({
+        multiple: false,
+        directory: false,
+        title: format === 'apache' ? '选择 Apache 配置文件' : '选择 Caddyfile',
+      });
+      if (!sourcePath || Array.isArray(sourcePath)) {
+        return null;
+      }
+
+      return invoke<ImportReport>('import_foreign_config', { sourcePath, format });
+    },
+
+    /**
+     * 将当前配置导出为声明式模型，模型只覆盖主配置文件
+     * @returns 导出结果，含未覆盖的 include 文件；用户取消时返回 null
+     */
+    async exportConfigModel(format: 'json' | 'yaml'): Promise<ConfigModelExport | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const outputPath = await save({
+        defaultPath: `nginx-config-model.${format}`,
+        filters: [{ name: format.toUpperCase(), extensions: format === 'json' ? ['json'] : ['yaml', 'yml'] }],
+      });
+      if (!outputPath) {
+        return null;
+      }
+
+      return invoke<ConfigModelExport>('export_config_model', {
+        configPath: this.config.filePath,
+        outputPath,
+        format,
+      });
+    },
+
+    /**
+     * 选择配置模型文件
+     */
+    async selectConfigModelFile(): Promise<string | null> {
+      const specPath = await open({
+        multiple: false,
+        directory: false,
+        filters: [{ name: '配置模型', extensions: ['json', 'yaml', 'yml'] }],
+      });
+      return typeof specPath === 'string' ? specPath : null;
+    },
+
+    /**
+     * 计算应用模型的变更计划，不修改配置文件
+     */
+    async planConfigModel(specPath: string): Promise<ConfigModelPlan | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      return invoke<ConfigModelPlan>('plan_config_model', {
+        configPath: this.config.filePath,
+        specPath,
+      });
+    },
+
+    /**
+     * 按已审阅的计划修改配置文件并重新加载，配置或模型在审阅后变化时拒绝应用
+     */
+    async applyConfigModel(plan: ConfigModelPlan, specPath: string): Promise<{ success: boolean; message: string } | null> {
+      if (!this.config) {
+        return null;
+      }
+
+      const result = await invoke<{ success: boolean; message: string }>('apply_config_model', {
+        configPath: plan.configPath,
+        specPath,
+        expectedDigest: plan.digest,
+      });
+      if (result.success) {
+        await this.reloadConfig();
+      }
+      return result;
+    },
+
     /**
      * 重新加载配置文件
      */
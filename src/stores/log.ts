@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   FileChangeLogDetail,
   LogEntry,
@@ -18,6 +19,9 @@ const sanitizeRetentionDays = (days: number) => {
   return Math.min(365, Math.max(1, Math.round(days)));
 };
 
+/** 后端审计层写入日志后推送的事件 */
+const OPERATION_LOG_EVENT = 'operation-log-appended';
+
 const compareLogs = (left: LogEntry, right: LogEntry) => {
   const timeDelta = right.timestamp.getTime() - left.timestamp.getTime();
   if (timeDelta !== 0) {
@@ -48,6 +52,7 @@ const mergePersistedWithPendingLogs = (
 export const useLogStore = defineStore('log', () => {
   const logs = ref<LogEntry[]>([]);
   const isLoaded = ref(false);
+  let unlistenBackendLogs: UnlistenFn | null = null;
 
   const getRetentionDays = () =>
     sanitizeRetentionDays(useSettingsStore().settings.logRetentionDays);
@@ -68,6 +73,7 @@ export const useLogStore = defineStore('log', () => {
     timestamp: new Date(entry.timestampMs),
     kind: entry.kind,
     detail: entry.kind === 'file-change' ? entry.detail : undefined,
+    context: entry.context ?? null,
   }) as LogEntry;
 
   const applyLogs = (entries: LogEntry[], mergeWithExisting = false) => {
@@ -103,7 +109,19 @@ export const useLogStore = defineStore('log', () => {
     }
   };
 
+  const subscribeBackendLogs = async () => {
+    if (unlistenBackendLogs) {
+      return;
+    }
+
+    // 启停、端口释放、配置写盘等操作由后端自动记录，这里只负责同步到列表
+    unlistenBackendLogs = await listen<StoredLogEntry>(OPERATION_LOG_EVENT, (event) => {
+      applyLogs([fromStoredLog(event.payload)], true);
+    });
+  };
+
   const initialize = async () => {
+    await subscribeBackendLogs();
     await loadPersisted();
   };
 
@@ -121,12 +139,16 @@ export const useLogStore = defineStore('log', () => {
     detail,
   }) as LogEntry;
 
-  const persistEntry = async (entry: LogEntry) => {
+  const persistEntry = async (entry: LogEntry, command?: string) => {
     try {
-      await invoke('append_operation_log', {
+      const storedEntry = await invoke<StoredLogEntry>('append_operation_log', {
         entry: toStoredLog(entry),
         retentionDays: getRetentionDays(),
+        command: command ?? null,
       });
+      // 审计上下文由后端采集，回填到当前列表
+      entry.context = storedEntry.context ?? null;
+      logs.value = [...logs.value];
 
       if (entry.kind === 'file-change') {
         await loadPersisted();
@@ -136,21 +158,23 @@ export const useLogStore = defineStore('log', () => {
     }
   };
 
-  const appendEntry = (entry: LogEntry) => {
+  // command 为触发该日志的后端命令名，写入审计上下文
+  const appendEntry = (entry: LogEntry, command?: string) => {
     applyLogs([entry], true);
-    void persistEntry(entry);
+    void persistEntry(entry, command);
   };
 
-  const addLog = (level: LogLevel, summary: string) => {
-    appendEntry(createLogEntry(level, summary, 'operation'));
+  const addLog = (level: LogLevel, summary: string, command?: string) => {
+    appendEntry(createLogEntry(level, summary, 'operation'), command);
   };
 
   const recordFileChange = (
     level: LogLevel,
     summary: string,
     detail: FileChangeLogDetail,
+    command?: string,
   ) => {
-    appendEntry(createLogEntry(level, summary, 'file-change', detail));
+    appendEntry(createLogEntry(level, summary, 'file-change', detail), command);
   };
 
   const info = (summary: string) => addLog('info', summary);
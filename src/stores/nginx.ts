@@ -1,15 +1,22 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   NginxStatus,
+  NginxStatusChangedEvent,
   PermissionStatus,
   PortInspectionResult,
+  PortPreflightReport,
   ProcessOperationResult,
 } from '../types/nginx';
 import { eventBus, EVENTS } from '../composables/useEventBus';
 import type { NginxOperationResult } from '../composables/useEventBus';
 
+const NGINX_STATUS_CHANGED_EVENT = 'nginx-status-changed';
+
+type RawNginxStatus = { is_running: boolean; process_count: number; message: string };
+
 export const useNginxStore = defineStore('nginx', () => {
   const status = ref<NginxStatus>({
     isRunning: false,
@@ -27,8 +34,9 @@ export const useNginxStore = defineStore('nginx', () => {
     }
   };
 
-  const refreshStatus = async () => {
-    const result = await invoke<{ is_running: boolean; process_count: number; message: string }>('check_nginx_status');
+  let unlistenStatusChanges: UnlistenFn | null = null;
+
+  const applyStatus = (result: RawNginxStatus) => {
     status.value = {
       isRunning: result.is_running,
       processCount: result.process_count,
@@ -36,6 +44,21 @@ export const useNginxStore = defineStore('nginx', () => {
     };
   };
 
+  const refreshStatus = async () => {
+    applyStatus(await invoke<RawNginxStatus>('check_nginx_status'));
+  };
+
+  const subscribeStatusChanges = async () => {
+    if (unlistenStatusChanges) {
+      return;
+    }
+
+    // 进程启停、崩溃与 worker 数量变化由后端监听并推送，无需轮询
+    unlistenStatusChanges = await listen<NginxStatusChangedEvent>(NGINX_STATUS_CHANGED_EVENT, (event) => {
+      applyStatus(event.payload.status);
+    });
+  };
+
   const checkStatus = async () => {
     try {
       await runWithLoading(refreshStatus);
@@ -69,17 +92,17 @@ export const useNginxStore = defineStore('nginx', () => {
       });
   };
 
-  const start = (nginxPath: string) =>
-    dispatch('start', invoke('start_nginx', { nginxPath }));
+  const start = (nginxPath: string, configPath?: string) =>
+    dispatch('start', invoke('start_nginx', { nginxPath, configPath: configPath || null }));
 
   const stop = () =>
     dispatch('stop', invoke('stop_nginx'));
 
-  const restart = (nginxPath: string) =>
-    dispatch('restart', invoke('restart_nginx', { nginxPath }));
+  const restart = (nginxPath: string, configPath?: string) =>
+    dispatch('restart', invoke('restart_nginx', { nginxPath, configPath: configPath || null }));
 
-  const reload = (nginxPath: string) =>
-    dispatch('reload', invoke('reload_nginx', { nginxPath }));
+  const reload = (nginxPath: string, configPath?: string) =>
+    dispatch('reload', invoke('reload_nginx', { nginxPath, configPath: configPath || null }));
 
   const testConfig = (nginxPath: string) =>
     dispatch('test', invoke('test_nginx_config', { nginxPath }));
@@ -90,6 +113,9 @@ export const useNginxStore = defineStore('nginx', () => {
   const inspectPorts = (ports: number[]) =>
     invoke<PortInspectionResult[]>('inspect_ports', { ports });
 
+  const preflightPorts = (configPath?: string) =>
+    invoke<PortPreflightReport>('preflight_nginx_ports', { configPath: configPath || null });
+
   const terminateProcess = async (pid: number) => {
     const result = await invoke<ProcessOperationResult>('terminate_process', { pid });
     await refreshStatus().catch(() => undefined);
@@ -106,6 +132,7 @@ export const useNginxStore = defineStore('nginx', () => {
     status,
     isLoading,
     checkStatus,
+    subscribeStatusChanges,
     start,
     stop,
     restart,
@@ -113,6 +140,7 @@ export const useNginxStore = defineStore('nginx', () => {
     testConfig,
     checkPermissionStatus,
     inspectPorts,
+    preflightPorts,
     terminateProcess,
     releasePort,
   };
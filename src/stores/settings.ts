@@ -1,7 +1,7 @@
 import { defineStore } from 'pinia';
 import { ref } from 'vue';
 import { invoke } from '@tauri-apps/api/core';
-import type { AppSettings } from '../types/common';
+import type { AppSettings, LintRuleSetting } from '../types/common';
 import { eventBus, EVENTS } from '../composables/useEventBus';
 
 const sanitizeRetentionDays = (days: number) => {
@@ -20,6 +20,8 @@ export const useSettingsStore = defineStore('settings', () => {
     language: 'zh-CN',
     logRetentionDays: 30,
     autoReloadAfterSave: true,
+    lintRules: {},
+    certExpiryWarningDays: 30,
   });
 
   /** 标记设置是否已从后端加载完成，避免重复加载 */
@@ -35,6 +37,8 @@ export const useSettingsStore = defineStore('settings', () => {
         language: savedSettings.language || 'zh-CN',
         logRetentionDays: sanitizeRetentionDays(savedSettings.logRetentionDays || 30),
         autoReloadAfterSave: savedSettings.autoReloadAfterSave ?? true,
+        lintRules: savedSettings.lintRules || {},
+        certExpiryWarningDays: savedSettings.certExpiryWarningDays ?? 30,
       };
       isLoaded.value = true;
       eventBus.emit(EVENTS.SETTINGS_LOADED);
@@ -53,6 +57,8 @@ export const useSettingsStore = defineStore('settings', () => {
           language: settings.value.language,
           logRetentionDays: sanitizeRetentionDays(settings.value.logRetentionDays),
           autoReloadAfterSave: settings.value.autoReloadAfterSave,
+          lintRules: settings.value.lintRules,
+          certExpiryWarningDays: settings.value.certExpiryWarningDays,
         },
       });
     } catch (error) {
@@ -85,6 +91,16 @@ export const useSettingsStore = defineStore('settings', () => {
     void saveSettings();
   };
 
+  const updateLintRule = (ruleId: string, setting: LintRuleSetting) => {
+    settings.value.lintRules = { ...settings.value.lintRules, [ruleId]: setting };
+    void saveSettings();
+  };
+
+  const updateCertExpiryWarningDays = (days: number) => {
+    settings.value.certExpiryWarningDays = Math.max(1, Math.round(days));
+    void saveSettings();
+  };
+
   return {
     settings,
     isLoaded,
@@ -95,5 +111,7 @@ export const useSettingsStore = defineStore('settings', () => {
     updateTheme,
     updateLogRetentionDays,
     updateAutoReloadAfterSave,
+    updateLintRule,
+    updateCertExpiryWarningDays,
   };
 });
@@ -10,6 +10,15 @@ export interface AppSettings {
   language: 'zh-CN' | 'en-US';
   logRetentionDays: number;
   autoReloadAfterSave: boolean;
+  /** 按规则 id 覆盖 lint 规则的启用状态与级别 */
+  lintRules: Record<string, LintRuleSetting>;
+  /** 证书在该天数内过期时提醒 */
+  certExpiryWarningDays: number;
+}
+
+export interface LintRuleSetting {
+  disabled: boolean;
+  severity: 'error' | 'warning' | 'info' | null;
 }
 
 /**
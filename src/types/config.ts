@@ -52,6 +52,464 @@ export interface NginxConfig {
 
 export type ConfigSearchMode = 'port' | 'location' | 'keyword';
 
+/**
+ * 配置文件变化中涉及的 server 块
+ */
+export interface ServerBlockRef {
+  filePath: string;
+  id: string;
+  listen: string[];
+  serverName: string[];
+  enabled: boolean;
+  startLine: number;
+}
+
+/**
+ * 配置文件及 include 文件变化事件
+ */
+export interface ConfigFileChangedEvent {
+  configPath: string;
+  watchedFiles: string[];
+  changedFiles: string[];
+  summary: {
+    added: ServerBlockRef[];
+    removed: ServerBlockRef[];
+    changed: ServerBlockRef[];
+  };
+  parseErrors: string[];
+  diskDiffersFromRunning: boolean | null;
+  /** 变化全部来自应用自身的写入 */
+  ownWrite: boolean;
+  timestampMs: number;
+}
+
+export type ConfigDriftStatus = 'in-sync' | 'drifted' | 'unknown';
+
+/**
+ * 运行中配置与磁盘配置的差异
+ */
+export interface ConfigDriftReport {
+  status: ConfigDriftStatus;
+  message: string;
+  configPath: string;
+  appliedAtMs: number | null;
+  appliedDigest: string | null;
+  currentDigest: string;
+  files: Array<{
+    path: string;
+    kind: 'added' | 'removed' | 'modified';
+    diff: { label: string; before: string; after: string };
+  }>;
+}
+
+export type ServerConflictKind =
+  | 'duplicate-server-name'
+  | 'duplicate-default-server'
+  | 'name-overlap'
+  | 'disabled-server-conflict';
+
+/**
+ * listen / server_name 冲突诊断
+ */
+export interface ServerConflictDiagnostic {
+  kind: ServerConflictKind;
+  severity: LintSeverity;
+  address: string;
+  serverName: string | null;
+  message: string;
+  servers: ServerBlockRef[];
+}
+
+export interface ServerConflictReport {
+  configPath: string;
+  diagnostics: ServerConflictDiagnostic[];
+  errorCount: number;
+  warningCount: number;
+  parseErrors: string[];
+}
+
+/**
+ * 请求路由模拟结果
+ */
+export interface RouteSimulationResult {
+  scheme: string;
+  host: string;
+  port: number;
+  path: string;
+  query: string | null;
+  listenAddress: string | null;
+  candidates: ServerBlockRef[];
+  server: ServerBlock | null;
+  serverFilePath: string | null;
+  serverReason:
+    | 'exact-name'
+    | 'leading-wildcard'
+    | 'trailing-wildcard'
+    | 'regex'
+    | 'default-server'
+    | 'first-server'
+    | null;
+  matchedServerName: string | null;
+  location: LocationBlock | null;
+  locationReason: 'exact' | 'prefix-stop-regex' | 'regex' | 'longest-prefix' | null;
+  explanation: string[];
+  warnings: string[];
+  parseErrors: string[];
+}
+
+export type LintSeverity = 'error' | 'warning' | 'info';
+
+export interface LintRuleInfo {
+  id: string;
+  title: string;
+  description: string;
+  defaultSeverity: LintSeverity;
+  severity: LintSeverity;
+  enabled: boolean;
+  hasAutofix: boolean;
+}
+
+/**
+ * lint 检查结果，fix.serverText 为修复后的完整 server 文本
+ */
+export interface LintFinding {
+  ruleId: string;
+  severity: LintSeverity;
+  message: string;
+  filePath: string;
+  serverId: string;
+  serverName: string[];
+  locationId: string | null;
+  line: number;
+  fix: { description: string; serverText: string } | null;
+}
+
+export interface LintReport {
+  configPath: string;
+  rules: LintRuleInfo[];
+  findings: LintFinding[];
+  parseErrors: string[];
+}
+
+export type SecurityCheckStatus = 'pass' | 'warn' | 'fail' | 'not-applicable';
+
+export interface SecurityCheck {
+  id: string;
+  title: string;
+  status: SecurityCheckStatus;
+  weight: number;
+  detail: string;
+  recommendation: string | null;
+}
+
+export interface ServerSecurityAudit {
+  server: ServerBlockRef;
+  internetFacing: boolean;
+  tlsEnabled: boolean;
+  score: number;
+  grade: 'A' | 'B' | 'C' | 'D' | 'F';
+  checks: SecurityCheck[];
+}
+
+/**
+ * 安全审计报告
+ */
+export interface SecurityAuditReport {
+  configPath: string;
+  generatedAt: string;
+  servers: ServerSecurityAudit[];
+  averageScore: number;
+  failCount: number;
+  warnCount: number;
+  parseErrors: string[];
+}
+
+export type CertificateStatus = 'invalid' | 'expired' | 'not-yet-valid' | 'expiring-soon' | 'valid';
+
+export interface CertificateDetails {
+  subject: string;
+  commonName: string | null;
+  issuer: string;
+  selfSigned: boolean;
+  subjectAltNames: string[];
+  notBefore: string;
+  notAfter: string;
+  daysRemaining: number;
+  keyType: string;
+  chainLength: number;
+}
+
+export interface CertificateEntry {
+  certificatePath: string;
+  keyPath: string | null;
+  status: CertificateStatus;
+  certificate: CertificateDetails | null;
+  keyMatches: boolean | null;
+  servers: { server: ServerBlockRef; uncoveredNames: string[] }[];
+  warnings: string[];
+}
+
+/**
+ * TLS 证书清单
+ */
+export interface CertificateInventory {
+  configPath: string;
+  warningDays: number;
+  certificates: CertificateEntry[];
+  expiringCount: number;
+  expiredCount: number;
+  parseErrors: string[];
+}
+
+/**
+ * 开发证书生成结果
+ */
+export interface DevCertificateResult {
+  success: boolean;
+  message: string;
+  certificatePath: string;
+  keyPath: string;
+  subjectAltNames: string[];
+  notAfter: string;
+  caCertificatePath: string | null;
+  caCreated: boolean;
+}
+
+export type TlsPreset = 'modern' | 'intermediate' | 'legacy';
+
+/**
+ * 为 HTTP server 启用 HTTPS 的参数
+ */
+export interface EnableHttpsRequest {
+  serverId: string;
+  certificatePath: string;
+  keyPath: string;
+  preset: TlsPreset;
+  hsts: boolean;
+  /** 拆分出 80 端口的 301 跳转 server */
+  redirectHttp: boolean;
+}
+
+export type TemplateParameterKind = 'text' | 'hostList' | 'port' | 'number' | 'boolean' | 'path' | 'upstream';
+
+export interface TemplateParameter {
+  name: string;
+  label: string;
+  kind: TemplateParameterKind;
+  required: boolean;
+  default: string | null;
+  description: string | null;
+}
+
+/**
+ * Server 块模板，body 中 {{name}} 为参数占位，{{#name}}...{{/name}} 为条件区块
+ */
+export interface ServerTemplate {
+  id: string;
+  name: string;
+  description: string;
+  builtin: boolean;
+  parameters: TemplateParameter[];
+  body: string;
+}
+
+export interface DirectiveInput {
+  name: string;
+  value: string;
+}
+
+export interface LocationBlockInput {
+  path: string;
+  modifier: string | null;
+  directives: DirectiveInput[];
+}
+
+/**
+ * 反向代理向导参数，可选项为 null 时沿用 nginx 默认值
+ */
+export interface ProxyWizardRequest {
+  path: string;
+  modifier: string | null;
+  /** 目标地址或 upstream 名称 */
+  target: string;
+  /** 去掉 location 前缀后转发 */
+  stripPrefix: boolean;
+  websocket: boolean;
+  buffering: boolean | null;
+  requestBuffering: boolean | null;
+  clientMaxBodySize: string | null;
+  connectTimeout: string | null;
+  sendTimeout: string | null;
+  readTimeout: string | null;
+}
+
+export interface ProxyWizardResult {
+  location: LocationBlockInput;
+  /** 需要 http 级的 map $http_upgrade，缺失时应用时会自动添加 */
+  requiresUpgradeMap: boolean;
+  notes: string[];
+}
+
+export type ImportSourceFormat = 'apache' | 'caddy';
+
+export interface ImportIssue {
+  line: number;
+  directive: string;
+  reason: string;
+}
+
+export interface ServerBlockInput {
+  listen: string[];
+  serverName: string[];
+  directives: DirectiveInput[];
+}
+
+/**
+ * 从 Apache / Caddy 导入的站点，逐个审核后再添加
+ */
+export interface ImportedServer {
+  source: string;
+  line: number;
+  server: ServerBlockInput;
+  locations: LocationBlockInput[];
+  serverText: string;
+  issues: ImportIssue[];
+}
+
+export interface ImportReport {
+  format: ImportSourceFormat;
+  sourcePath: string;
+  servers: ImportedServer[];
+  issues: ImportIssue[];
+}
+
+export interface ModelDirective {
+  name: string;
+  value?: string;
+}
+
+export interface LocationModel {
+  modifier?: string;
+  path: string;
+  directives: ModelDirective[];
+}
+
+export interface ServerModel {
+  /** 与现有配置匹配的标识，缺省为「首个域名@首个监听」 */
+  key?: string;
+  enabled: boolean;
+  listen: string[];
+  serverName: string[];
+  directives: ModelDirective[];
+  locations: LocationModel[];
+}
+
+export interface UpstreamModel {
+  name: string;
+  directives: ModelDirective[];
+}
+
+/**
+ * 声明式配置模型（JSON / YAML）
+ */
+export interface ConfigModel {
+  version: number;
+  /** 删除模型中未列出的 server / upstream */
+  prune: boolean;
+  globalDirectives?: ModelDirective[];
+  upstreams: UpstreamModel[];
+  servers: ServerModel[];
+}
+
+export type ModelChangeKind = 'add' | 'update' | 'remove';
+
+export interface ModelChange {
+  kind: ModelChangeKind;
+  target: 'server' | 'upstream';
+  key: string;
+  details: string[];
+}
+
+export interface ConfigModelPlan {
+  configPath: string;
+  changes: ModelChange[];
+  unmanaged: string[];
+  hasChanges: boolean;
+  newContent: string;
+  validation: PlanValidation;
+  /** 配置原文与变更后内容的摘要，应用时用于确认计划未过期 */
+  digest: string;
+  /** 含有 server / upstream、但不在模型范围内的 include 文件 */
+  uncoveredFiles: string[];
+}
+
+export interface ConfigModelExport {
+  outputPath: string;
+  uncoveredFiles: string[];
+}
+
+export type PlanChangeKind = 'added' | 'removed' | 'modified';
+
+export interface PlannedFileChange {
+  filePath: string;
+  addedLines: number;
+  removedLines: number;
+}
+
+export interface PlannedLocationChange {
+  server: ServerBlockRef;
+  kind: PlanChangeKind;
+  modifier: string | null;
+  path: string;
+}
+
+export interface PlanValidation {
+  performed: boolean;
+  success: boolean;
+  message: string;
+}
+
+export interface ReloadImpact {
+  requiresReload: boolean;
+  listenersAdded: string[];
+  listenersRemoved: string[];
+  serversEnabled: ServerBlockRef[];
+  serversDisabled: ServerBlockRef[];
+}
+
+/**
+ * 编辑操作的结构化变更计划
+ */
+export interface ChangePlan {
+  configPath: string;
+  files: PlannedFileChange[];
+  servers: {
+    added: ServerBlockRef[];
+    removed: ServerBlockRef[];
+    changed: ServerBlockRef[];
+  };
+  locations: PlannedLocationChange[];
+  diff: string;
+  validation: PlanValidation;
+  reloadImpact: ReloadImpact;
+  newContent: string;
+}
+
+/**
+ * 添加反向代理 location 的变更计划
+ */
+export interface ProxyLocationPlan {
+  plan: ChangePlan;
+  /** server 位于 include 文件且主配置缺少 map $http_upgrade 时，对主配置的修改 */
+  upgradeMapPlan: ChangePlan | null;
+}
+
+export interface ConfigWatchStatus {
+  configPath: string;
+  watchedFiles: string[];
+  diskDiffersFromRunning: boolean | null;
+}
+
 /**
  * 配置编辑表单数据 - Server
  */
@@ -10,6 +10,29 @@ export interface NginxStatus {
   lastOperation?: string;
 }
 
+/**
+ * Nginx 进程快照
+ */
+export interface NginxProcessSnapshot {
+  masterPid?: number | null;
+  workerPids: number[];
+  processCount: number;
+}
+
+export type NginxStatusChangeKind = 'started' | 'stopped' | 'crashed' | 'restarted' | 'workers-changed';
+
+/**
+ * 后端状态监听推送的进程变化事件
+ */
+export interface NginxStatusChangedEvent {
+  kind: NginxStatusChangeKind;
+  expected: boolean;
+  status: { is_running: boolean; process_count: number; message: string };
+  snapshot: NginxProcessSnapshot;
+  previous: NginxProcessSnapshot;
+  timestampMs: number;
+}
+
 export interface PermissionStatus {
   isWindows: boolean;
   isElevated: boolean;
@@ -43,6 +66,32 @@ export interface ProcessOperationResult {
   requiresElevation: boolean;
 }
 
+export type PortPreflightStatus = 'free' | 'owned-by-nginx' | 'conflict' | 'unknown';
+
+export interface PortPreflightEntry {
+  port: number;
+  protocol: 'tcp' | 'udp';
+  listen: string[];
+  servers: string[];
+  status: PortPreflightStatus;
+  owners: PortProcessInfo[];
+  canRelease: boolean;
+  message: string;
+}
+
+/**
+ * 启动/重载前的端口预检报告
+ */
+export interface PortPreflightReport {
+  ok: boolean;
+  configPath: string;
+  nginxRunning: boolean;
+  entries: PortPreflightEntry[];
+  conflicts: number[];
+  parseErrors: string[];
+  message: string;
+}
+
 /**
  * Nginx 配置校验结果
  */
@@ -77,6 +126,24 @@ export interface FileChangeLogDetail {
   locationDiffs: FileChangeScopeDiff[];
 }
 
+export interface InstanceProfile {
+  nginxPath: string;
+  configPath: string;
+}
+
+/**
+ * 日志审计上下文（由后端采集）
+ */
+export interface LogContext {
+  osUser: string;
+  hostname: string;
+  instanceProfile: InstanceProfile;
+  nginxVersion?: string | null;
+  command: string;
+  durationMs?: number | null;
+  exitStatus?: number | null;
+}
+
 /**
  * 操作日志条目
  */
@@ -86,6 +153,7 @@ interface BaseLogEntry {
   level: LogLevel;
   kind: LogKind;
   summary: string;
+  context?: LogContext | null;
 }
 
 export interface OperationLogEntry extends BaseLogEntry {
@@ -107,6 +175,7 @@ export interface StoredLogEntry {
   kind: LogKind;
   summary: string;
   detail?: FileChangeLogDetail;
+  context?: LogContext | null;
 }
 
 /**
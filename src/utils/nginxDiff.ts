@@ -1,4 +1,4 @@
-import type { ServerBlock } from '@/types/config';
+import type { ChangePlan, ServerBlock } from '@/types/config';
 import type { FileChangeLogDetail, FileChangeScopeDiff } from '@/types/nginx';
 
 interface ParsedLocationBlock {
@@ -165,6 +165,27 @@ export const buildFileChangeSummary = (
     : `${operationLabel} · ${normalizedPath}`;
 };
 
+export const summarizeChangePlan = (plan: ChangePlan) => {
+  const parts = plan.files.map(
+    (file) => `${file.filePath}: +${file.addedLines} -${file.removedLines}`,
+  );
+  const { added, removed, changed } = plan.servers;
+  if (added.length || removed.length || changed.length) {
+    parts.push(`server 新增 ${added.length} / 删除 ${removed.length} / 修改 ${changed.length}`);
+  }
+  if (plan.locations.length > 0) {
+    parts.push(`location 变更 ${plan.locations.length} 处`);
+  }
+  const { listenersAdded, listenersRemoved } = plan.reloadImpact;
+  if (listenersAdded.length > 0) {
+    parts.push(`新增监听 ${listenersAdded.join(', ')}`);
+  }
+  if (listenersRemoved.length > 0) {
+    parts.push(`移除监听 ${listenersRemoved.join(', ')}`);
+  }
+  return parts.length > 0 ? parts.join('\n') : '配置无变化';
+};
+
 export const createFileChangeDetail = ({
   operationLabel,
   configPath,